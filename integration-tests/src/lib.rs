@@ -0,0 +1,223 @@
+//! task_1 / task_2 的进程内测试工具
+//!
+//! 每个测试进程先用 `cargo build-sbf` 增量构建两个程序（源码未变时不重新编译），再从 `target/deploy/*.so`
+//! 加载，连同仓库中的
+//! `task_2/mpl_token_metadata.so` 一起放进 solana-program-test 的 bank，不需要启动 localnet。
+//! SPL Token、Token-2022 和 ATA 程序由 solana-program-test 内置。
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Mutex,
+};
+
+use anchor_lang::{solana_program::program_pack::Pack, AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token::spl_token::{native_mint, state::Mint as SplMint},
+    token_interface::TokenAccount,
+};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    bpf_loader, bpf_loader_upgradeable,
+    bpf_loader_upgradeable::UpgradeableLoaderState,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::{Transaction, TransactionError},
+};
+
+pub use anchor_spl::metadata::mpl_token_metadata::ID as METADATA_PROGRAM_ID;
+pub use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+pub use native_mint::ID as NATIVE_MINT;
+pub use solana_sdk::native_token::LAMPORTS_PER_SOL;
+
+// 本进程中已经构建过的程序；多个测试并发加载同一程序时只构建一次
+static BUILT_PROGRAMS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+// 读取仓库中的程序文件
+fn read_program(relative: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(relative);
+    std::fs::read(&path).unwrap_or_else(|err| panic!("{}: {}", path.display(), err))
+}
+
+// 读取 <workspace>/target/deploy/<name>.so；每个进程第一次加载前都执行 cargo build-sbf，
+// 由 cargo 判断源码是否变化，避免测试悄悄跑在旧的程序产物上，也不需要事先 anchor build
+fn read_deployed_program(workspace: &str, name: &str) -> Vec<u8> {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..").join(workspace);
+    let path = root.join("target/deploy").join(format!("{name}.so"));
+
+    let mut built = BUILT_PROGRAMS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if !built.iter().any(|program| program == name) {
+        build_sbf(&root, name);
+        built.push(name.to_string());
+    }
+    std::fs::read(&path).unwrap_or_else(|err| panic!("{}: {}", path.display(), err))
+}
+
+// 构建 <root>/programs/<name>，输出到 <root>/target/deploy
+fn build_sbf(root: &Path, name: &str) {
+    let available = Command::new("cargo")
+        .args(["build-sbf", "--version"])
+        .output()
+        .is_ok_and(|output| output.status.success());
+    assert!(
+        available,
+        "`cargo build-sbf` is not available; install the Solana CLI tool suite \
+         (https://docs.solanalabs.com/cli/install) to build {name} for the integration tests"
+    );
+
+    let status = Command::new("cargo")
+        .arg("build-sbf")
+        .arg("--manifest-path")
+        .arg(root.join("programs").join(name).join("Cargo.toml"))
+        .arg("--sbf-out-dir")
+        .arg(root.join("target/deploy"))
+        .status()
+        .unwrap_or_else(|err| panic!("cargo build-sbf for {name}: {err}"));
+    assert!(status.success(), "cargo build-sbf for {name} failed: {status}");
+}
+
+// 以可升级程序的形式加入bank，program_data 中记录升级权限账户
+pub fn add_upgradeable_program(pt: &mut ProgramTest, program_id: Pubkey, elf: &[u8], upgrade_authority: Pubkey) {
+    let rent = Rent::default();
+    let (programdata_address, _) = Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+
+    let program_data = bincode::serialize(&UpgradeableLoaderState::Program { programdata_address }).unwrap();
+    pt.add_account(
+        program_id,
+        Account {
+            lamports: rent.minimum_balance(program_data.len()),
+            data: program_data,
+            owner: bpf_loader_upgradeable::id(),
+            executable: true,
+            rent_epoch: 0,
+        },
+    );
+
+    let mut data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+        slot: 0,
+        upgrade_authority_address: Some(upgrade_authority),
+    })
+    .unwrap();
+    data.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+    data.extend_from_slice(elf);
+    pt.add_account(
+        programdata_address,
+        Account {
+            lamports: rent.minimum_balance(data.len()),
+            data,
+            owner: bpf_loader_upgradeable::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+// 普通系统账户，用于给测试钱包预置 SOL
+pub fn add_wallet(pt: &mut ProgramTest, wallet: &Pubkey, lamports: u64) {
+    pt.add_account(
+        *wallet,
+        Account { lamports, data: vec![], owner: system_program::id(), executable: false, rent_epoch: 0 },
+    );
+}
+
+/** 加载 task_1 的测试环境，upgrade_authority 为 task_1 的升级权限账户（配置管理员） */
+pub fn task_1_program_test(upgrade_authority: Pubkey) -> ProgramTest {
+    let mut pt = ProgramTest::default();
+    pt.prefer_bpf(true);
+    add_upgradeable_program(&mut pt, task_1::ID, &read_deployed_program("task_1", "task_1"), upgrade_authority);
+    pt
+}
+
+/** 加载 task_2 和 Metaplex 的测试环境，upgrade_authority 为 task_2 的升级权限账户（白名单和配置管理员） */
+pub fn task_2_program_test(upgrade_authority: Pubkey) -> ProgramTest {
+    let mut pt = ProgramTest::default();
+    pt.prefer_bpf(true);
+    add_upgradeable_program(&mut pt, task_2::ID, &read_deployed_program("task_2", "task_2"), upgrade_authority);
+
+    let metadata = read_program("../task_2/mpl_token_metadata.so");
+    pt.add_account(
+        METADATA_PROGRAM_ID,
+        Account {
+            lamports: Rent::default().minimum_balance(metadata.len()),
+            data: metadata,
+            owner: bpf_loader::id(),
+            executable: true,
+            rent_epoch: 0,
+        },
+    );
+
+    // WSOL mint，deposit_spl_native / withdraw_spl_native 使用
+    let mut data = vec![0; SplMint::LEN];
+    SplMint { decimals: native_mint::DECIMALS, is_initialized: true, ..SplMint::default() }.pack_into_slice(&mut data);
+    pt.add_account(
+        NATIVE_MINT,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: TOKEN_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    pt
+}
+
+/** 构造 anchor 指令 */
+pub fn anchor_ix(program_id: Pubkey, accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction { program_id, accounts: accounts.to_account_metas(None), data: args.data() }
+}
+
+/** 发送交易，ctx.payer 付手续费，extra_signers 为其他签名账户 */
+pub async fn process(
+    ctx: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    extra_signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    // metaplex CPI 计算量较大
+    let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(400_000)];
+    instructions.extend_from_slice(ixs);
+
+    // 每笔交易使用新的 blockhash，相同指令重复发送时不会被当作重复交易
+    let blockhash = ctx.get_new_latest_blockhash().await?;
+    let mut signers: Vec<&Keypair> = vec![&ctx.payer];
+    signers.extend_from_slice(extra_signers);
+    let tx = Transaction::new_signed_with_payer(&instructions, Some(&ctx.payer.pubkey()), &signers, blockhash);
+    ctx.banks_client.process_transaction(tx).await
+}
+
+/** 断言交易因指定错误码失败，code 为 anchor 错误码（自定义错误从 6000 开始） */
+pub fn assert_error(result: Result<(), BanksClientError>, code: u32) {
+    match result.expect_err("transaction should fail").unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(actual)) => {
+            assert_eq!(actual, code, "expected error {code}, got {actual}")
+        }
+        other => panic!("expected custom error {code}, got {other:?}"),
+    }
+}
+
+/** 读取 anchor 账户 */
+pub async fn fetch<T: AccountDeserialize>(ctx: &mut ProgramTestContext, address: Pubkey) -> T {
+    let account = ctx.banks_client.get_account(address).await.unwrap().expect("account not found");
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+/** 读取账户 lamports，账户不存在时为 0 */
+pub async fn lamports(ctx: &mut ProgramTestContext, address: Pubkey) -> u64 {
+    ctx.banks_client.get_account(address).await.unwrap().map(|a| a.lamports).unwrap_or(0)
+}
+
+/** 读取代币账户余额 */
+pub async fn token_balance(ctx: &mut ProgramTestContext, address: Pubkey) -> u64 {
+    fetch::<TokenAccount>(ctx, address).await.amount
+}
+
+/** SPL Token 程序下的 ATA 地址 */
+pub fn ata(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(wallet, mint, &TOKEN_PROGRAM_ID)
+}
@@ -0,0 +1,462 @@
+use anchor_lang::error::ErrorCode;
+use integration_tests::*;
+use anchor_lang::{AccountSerialize, Discriminator, Space};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_program,
+};
+use task_1::{ConfigParams, CustomError, StackAccount, LEGACY_STACK_ACCOUNT_SPACES, STACK_ACCOUNT_VERSION};
+
+// 用户的 SOL 质押账户与数据账户
+fn stack_pdas(user: &Pubkey) -> (Pubkey, Pubkey) {
+    let (vault, _) = Pubkey::find_program_address(&[b"stack", user.as_ref()], &task_1::ID);
+    let (ledger, _) = Pubkey::find_program_address(&[user.as_ref()], &task_1::ID);
+    (vault, ledger)
+}
+
+fn config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], &task_1::ID).0
+}
+
+fn treasury_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"treasury"], &task_1::ID).0
+}
+
+fn initialize_config_ix(authority: &Pubkey) -> Instruction {
+    let (program_data, _) = Pubkey::find_program_address(&[task_1::ID.as_ref()], &bpf_loader_upgradeable::id());
+    anchor_ix(
+        task_1::ID,
+        task_1::accounts::InitializeConfig {
+            authority: *authority,
+            config: config_pda(),
+            program: task_1::ID,
+            program_data,
+            system_program: system_program::id(),
+        },
+        task_1::instruction::InitializeConfig {
+            params: ConfigParams { emergency: false, deposit_fee_bps: 0, withdraw_fee_bps: 0 },
+        },
+    )
+}
+
+fn set_paused_ix(admin: &Pubkey, paused: bool) -> Instruction {
+    anchor_ix(
+        task_1::ID,
+        task_1::accounts::UpdateConfig { admin: *admin, config: config_pda() },
+        task_1::instruction::SetPaused { paused },
+    )
+}
+
+fn set_emergency_ix(admin: &Pubkey, emergency: bool) -> Instruction {
+    anchor_ix(
+        task_1::ID,
+        task_1::accounts::UpdateConfig { admin: *admin, config: config_pda() },
+        task_1::instruction::UpdateConfig {
+            params: ConfigParams { emergency, deposit_fee_bps: 0, withdraw_fee_bps: 0 },
+        },
+    )
+}
+
+fn set_fees_ix(admin: &Pubkey, deposit_fee_bps: u16, withdraw_fee_bps: u16) -> Instruction {
+    anchor_ix(
+        task_1::ID,
+        task_1::accounts::UpdateConfig { admin: *admin, config: config_pda() },
+        task_1::instruction::UpdateConfig {
+            params: ConfigParams { emergency: false, deposit_fee_bps, withdraw_fee_bps },
+        },
+    )
+}
+
+fn collect_fees_ix(admin: &Pubkey, recipient: &Pubkey) -> Instruction {
+    anchor_ix(
+        task_1::ID,
+        task_1::accounts::CollectFees {
+            admin: *admin,
+            config: config_pda(),
+            treasury: treasury_pda(),
+            recipient: *recipient,
+            system_program: system_program::id(),
+        },
+        task_1::instruction::CollectFees {},
+    )
+}
+
+fn propose_admin_ix(admin: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    anchor_ix(
+        task_1::ID,
+        task_1::accounts::UpdateConfig { admin: *admin, config: config_pda() },
+        task_1::instruction::ProposeAdmin { new_admin: *new_admin },
+    )
+}
+
+fn accept_admin_ix(pending_admin: &Pubkey) -> Instruction {
+    anchor_ix(
+        task_1::ID,
+        task_1::accounts::AcceptAdmin { pending_admin: *pending_admin, config: config_pda() },
+        task_1::instruction::AcceptAdmin {},
+    )
+}
+
+fn deposit_accounts(user: &Pubkey) -> task_1::accounts::Deposit {
+    let (stack_account_pda, stack_account) = stack_pdas(user);
+    task_1::accounts::Deposit {
+        user: *user,
+        stack_account_pda,
+        stack_account,
+        config: config_pda(),
+        treasury: treasury_pda(),
+        system_program: system_program::id(),
+    }
+}
+
+fn deposit_ix(user: &Pubkey, amount: u64) -> Instruction {
+    anchor_ix(task_1::ID, deposit_accounts(user), task_1::instruction::Deposit { amount })
+}
+
+fn deposit_locked_ix(user: &Pubkey, amount: u64, lock_duration: i64) -> Instruction {
+    anchor_ix(task_1::ID, deposit_accounts(user), task_1::instruction::DepositLocked { amount, lock_duration })
+}
+
+fn withdraw_accounts(user: &Pubkey) -> task_1::accounts::Withdraw {
+    let (stack_account_pda, stack_account) = stack_pdas(user);
+    task_1::accounts::Withdraw {
+        user: *user,
+        stack_account_pda,
+        stack_account,
+        config: config_pda(),
+        treasury: treasury_pda(),
+        system_program: system_program::id(),
+    }
+}
+
+fn withdraw_ix(user: &Pubkey) -> Instruction {
+    anchor_ix(task_1::ID, withdraw_accounts(user), task_1::instruction::Withdraw {})
+}
+
+fn withdraw_amount_ix(user: &Pubkey, amount: u64) -> Instruction {
+    anchor_ix(task_1::ID, withdraw_accounts(user), task_1::instruction::WithdrawAmount { amount })
+}
+
+fn migrate_position_ix(user: &Pubkey) -> Instruction {
+    let (_, stack_account) = stack_pdas(user);
+    anchor_ix(
+        task_1::ID,
+        task_1::accounts::MigratePosition { user: *user, stack_account, system_program: system_program::id() },
+        task_1::instruction::MigratePosition {},
+    )
+}
+
+// admin 为 task_1 的升级权限账户，初始化全局配置后成为管理员
+async fn setup() -> (ProgramTestContext, Keypair) {
+    let admin = Keypair::new();
+    let mut pt = task_1_program_test(admin.pubkey());
+    add_wallet(&mut pt, &admin.pubkey(), 10 * LAMPORTS_PER_SOL);
+    let mut ctx = pt.start_with_context().await;
+
+    process(&mut ctx, &[initialize_config_ix(&admin.pubkey())], &[&admin]).await.unwrap();
+    (ctx, admin)
+}
+
+#[tokio::test]
+async fn deposit_and_withdraw() {
+    let (mut ctx, _) = setup().await;
+    let user = ctx.payer.pubkey();
+    let (vault, ledger) = stack_pdas(&user);
+    // 质押账户创建时由用户支付的租金
+    let vault_rent = Rent::default().minimum_balance(0);
+
+    process(&mut ctx, &[deposit_ix(&user, LAMPORTS_PER_SOL)], &[]).await.unwrap();
+    process(&mut ctx, &[deposit_ix(&user, LAMPORTS_PER_SOL / 2)], &[]).await.unwrap();
+
+    let stack_account: StackAccount = fetch(&mut ctx, ledger).await;
+    assert_eq!(stack_account.balance, LAMPORTS_PER_SOL * 3 / 2);
+    assert_eq!(lamports(&mut ctx, vault).await, vault_rent + LAMPORTS_PER_SOL * 3 / 2);
+
+    process(&mut ctx, &[withdraw_amount_ix(&user, LAMPORTS_PER_SOL)], &[]).await.unwrap();
+    let stack_account: StackAccount = fetch(&mut ctx, ledger).await;
+    assert_eq!(stack_account.balance, LAMPORTS_PER_SOL / 2);
+
+    process(&mut ctx, &[withdraw_ix(&user)], &[]).await.unwrap();
+    let stack_account: StackAccount = fetch(&mut ctx, ledger).await;
+    assert_eq!(stack_account.balance, 0);
+    assert_eq!(lamports(&mut ctx, vault).await, vault_rent);
+}
+
+#[tokio::test]
+async fn withdraw_empty_position_fails() {
+    let (mut ctx, _) = setup().await;
+    let user = ctx.payer.pubkey();
+
+    process(&mut ctx, &[deposit_ix(&user, LAMPORTS_PER_SOL)], &[]).await.unwrap();
+    process(&mut ctx, &[withdraw_ix(&user)], &[]).await.unwrap();
+
+    let result = process(&mut ctx, &[withdraw_ix(&user)], &[]).await;
+    assert_error(result, CustomError::InsufficientBalance.into());
+    let result = process(&mut ctx, &[withdraw_amount_ix(&user, 1)], &[]).await;
+    assert_error(result, CustomError::InsufficientBalance.into());
+}
+
+#[tokio::test]
+async fn withdraw_amount_above_balance_fails() {
+    let (mut ctx, _) = setup().await;
+    let user = ctx.payer.pubkey();
+
+    process(&mut ctx, &[deposit_ix(&user, LAMPORTS_PER_SOL)], &[]).await.unwrap();
+
+    let result = process(&mut ctx, &[withdraw_amount_ix(&user, LAMPORTS_PER_SOL + 1)], &[]).await;
+    assert_error(result, CustomError::WithdrawAmountExceedsBalance.into());
+}
+
+#[tokio::test]
+async fn withdraw_from_foreign_position_fails() {
+    let (mut ctx, _) = setup().await;
+    let user = ctx.payer.pubkey();
+    let attacker = Keypair::new();
+
+    process(&mut ctx, &[deposit_ix(&user, LAMPORTS_PER_SOL)], &[]).await.unwrap();
+
+    // 攻击者签名，但传入其他用户的数据账户
+    let (_, victim_ledger) = stack_pdas(&user);
+    let mut accounts = withdraw_accounts(&attacker.pubkey());
+    accounts.stack_account = victim_ledger;
+    let ix = anchor_ix(task_1::ID, accounts, task_1::instruction::Withdraw {});
+
+    let result = process(&mut ctx, &[ix], &[&attacker]).await;
+    assert_error(result, ErrorCode::ConstraintSeeds.into());
+}
+
+#[tokio::test]
+async fn deposit_locked_rejects_an_unlocked_balance() {
+    let (mut ctx, _) = setup().await;
+    let user = ctx.payer.pubkey();
+    let (_, ledger) = stack_pdas(&user);
+
+    // 未锁定的余额不能被锁定，提取后才能锁仓
+    process(&mut ctx, &[deposit_ix(&user, LAMPORTS_PER_SOL)], &[]).await.unwrap();
+    let result = process(&mut ctx, &[deposit_locked_ix(&user, LAMPORTS_PER_SOL, 3600)], &[]).await;
+    assert_error(result, CustomError::UnlockedBalance.into());
+
+    process(&mut ctx, &[withdraw_ix(&user)], &[]).await.unwrap();
+    process(&mut ctx, &[deposit_locked_ix(&user, LAMPORTS_PER_SOL, 3600)], &[]).await.unwrap();
+    // 锁定期内不能未锁定充值，只能追加锁仓
+    let result = process(&mut ctx, &[deposit_ix(&user, LAMPORTS_PER_SOL)], &[]).await;
+    assert_error(result, CustomError::DepositIntoLockedPosition.into());
+    // 锁定期内可以追加锁仓
+    process(&mut ctx, &[deposit_locked_ix(&user, LAMPORTS_PER_SOL / 2, 7200)], &[]).await.unwrap();
+    let position: StackAccount = fetch(&mut ctx, ledger).await;
+    assert_eq!(position.balance, LAMPORTS_PER_SOL * 3 / 2);
+    assert_eq!(position.lock_end - position.lock_start, 7200);
+    let result = process(&mut ctx, &[withdraw_ix(&user)], &[]).await;
+    assert_error(result, CustomError::PositionLocked.into());
+}
+
+#[tokio::test]
+async fn pause_blocks_deposits_and_withdrawals() {
+    let (mut ctx, admin) = setup().await;
+    let user = ctx.payer.pubkey();
+
+    process(&mut ctx, &[deposit_ix(&user, LAMPORTS_PER_SOL)], &[]).await.unwrap();
+    process(&mut ctx, &[set_paused_ix(&admin.pubkey(), true)], &[&admin]).await.unwrap();
+
+    let result = process(&mut ctx, &[deposit_ix(&user, 1)], &[]).await;
+    assert_error(result, CustomError::ProgramPaused.into());
+    let result = process(&mut ctx, &[withdraw_ix(&user)], &[]).await;
+    assert_error(result, CustomError::ProgramPaused.into());
+
+    process(&mut ctx, &[set_paused_ix(&admin.pubkey(), false)], &[&admin]).await.unwrap();
+    process(&mut ctx, &[withdraw_ix(&user)], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn emergency_mode_allows_only_withdrawals() {
+    let (mut ctx, admin) = setup().await;
+    let user = ctx.payer.pubkey();
+
+    process(&mut ctx, &[deposit_ix(&user, LAMPORTS_PER_SOL)], &[]).await.unwrap();
+    process(&mut ctx, &[set_emergency_ix(&admin.pubkey(), true)], &[&admin]).await.unwrap();
+
+    let result = process(&mut ctx, &[deposit_ix(&user, 1)], &[]).await;
+    assert_error(result, CustomError::DepositsDisabled.into());
+    process(&mut ctx, &[withdraw_ix(&user)], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn withdraw_works_before_config_is_initialized() {
+    let admin = Keypair::new();
+    let mut ctx = task_1_program_test(admin.pubkey()).start_with_context().await;
+    let user = ctx.payer.pubkey();
+    let (vault, ledger) = stack_pdas(&user);
+    let (_, vault_bump) = Pubkey::find_program_address(&[b"stack", user.as_ref()], &task_1::ID);
+    let (_, ledger_bump) = Pubkey::find_program_address(&[user.as_ref()], &task_1::ID);
+    let vault_rent = Rent::default().minimum_balance(0);
+
+    // 程序升级前已有的仓位，管理员还没有调用 initialize_config
+    let mut data = Vec::new();
+    StackAccount {
+        balance: LAMPORTS_PER_SOL,
+        stack_account_pump: ledger_bump,
+        stack_account_pda_pump: vault_bump,
+        lock_start: 0,
+        lock_end: 0,
+        version: STACK_ACCOUNT_VERSION,
+        reserved: [0; 64],
+    }
+    .try_serialize(&mut data)
+    .unwrap();
+    let position = Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: task_1::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    ctx.set_account(&ledger, &position.into());
+    let vault_account = Account { lamports: vault_rent + LAMPORTS_PER_SOL, ..Account::default() };
+    ctx.set_account(&vault, &vault_account.into());
+
+    // 缺少配置账户时按未暂停、不收手续费处理，充值仍需先初始化配置
+    let result = process(&mut ctx, &[deposit_ix(&user, 1)], &[]).await;
+    assert_error(result, ErrorCode::AccountNotInitialized.into());
+    let before = lamports(&mut ctx, user).await;
+    process(&mut ctx, &[withdraw_ix(&user)], &[]).await.unwrap();
+    let stack_account: StackAccount = fetch(&mut ctx, ledger).await;
+    assert_eq!(stack_account.balance, 0);
+    assert_eq!(lamports(&mut ctx, vault).await, vault_rent);
+    assert!(lamports(&mut ctx, user).await > before + LAMPORTS_PER_SOL / 2);
+}
+
+#[tokio::test]
+async fn config_changes_require_admin() {
+    let (mut ctx, _) = setup().await;
+    let user = ctx.payer.pubkey();
+
+    let result = process(&mut ctx, &[set_paused_ix(&user, true)], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+    let result = process(&mut ctx, &[set_emergency_ix(&user, true)], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+}
+
+#[tokio::test]
+async fn admin_handover_takes_two_steps() {
+    let (mut ctx, admin) = setup().await;
+    let new_admin = Keypair::new();
+    let stranger = ctx.payer.pubkey();
+
+    process(&mut ctx, &[propose_admin_ix(&admin.pubkey(), &new_admin.pubkey())], &[&admin]).await.unwrap();
+    // 只有被提名的账户可以接受
+    let result = process(&mut ctx, &[accept_admin_ix(&stranger)], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+    // 接受前管理员不变
+    process(&mut ctx, &[set_paused_ix(&admin.pubkey(), false)], &[&admin]).await.unwrap();
+
+    process(&mut ctx, &[accept_admin_ix(&new_admin.pubkey())], &[&new_admin]).await.unwrap();
+    let config: task_1::Config = fetch(&mut ctx, config_pda()).await;
+    assert_eq!(config.admin, new_admin.pubkey());
+    assert_eq!(config.pending_admin, Pubkey::default());
+
+    let result = process(&mut ctx, &[set_paused_ix(&admin.pubkey(), true)], &[&admin]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+    process(&mut ctx, &[set_paused_ix(&new_admin.pubkey(), true)], &[&new_admin]).await.unwrap();
+}
+
+#[tokio::test]
+async fn fees_are_routed_to_treasury_and_collected() {
+    let (mut ctx, admin) = setup().await;
+    let user = ctx.payer.pubkey();
+    let (_, ledger) = stack_pdas(&user);
+    let treasury_rent = Rent::default().minimum_balance(0);
+
+    // 充值 1%、提现 0.5%，向上取整
+    process(&mut ctx, &[set_fees_ix(&admin.pubkey(), 100, 50)], &[&admin]).await.unwrap();
+    process(&mut ctx, &[deposit_ix(&user, 10_001)], &[]).await.unwrap();
+    let position: StackAccount = fetch(&mut ctx, ledger).await;
+    assert_eq!(position.balance, 10_001 - 101);
+    assert_eq!(lamports(&mut ctx, treasury_pda()).await, treasury_rent + 101);
+
+    process(&mut ctx, &[withdraw_amount_ix(&user, 1_000)], &[]).await.unwrap();
+    let position: StackAccount = fetch(&mut ctx, ledger).await;
+    assert_eq!(position.balance, 10_001 - 101 - 1_000);
+    assert_eq!(lamports(&mut ctx, treasury_pda()).await, treasury_rent + 101 + 5);
+
+    // 只有管理员可以提取，金库保留租金
+    let recipient = Pubkey::new_unique();
+    let result = process(&mut ctx, &[collect_fees_ix(&user, &recipient)], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+    process(&mut ctx, &[collect_fees_ix(&admin.pubkey(), &admin.pubkey())], &[&admin]).await.unwrap();
+    assert_eq!(lamports(&mut ctx, treasury_pda()).await, treasury_rent);
+    let result = process(&mut ctx, &[collect_fees_ix(&admin.pubkey(), &admin.pubkey())], &[&admin]).await;
+    assert_error(result, CustomError::NoFeesToCollect.into());
+}
+
+// 按旧版本的字段顺序手工编码仓位：0 为初始的 balance + 两个bump，1 增加锁定时间
+fn legacy_position_data(version: usize, balance: u64, position_bump: u8, vault_bump: u8) -> Vec<u8> {
+    let mut data = StackAccount::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&balance.to_le_bytes());
+    data.extend_from_slice(&[position_bump, vault_bump]);
+    if version == 0 {
+        data.resize(8 + 16, 0); // 初始版本的 space
+    }
+    if version >= 1 {
+        data.extend_from_slice(&[0; 16]); // lock_start、lock_end
+    }
+    assert_eq!(data.len(), LEGACY_STACK_ACCOUNT_SPACES[version]);
+    data
+}
+
+#[tokio::test]
+async fn legacy_position_is_migrated_in_place() {
+    let (mut ctx, admin) = setup().await;
+    let user = ctx.payer.pubkey();
+    let (vault, ledger) = stack_pdas(&user);
+    let (_, vault_bump) = Pubkey::find_program_address(&[b"stack", user.as_ref()], &task_1::ID);
+    let (_, ledger_bump) = Pubkey::find_program_address(&[user.as_ref()], &task_1::ID);
+    let vault_rent = Rent::default().minimum_balance(0);
+
+    for version in 0..LEGACY_STACK_ACCOUNT_SPACES.len() {
+        // 质押账户中存有 1 SOL，数据账户为旧版本布局
+        let data = legacy_position_data(version, LAMPORTS_PER_SOL, ledger_bump, vault_bump);
+        let legacy_rent = Rent::default().minimum_balance(data.len());
+        let legacy = Account { lamports: legacy_rent, data, owner: task_1::ID, executable: false, rent_epoch: 0 };
+        ctx.set_account(&ledger, &legacy.into());
+        let vault_account = Account { lamports: vault_rent + LAMPORTS_PER_SOL, ..Account::default() };
+        ctx.set_account(&vault, &vault_account.into());
+
+        // 旧布局按各自的版本号读取，但迁移前不能直接使用
+        let raw = ctx.banks_client.get_account(ledger).await.unwrap().unwrap();
+        let old = StackAccount::try_deserialize_versioned(&raw.data).unwrap();
+        assert_eq!((old.version as usize, old.balance), (version, LAMPORTS_PER_SOL));
+        let result = process(&mut ctx, &[withdraw_ix(&user)], &[]).await;
+        assert_error(result, ErrorCode::AccountDidNotDeserialize.into());
+
+        // 只能迁移自己的仓位
+        let mut foreign = migrate_position_ix(&admin.pubkey());
+        foreign.accounts[1].pubkey = ledger;
+        let result = process(&mut ctx, &[foreign], &[&admin]).await;
+        assert_error(result, ErrorCode::ConstraintSeeds.into());
+
+        let before = lamports(&mut ctx, user).await;
+        process(&mut ctx, &[migrate_position_ix(&user)], &[]).await.unwrap();
+        let raw = ctx.banks_client.get_account(ledger).await.unwrap().unwrap();
+        assert_eq!(raw.data.len(), 8 + StackAccount::INIT_SPACE);
+        assert_eq!(raw.lamports, Rent::default().minimum_balance(raw.data.len()));
+        assert!(before - lamports(&mut ctx, user).await >= raw.lamports - legacy_rent);
+        let migrated: StackAccount = fetch(&mut ctx, ledger).await;
+        assert_eq!((migrated.version, migrated.balance), (STACK_ACCOUNT_VERSION, LAMPORTS_PER_SOL));
+        assert_eq!((migrated.stack_account_pump, migrated.stack_account_pda_pump), (ledger_bump, vault_bump));
+
+        let result = process(&mut ctx, &[migrate_position_ix(&user)], &[]).await;
+        assert_error(result, CustomError::PositionAlreadyMigrated.into());
+
+        // 迁移后仓位照常提现
+        process(&mut ctx, &[withdraw_ix(&user)], &[]).await.unwrap();
+        let withdrawn: StackAccount = fetch(&mut ctx, ledger).await;
+        assert_eq!(withdrawn.balance, 0);
+        assert_eq!(lamports(&mut ctx, vault).await, vault_rent);
+    }
+}
@@ -0,0 +1,1493 @@
+use anchor_lang::{
+    solana_program::{program_option::COption, program_pack::Pack},
+    system_program, AccountSerialize, Discriminator, Space,
+};
+use anchor_spl::{
+    associated_token,
+    metadata::MetadataAccount,
+    token::spl_token::{
+        self,
+        state::{Account as SplTokenAccount, AccountState, Mint as SplMint},
+    },
+    token_interface::{Mint, TokenAccount},
+};
+use integration_tests::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    bpf_loader_upgradeable,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    sysvar,
+};
+use task_2::instructions::{
+    config::{Config, ConfigParams},
+    multisig::{Proposal, ProposalAccount, ProposalInstruction},
+    spl::{InitTokenParams, MintConfig, TokenCreator, TokenRegistry, UpdateTokenMetadataParams},
+    reward::RewardPool,
+    stack::{CustomError, StackAccount, LEGACY_STACK_ACCOUNT_SPACES, REWARD_STACK_ACCOUNT_VERSION, STACK_ACCOUNT_VERSION},
+    vault::ShareVault,
+};
+
+const SYMBOL: &str = "TT1";
+const DECIMALS: u8 = 9;
+
+/** 测试环境：admin 为 task_2 的升级权限账户，也是 TT1 的创建者 */
+struct Fixture {
+    ctx: ProgramTestContext,
+    admin: Keypair,
+    mint: Pubkey,
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &task_2::ID).0
+}
+
+fn mint_pda(symbol: &str) -> Pubkey {
+    pda(&[b"mint", symbol.as_bytes()])
+}
+
+fn initialize_config_ix(authority: &Pubkey) -> Instruction {
+    let (program_data, _) = Pubkey::find_program_address(&[task_2::ID.as_ref()], &bpf_loader_upgradeable::id());
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::InitializeConfig {
+            authority: *authority,
+            config: pda(&[b"config"]),
+            program: task_2::ID,
+            program_data,
+            system_program: system_program::ID,
+        },
+        task_2::instruction::InitializeConfig {
+            params: ConfigParams { emergency: false, deposit_fee_bps: 0, withdraw_fee_bps: 0 },
+        },
+    )
+}
+
+fn set_paused_ix(admin: &Pubkey, paused: bool) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::UpdateConfig { admin: *admin, config: pda(&[b"config"]) },
+        task_2::instruction::SetPaused { paused },
+    )
+}
+
+fn set_emergency_ix(admin: &Pubkey, emergency: bool) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::UpdateConfig { admin: *admin, config: pda(&[b"config"]) },
+        task_2::instruction::UpdateConfig {
+            params: ConfigParams { emergency, deposit_fee_bps: 0, withdraw_fee_bps: 0 },
+        },
+    )
+}
+
+fn set_fees_ix(admin: &Pubkey, deposit_fee_bps: u16, withdraw_fee_bps: u16) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::UpdateConfig { admin: *admin, config: pda(&[b"config"]) },
+        task_2::instruction::UpdateConfig {
+            params: ConfigParams { emergency: false, deposit_fee_bps, withdraw_fee_bps },
+        },
+    )
+}
+
+fn collect_fees_spl_ix(admin: &Pubkey, mint: &Pubkey, recipient: &Pubkey) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::CollectFeesSpl {
+            admin: *admin,
+            config: pda(&[b"config"]),
+            treasury: pda(&[b"treasury"]),
+            mint: *mint,
+            treasury_ata: ata(&pda(&[b"treasury"]), mint),
+            recipient_ata: ata(recipient, mint),
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        task_2::instruction::CollectFeesSpl {},
+    )
+}
+
+fn propose_admin_ix(admin: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::UpdateConfig { admin: *admin, config: pda(&[b"config"]) },
+        task_2::instruction::ProposeAdmin { new_admin: *new_admin },
+    )
+}
+
+fn accept_admin_ix(pending_admin: &Pubkey) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::AcceptAdmin { pending_admin: *pending_admin, config: pda(&[b"config"]) },
+        task_2::instruction::AcceptAdmin {},
+    )
+}
+
+fn propose_mint_authority_ix(authority: &Pubkey, mint: &Pubkey, new_authority: &Pubkey) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::ProposeMintAuthority { authority: *authority, mint_config: pda(&[b"mint_config", mint.as_ref()]) },
+        task_2::instruction::ProposeMintAuthority { new_authority: *new_authority },
+    )
+}
+
+fn accept_mint_authority_ix(pending_authority: &Pubkey, mint: &Pubkey) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::AcceptMintAuthority {
+            pending_authority: *pending_authority,
+            mint_config: pda(&[b"mint_config", mint.as_ref()]),
+        },
+        task_2::instruction::AcceptMintAuthority {},
+    )
+}
+
+fn create_multisig_ix(admin: &Pubkey, signers: Vec<Pubkey>, threshold: u8) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::CreateMultisig {
+            admin: *admin,
+            config: pda(&[b"config"]),
+            multisig: pda(&[b"multisig"]),
+            multisig_signer: pda(&[b"multisig_signer"]),
+            system_program: system_program::ID,
+        },
+        task_2::instruction::CreateMultisig { signers, threshold },
+    )
+}
+
+fn create_proposal_ix(proposer: &Pubkey, index: u64, ix: &Instruction) -> Instruction {
+    let instruction = ProposalInstruction {
+        program_id: ix.program_id,
+        accounts: ix
+            .accounts
+            .iter()
+            .map(|meta| ProposalAccount { pubkey: meta.pubkey, is_signer: meta.is_signer, is_writable: meta.is_writable })
+            .collect(),
+        data: ix.data.clone(),
+    };
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::CreateProposal {
+            proposer: *proposer,
+            multisig: pda(&[b"multisig"]),
+            proposal: pda(&[b"proposal", &index.to_le_bytes()]),
+            system_program: system_program::ID,
+        },
+        task_2::instruction::CreateProposal { instruction },
+    )
+}
+
+fn approve_proposal_ix(signer: &Pubkey, index: u64) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::ApproveProposal {
+            signer: *signer,
+            multisig: pda(&[b"multisig"]),
+            proposal: pda(&[b"proposal", &index.to_le_bytes()]),
+        },
+        task_2::instruction::ApproveProposal {},
+    )
+}
+
+// 提案指令的账户（去掉多签签名PDA）和目标程序作为 remaining_accounts
+fn execute_proposal_ix(index: u64, target: &Instruction) -> Instruction {
+    let signer = pda(&[b"multisig_signer"]);
+    let mut ix = anchor_ix(
+        task_2::ID,
+        task_2::accounts::ExecuteProposal {
+            multisig: pda(&[b"multisig"]),
+            proposal: pda(&[b"proposal", &index.to_le_bytes()]),
+            multisig_signer: signer,
+        },
+        task_2::instruction::ExecuteProposal {},
+    );
+    ix.accounts.extend(
+        target
+            .accounts
+            .iter()
+            .filter(|meta| meta.pubkey != signer)
+            .map(|meta| AccountMeta { pubkey: meta.pubkey, is_signer: false, is_writable: meta.is_writable }),
+    );
+    ix.accounts.push(AccountMeta::new_readonly(target.program_id, false));
+    ix
+}
+
+// 不限总量、不限每 epoch 铸币量
+fn create_token_ix(payer: &Pubkey, symbol: &str, decimals: u8) -> Instruction {
+    create_capped_token_ix(payer, symbol, decimals, u64::MAX, 0)
+}
+
+fn create_capped_token_ix(payer: &Pubkey, symbol: &str, decimals: u8, max_supply: u64, epoch_emission: u64) -> Instruction {
+    let mint = mint_pda(symbol);
+    let (metadata, _) = Pubkey::find_program_address(
+        &[b"metadata", METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &METADATA_PROGRAM_ID,
+    );
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::CreateSpl {
+            payer: *payer,
+            config: pda(&[b"config"]),
+            mint,
+            mint_config: pda(&[b"mint_config", mint.as_ref()]),
+            registry: pda(&[b"registry"]),
+            metadata,
+            rent: sysvar::rent::id(),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            token_metadata_program: METADATA_PROGRAM_ID,
+            associated_token_program: associated_token::ID,
+        },
+        task_2::instruction::CreateToken {
+            metadata: InitTokenParams {
+                name: format!("Test Token {symbol}"),
+                symbol: symbol.to_string(),
+                uri: "https://example.com/token.json".to_string(),
+                decimals,
+                max_supply,
+                epoch_emission,
+            },
+        },
+    )
+}
+
+fn metadata_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"metadata", METADATA_PROGRAM_ID.as_ref(), mint.as_ref()], &METADATA_PROGRAM_ID).0
+}
+
+fn update_token_metadata_accounts(admin: &Pubkey, mint: &Pubkey) -> task_2::accounts::UpdateTokenMetadata {
+    task_2::accounts::UpdateTokenMetadata {
+        admin: *admin,
+        config: pda(&[b"config"]),
+        mint_config: pda(&[b"mint_config", mint.as_ref()]),
+        mint: *mint,
+        metadata: metadata_pda(mint),
+        token_metadata_program: METADATA_PROGRAM_ID,
+    }
+}
+
+fn update_token_metadata_ix(admin: &Pubkey, mint: &Pubkey, params: UpdateTokenMetadataParams) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        update_token_metadata_accounts(admin, mint),
+        task_2::instruction::UpdateTokenMetadata { params },
+    )
+}
+
+fn freeze_metadata_ix(admin: &Pubkey, mint: &Pubkey) -> Instruction {
+    anchor_ix(task_2::ID, update_token_metadata_accounts(admin, mint), task_2::instruction::FreezeMetadata {})
+}
+
+fn burn_spl_ix(holder: &Pubkey, mint: &Pubkey, amount: u64) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::BurnSpl {
+            holder: *holder,
+            mint_config: pda(&[b"mint_config", mint.as_ref()]),
+            mint: *mint,
+            holder_ata: ata(holder, mint),
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        task_2::instruction::BurnSpl { amount },
+    )
+}
+
+fn burn_from_vault_ix(admin: &Pubkey, user: &Pubkey, mint: &Pubkey, amount: u64) -> Instruction {
+    let pda_stack_account = pda(&[b"stack", user.as_ref()]);
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::BurnFromVault {
+            admin: *admin,
+            config: pda(&[b"config"]),
+            user: *user,
+            pda_stack_account,
+            stack_account: pda(&[user.as_ref(), mint.as_ref()]),
+            mint_config: pda(&[b"mint_config", mint.as_ref()]),
+            mint: *mint,
+            vault_ata: ata(&pda_stack_account, mint),
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        task_2::instruction::BurnFromVault { amount },
+    )
+}
+
+fn revoke_mint_authority_ix(authority: &Pubkey, mint: &Pubkey) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::RevokeMintAuthority {
+            authority: *authority,
+            mint_config: pda(&[b"mint_config", mint.as_ref()]),
+            mint: *mint,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        task_2::instruction::RevokeMintAuthority {},
+    )
+}
+
+fn freeze_token_account_accounts(admin: &Pubkey, mint: &Pubkey, owner: &Pubkey) -> task_2::accounts::FreezeTokenAccount {
+    task_2::accounts::FreezeTokenAccount {
+        admin: *admin,
+        config: pda(&[b"config"]),
+        mint_config: pda(&[b"mint_config", mint.as_ref()]),
+        mint: *mint,
+        token_account: ata(owner, mint),
+        token_program: TOKEN_PROGRAM_ID,
+    }
+}
+
+fn freeze_account_ix(admin: &Pubkey, mint: &Pubkey, owner: &Pubkey) -> Instruction {
+    anchor_ix(task_2::ID, freeze_token_account_accounts(admin, mint, owner), task_2::instruction::FreezeAccount {})
+}
+
+fn thaw_account_ix(admin: &Pubkey, mint: &Pubkey, owner: &Pubkey) -> Instruction {
+    anchor_ix(task_2::ID, freeze_token_account_accounts(admin, mint, owner), task_2::instruction::ThawAccount {})
+}
+
+fn mint_spl_ix(payer: &Pubkey, mint: &Pubkey, recipient: &Pubkey, amount: u64) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::MintSpl {
+            payer: *payer,
+            mint_config: pda(&[b"mint_config", mint.as_ref()]),
+            mint: *mint,
+            config: pda(&[b"config"]),
+            recipient: *recipient,
+            recipient_ata: ata(recipient, mint),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            token_metadata_program: METADATA_PROGRAM_ID,
+            associated_token_program: associated_token::ID,
+        },
+        task_2::instruction::MintSpl { amount },
+    )
+}
+
+fn add_whitelisted_mint_ix(authority: &Pubkey, mint: &Pubkey) -> Instruction {
+    let (program_data, _) = Pubkey::find_program_address(&[task_2::ID.as_ref()], &bpf_loader_upgradeable::id());
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::AddWhitelistedMint {
+            authority: *authority,
+            mint: *mint,
+            whitelisted_mint: pda(&[b"whitelist", mint.as_ref()]),
+            reward_pool: pda(&[b"reward_pool", mint.as_ref()]),
+            treasury: pda(&[b"treasury"]),
+            treasury_ata: ata(&pda(&[b"treasury"]), mint),
+            program: task_2::ID,
+            program_data,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: associated_token::ID,
+        },
+        task_2::instruction::AddWhitelistedMint {},
+    )
+}
+
+fn init_treasury_ata_ix(admin: &Pubkey, mint: &Pubkey) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::InitTreasuryAta {
+            admin: *admin,
+            config: pda(&[b"config"]),
+            treasury: pda(&[b"treasury"]),
+            mint: *mint,
+            treasury_ata: ata(&pda(&[b"treasury"]), mint),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: associated_token::ID,
+        },
+        task_2::instruction::InitTreasuryAta {},
+    )
+}
+
+fn deposit_spl_accounts(user: &Pubkey, mint: &Pubkey) -> task_2::accounts::DepositSpl {
+    let pda_stack_account = pda(&[b"stack", user.as_ref()]);
+    task_2::accounts::DepositSpl {
+        payer: *user,
+        pda_stack_account,
+        stack_account: pda(&[user.as_ref(), mint.as_ref()]),
+        mint: *mint,
+        whitelisted_mint: pda(&[b"whitelist", mint.as_ref()]),
+        reward_pool: pda(&[b"reward_pool", mint.as_ref()]),
+        config: pda(&[b"config"]),
+        payer_ata: ata(user, mint),
+        stack_account_ata: ata(&pda_stack_account, mint),
+        treasury: pda(&[b"treasury"]),
+        treasury_ata: ata(&pda(&[b"treasury"]), mint),
+        system_program: system_program::ID,
+        token_program: TOKEN_PROGRAM_ID,
+        associated_token_program: associated_token::ID,
+    }
+}
+
+// 奖励池由白名单的 authority 管理，奖励金库为奖励池PDA的ATA
+fn configure_reward_pool_ix(admin: &Pubkey, stake_mint: &Pubkey, reward_mint: &Pubkey, reward_rate: u64) -> Instruction {
+    let reward_pool = pda(&[b"reward_pool", stake_mint.as_ref()]);
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::ConfigureRewardPool {
+            admin: *admin,
+            reward_pool,
+            reward_mint: *reward_mint,
+            reward_vault: ata(&reward_pool, reward_mint),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: associated_token::ID,
+        },
+        task_2::instruction::ConfigureRewardPool { reward_rate },
+    )
+}
+
+fn fund_reward_pool_ix(admin: &Pubkey, stake_mint: &Pubkey, reward_mint: &Pubkey, amount: u64) -> Instruction {
+    let reward_pool = pda(&[b"reward_pool", stake_mint.as_ref()]);
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::FundRewardPool {
+            admin: *admin,
+            reward_pool,
+            reward_mint: *reward_mint,
+            admin_ata: ata(admin, reward_mint),
+            reward_vault: ata(&reward_pool, reward_mint),
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        task_2::instruction::FundRewardPool { amount },
+    )
+}
+
+fn claim_rewards_ix(user: &Pubkey, stake_mint: &Pubkey, reward_mint: &Pubkey) -> Instruction {
+    let reward_pool = pda(&[b"reward_pool", stake_mint.as_ref()]);
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::ClaimRewards {
+            payer: *user,
+            reward_pool,
+            stack_account: pda(&[user.as_ref(), stake_mint.as_ref()]),
+            config: pda(&[b"config"]),
+            reward_mint: *reward_mint,
+            reward_vault: ata(&reward_pool, reward_mint),
+            user_reward_ata: ata(user, reward_mint),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: associated_token::ID,
+        },
+        task_2::instruction::ClaimRewards {},
+    )
+}
+
+fn deposit_spl_ix(user: &Pubkey, mint: &Pubkey, amount: u64) -> Instruction {
+    anchor_ix(task_2::ID, deposit_spl_accounts(user, mint), task_2::instruction::DepositSpl { amount })
+}
+
+fn deposit_spl_locked_ix(user: &Pubkey, mint: &Pubkey, amount: u64, lock_duration: i64) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        deposit_spl_accounts(user, mint),
+        task_2::instruction::DepositSplLocked { amount, lock_duration },
+    )
+}
+
+fn withdraw_spl_ix(user: &Pubkey, mint: &Pubkey) -> Instruction {
+    let pda_stack_account = pda(&[b"stack", user.as_ref()]);
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::WithdrawSpl {
+            payer: *user,
+            pda_stack_account,
+            stack_account: pda(&[user.as_ref(), mint.as_ref()]),
+            mint: *mint,
+            reward_pool: pda(&[b"reward_pool", mint.as_ref()]),
+            config: pda(&[b"config"]),
+            user_ata: ata(user, mint),
+            stack_account_ata: ata(&pda_stack_account, mint),
+            treasury: pda(&[b"treasury"]),
+            treasury_ata: ata(&pda(&[b"treasury"]), mint),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: associated_token::ID,
+        },
+        task_2::instruction::WithdrawSpl {},
+    )
+}
+
+fn withdraw_spl_legacy_ix(user: &Pubkey) -> Instruction {
+    let pda_stack_account = pda(&[b"stack", user.as_ref()]);
+    let mint = pda(&[b"mint"]);
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::WithdrawSplLegacy {
+            payer: *user,
+            pda_stack_account,
+            legacy_stack_account: pda(&[user.as_ref()]),
+            mint,
+            config: pda(&[b"config"]),
+            user_ata: ata(user, &mint),
+            stack_account_ata: ata(&pda_stack_account, &mint),
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: associated_token::ID,
+        },
+        task_2::instruction::WithdrawSplLegacy {},
+    )
+}
+
+fn deposit_spl_native_ix(user: &Pubkey, amount: u64) -> Instruction {
+    let pda_stack_account = pda(&[b"stack", user.as_ref()]);
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::DepositSplNative {
+            payer: *user,
+            pda_stack_account,
+            stack_account: pda(&[user.as_ref(), NATIVE_MINT.as_ref()]),
+            mint: NATIVE_MINT,
+            whitelisted_mint: pda(&[b"whitelist", NATIVE_MINT.as_ref()]),
+            reward_pool: pda(&[b"reward_pool", NATIVE_MINT.as_ref()]),
+            config: pda(&[b"config"]),
+            stack_account_ata: ata(&pda_stack_account, &NATIVE_MINT),
+            treasury: pda(&[b"treasury"]),
+            treasury_ata: ata(&pda(&[b"treasury"]), &NATIVE_MINT),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: associated_token::ID,
+        },
+        task_2::instruction::DepositSplNative { amount },
+    )
+}
+
+fn withdraw_spl_native_ix(user: &Pubkey) -> Instruction {
+    let pda_stack_account = pda(&[b"stack", user.as_ref()]);
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::WithdrawSplNative {
+            payer: *user,
+            pda_stack_account,
+            stack_account: pda(&[user.as_ref(), NATIVE_MINT.as_ref()]),
+            mint: NATIVE_MINT,
+            reward_pool: pda(&[b"reward_pool", NATIVE_MINT.as_ref()]),
+            config: pda(&[b"config"]),
+            stack_account_ata: ata(&pda_stack_account, &NATIVE_MINT),
+            unwrap_account: pda(&[b"unwrap", user.as_ref()]),
+            treasury: pda(&[b"treasury"]),
+            treasury_ata: ata(&pda(&[b"treasury"]), &NATIVE_MINT),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: associated_token::ID,
+        },
+        task_2::instruction::WithdrawSplNative {},
+    )
+}
+
+fn initialize_share_vault_ix(admin: &Pubkey, mint: &Pubkey) -> Instruction {
+    let share_vault = pda(&[b"share_vault", mint.as_ref()]);
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::InitializeShareVault {
+            admin: *admin,
+            config: pda(&[b"config"]),
+            mint: *mint,
+            whitelisted_mint: pda(&[b"whitelist", mint.as_ref()]),
+            share_vault,
+            receipt_mint: pda(&[b"receipt", mint.as_ref()]),
+            vault_ata: ata(&share_vault, mint),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: associated_token::ID,
+        },
+        task_2::instruction::InitializeShareVault {},
+    )
+}
+
+fn deposit_shares_ix(user: &Pubkey, mint: &Pubkey, amount: u64) -> Instruction {
+    let share_vault = pda(&[b"share_vault", mint.as_ref()]);
+    let receipt_mint = pda(&[b"receipt", mint.as_ref()]);
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::DepositShares {
+            payer: *user,
+            mint: *mint,
+            whitelisted_mint: pda(&[b"whitelist", mint.as_ref()]),
+            config: pda(&[b"config"]),
+            share_vault,
+            receipt_mint,
+            payer_ata: ata(user, mint),
+            payer_receipt_ata: ata(user, &receipt_mint),
+            vault_ata: ata(&share_vault, mint),
+            treasury: pda(&[b"treasury"]),
+            treasury_ata: ata(&pda(&[b"treasury"]), mint),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: associated_token::ID,
+        },
+        task_2::instruction::DepositShares { amount },
+    )
+}
+
+fn withdraw_shares_ix(user: &Pubkey, mint: &Pubkey, shares: u64) -> Instruction {
+    let share_vault = pda(&[b"share_vault", mint.as_ref()]);
+    let receipt_mint = pda(&[b"receipt", mint.as_ref()]);
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::WithdrawShares {
+            payer: *user,
+            mint: *mint,
+            config: pda(&[b"config"]),
+            share_vault,
+            receipt_mint,
+            payer_receipt_ata: ata(user, &receipt_mint),
+            user_ata: ata(user, mint),
+            vault_ata: ata(&share_vault, mint),
+            treasury: pda(&[b"treasury"]),
+            treasury_ata: ata(&pda(&[b"treasury"]), mint),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: associated_token::ID,
+        },
+        task_2::instruction::WithdrawShares { shares },
+    )
+}
+
+fn deposit_ix(user: &Pubkey, amount: u64) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::Deposit {
+            user: *user,
+            stack_account_pda: pda(&[b"sol_stack", user.as_ref()]),
+            stack_account: pda(&[b"sol_position", user.as_ref()]),
+            config: pda(&[b"config"]),
+            treasury: pda(&[b"treasury"]),
+            system_program: system_program::ID,
+        },
+        task_2::instruction::Deposit { amount },
+    )
+}
+
+fn migrate_position_ix(user: &Pubkey, mint: Option<Pubkey>) -> Instruction {
+    let stack_account = match &mint {
+        Some(mint) => pda(&[user.as_ref(), mint.as_ref()]),
+        None => pda(&[b"sol_position", user.as_ref()]),
+    };
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::MigratePosition {
+            user: *user,
+            stack_account,
+            reward_pool: mint.map(|mint| pda(&[b"reward_pool", mint.as_ref()])),
+            system_program: system_program::ID,
+        },
+        task_2::instruction::MigratePosition { mint },
+    )
+}
+
+fn withdraw_ix(user: &Pubkey) -> Instruction {
+    anchor_ix(
+        task_2::ID,
+        task_2::accounts::Withdraw {
+            user: *user,
+            stack_account_pda: pda(&[b"sol_stack", user.as_ref()]),
+            stack_account: pda(&[b"sol_position", user.as_ref()]),
+            config: pda(&[b"config"]),
+            treasury: pda(&[b"treasury"]),
+            system_program: system_program::ID,
+        },
+        task_2::instruction::Withdraw {},
+    )
+}
+
+// 初始化全局配置，创建 TT1 并加入白名单，给 ctx.payer 铸造 1000 枚
+async fn setup() -> Fixture {
+    let admin = Keypair::new();
+    let mut pt = task_2_program_test(admin.pubkey());
+    add_wallet(&mut pt, &admin.pubkey(), 100 * LAMPORTS_PER_SOL);
+    let mut ctx = pt.start_with_context().await;
+
+    let mint = mint_pda(SYMBOL);
+    let user = ctx.payer.pubkey();
+    process(
+        &mut ctx,
+        &[
+            initialize_config_ix(&admin.pubkey()),
+            create_token_ix(&admin.pubkey(), SYMBOL, DECIMALS),
+            add_whitelisted_mint_ix(&admin.pubkey(), &mint),
+            mint_spl_ix(&admin.pubkey(), &mint, &user, 1_000 * 10u64.pow(DECIMALS as u32)),
+        ],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+
+    Fixture { ctx, admin, mint }
+}
+
+#[tokio::test]
+async fn create_token_and_mint() {
+    let Fixture { mut ctx, admin, mint } = setup().await;
+    let user = ctx.payer.pubkey();
+
+    let mint_account: Mint = fetch(&mut ctx, mint).await;
+    assert_eq!(mint_account.decimals, DECIMALS);
+    assert_eq!(mint_account.supply, 1_000 * 10u64.pow(DECIMALS as u32));
+
+    let mint_config: MintConfig = fetch(&mut ctx, pda(&[b"mint_config", mint.as_ref()])).await;
+    assert_eq!(mint_config.authority, admin.pubkey());
+    assert_eq!(mint_config.symbol, SYMBOL);
+
+    let registry: TokenRegistry = fetch(&mut ctx, pda(&[b"registry"])).await;
+    assert_eq!(registry.mints, vec![mint]);
+
+    assert_eq!(token_balance(&mut ctx, ata(&user, &mint)).await, 1_000 * 10u64.pow(DECIMALS as u32));
+
+    // 不同symbol可以使用不同精度
+    process(&mut ctx, &[create_token_ix(&admin.pubkey(), "TT6", 6)], &[&admin]).await.unwrap();
+    let tt6: Mint = fetch(&mut ctx, mint_pda("TT6")).await;
+    assert_eq!(tt6.decimals, 6);
+    let registry: TokenRegistry = fetch(&mut ctx, pda(&[b"registry"])).await;
+    assert_eq!(registry.mints, vec![mint, mint_pda("TT6")]);
+}
+
+#[tokio::test]
+async fn create_token_rejects_non_admin() {
+    let Fixture { mut ctx, .. } = setup().await;
+    let user = ctx.payer.pubkey();
+
+    // 注册表容量有限，只有配置管理员可以创建代币
+    let result = process(&mut ctx, &[create_token_ix(&user, "TT6", 6)], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+    assert!(ctx.banks_client.get_account(mint_pda("TT6")).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn create_token_rejects_invalid_symbols() {
+    let Fixture { mut ctx, admin, .. } = setup().await;
+
+    // 空 symbol 会落到初始版本的 [b"mint"] 地址，超长的 symbol 放不进 MintConfig
+    for symbol in ["", "ELEVENCHARS"] {
+        let result = process(&mut ctx, &[create_token_ix(&admin.pubkey(), symbol, 6)], &[&admin]).await;
+        assert_error(result, CustomError::InvalidSymbol.into());
+    }
+    assert!(ctx.banks_client.get_account(pda(&[b"mint"])).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn mint_spl_rejects_non_authority() {
+    let Fixture { mut ctx, mint, .. } = setup().await;
+    let user = ctx.payer.pubkey();
+
+    let result = process(&mut ctx, &[mint_spl_ix(&user, &mint, &user, 1)], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+}
+
+#[tokio::test]
+async fn add_whitelisted_mint_rejects_non_upgrade_authority() {
+    let Fixture { mut ctx, admin, .. } = setup().await;
+    let user = ctx.payer.pubkey();
+
+    process(&mut ctx, &[create_token_ix(&admin.pubkey(), "TT6", 6)], &[&admin]).await.unwrap();
+    let result = process(&mut ctx, &[add_whitelisted_mint_ix(&user, &mint_pda("TT6"))], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+}
+
+#[tokio::test]
+async fn deposit_and_withdraw_spl() {
+    let Fixture { mut ctx, mint, .. } = setup().await;
+    let user = ctx.payer.pubkey();
+    let user_ata = ata(&user, &mint);
+    let vault = ata(&pda(&[b"stack", user.as_ref()]), &mint);
+    let before = token_balance(&mut ctx, user_ata).await;
+
+    process(&mut ctx, &[deposit_spl_ix(&user, &mint, 100)], &[]).await.unwrap();
+    process(&mut ctx, &[deposit_spl_ix(&user, &mint, 50)], &[]).await.unwrap();
+
+    let stack_account: StackAccount = fetch(&mut ctx, pda(&[user.as_ref(), mint.as_ref()])).await;
+    assert_eq!(stack_account.balance, 150);
+    assert_eq!(token_balance(&mut ctx, vault).await, 150);
+    assert_eq!(token_balance(&mut ctx, user_ata).await, before - 150);
+
+    process(&mut ctx, &[withdraw_spl_ix(&user, &mint)], &[]).await.unwrap();
+
+    let stack_account: StackAccount = fetch(&mut ctx, pda(&[user.as_ref(), mint.as_ref()])).await;
+    assert_eq!(stack_account.balance, 0);
+    assert_eq!(token_balance(&mut ctx, vault).await, 0);
+    assert_eq!(token_balance(&mut ctx, user_ata).await, before);
+}
+
+#[tokio::test]
+async fn withdraw_spl_empty_position_fails() {
+    let Fixture { mut ctx, mint, .. } = setup().await;
+    let user = ctx.payer.pubkey();
+
+    process(&mut ctx, &[deposit_spl_ix(&user, &mint, 100)], &[]).await.unwrap();
+    process(&mut ctx, &[withdraw_spl_ix(&user, &mint)], &[]).await.unwrap();
+
+    let result = process(&mut ctx, &[withdraw_spl_ix(&user, &mint)], &[]).await;
+    assert_error(result, CustomError::InsufficientBalance.into());
+}
+
+#[tokio::test]
+async fn deposit_spl_locked_rejects_an_unlocked_balance() {
+    let Fixture { mut ctx, mint, .. } = setup().await;
+    let user = ctx.payer.pubkey();
+    let position = pda(&[user.as_ref(), mint.as_ref()]);
+
+    // 未锁定的余额不能被锁定，提取后才能锁仓
+    process(&mut ctx, &[deposit_spl_ix(&user, &mint, 100)], &[]).await.unwrap();
+    let result = process(&mut ctx, &[deposit_spl_locked_ix(&user, &mint, 100, 3600)], &[]).await;
+    assert_error(result, CustomError::UnlockedBalance.into());
+
+    process(&mut ctx, &[withdraw_spl_ix(&user, &mint)], &[]).await.unwrap();
+    process(&mut ctx, &[deposit_spl_locked_ix(&user, &mint, 100, 3600)], &[]).await.unwrap();
+    // 锁定期内不能未锁定充值，只能追加锁仓
+    let result = process(&mut ctx, &[deposit_spl_ix(&user, &mint, 10)], &[]).await;
+    assert_error(result, CustomError::DepositIntoLockedPosition.into());
+    // 锁定期内可以追加锁仓
+    process(&mut ctx, &[deposit_spl_locked_ix(&user, &mint, 50, 7200)], &[]).await.unwrap();
+    let stack_account: StackAccount = fetch(&mut ctx, position).await;
+    assert_eq!(stack_account.balance, 150);
+    assert_eq!(stack_account.lock_end - stack_account.lock_start, 7200);
+    let result = process(&mut ctx, &[withdraw_spl_ix(&user, &mint)], &[]).await;
+    assert_error(result, CustomError::PositionLocked.into());
+}
+
+#[tokio::test]
+async fn deposit_spl_rejects_mint_outside_whitelist() {
+    let Fixture { mut ctx, admin, .. } = setup().await;
+    let user = ctx.payer.pubkey();
+    let tt6 = mint_pda("TT6");
+
+    process(
+        &mut ctx,
+        &[create_token_ix(&admin.pubkey(), "TT6", 6), mint_spl_ix(&admin.pubkey(), &tt6, &user, 1_000)],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+
+    // 白名单账户不存在
+    let result = process(&mut ctx, &[deposit_spl_ix(&user, &tt6, 100)], &[]).await;
+    assert_error(result, anchor_lang::error::ErrorCode::AccountNotInitialized.into());
+}
+
+#[tokio::test]
+async fn deposit_and_withdraw_sol() {
+    let Fixture { mut ctx, .. } = setup().await;
+    let user = ctx.payer.pubkey();
+    let ledger = pda(&[b"sol_position", user.as_ref()]);
+
+    process(&mut ctx, &[deposit_ix(&user, LAMPORTS_PER_SOL)], &[]).await.unwrap();
+    let stack_account: StackAccount = fetch(&mut ctx, ledger).await;
+    assert_eq!(stack_account.balance, LAMPORTS_PER_SOL);
+
+    process(&mut ctx, &[withdraw_ix(&user)], &[]).await.unwrap();
+    let stack_account: StackAccount = fetch(&mut ctx, ledger).await;
+    assert_eq!(stack_account.balance, 0);
+
+    let result = process(&mut ctx, &[withdraw_ix(&user)], &[]).await;
+    assert_error(result, CustomError::InsufficientBalance.into());
+}
+
+#[tokio::test]
+async fn pause_blocks_deposits_mints_and_withdrawals() {
+    let Fixture { mut ctx, admin, mint } = setup().await;
+    let user = ctx.payer.pubkey();
+
+    process(&mut ctx, &[deposit_spl_ix(&user, &mint, 100)], &[]).await.unwrap();
+    process(&mut ctx, &[set_paused_ix(&admin.pubkey(), true)], &[&admin]).await.unwrap();
+
+    let result = process(&mut ctx, &[deposit_spl_ix(&user, &mint, 100)], &[]).await;
+    assert_error(result, CustomError::ProgramPaused.into());
+    let result = process(&mut ctx, &[mint_spl_ix(&admin.pubkey(), &mint, &user, 1)], &[&admin]).await;
+    assert_error(result, CustomError::ProgramPaused.into());
+    let result = process(&mut ctx, &[withdraw_spl_ix(&user, &mint)], &[]).await;
+    assert_error(result, CustomError::ProgramPaused.into());
+
+    process(&mut ctx, &[set_paused_ix(&admin.pubkey(), false)], &[&admin]).await.unwrap();
+    process(&mut ctx, &[withdraw_spl_ix(&user, &mint)], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn emergency_mode_allows_only_withdrawals() {
+    let Fixture { mut ctx, admin, mint } = setup().await;
+    let user = ctx.payer.pubkey();
+
+    process(&mut ctx, &[deposit_spl_ix(&user, &mint, 100), deposit_ix(&user, LAMPORTS_PER_SOL)], &[]).await.unwrap();
+    process(&mut ctx, &[set_emergency_ix(&admin.pubkey(), true)], &[&admin]).await.unwrap();
+
+    let result = process(&mut ctx, &[deposit_spl_ix(&user, &mint, 100)], &[]).await;
+    assert_error(result, CustomError::DepositsDisabled.into());
+    let result = process(&mut ctx, &[deposit_ix(&user, 1)], &[]).await;
+    assert_error(result, CustomError::DepositsDisabled.into());
+    let result = process(&mut ctx, &[mint_spl_ix(&admin.pubkey(), &mint, &user, 1)], &[&admin]).await;
+    assert_error(result, CustomError::DepositsDisabled.into());
+
+    process(&mut ctx, &[withdraw_spl_ix(&user, &mint), withdraw_ix(&user)], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn config_changes_require_admin() {
+    let Fixture { mut ctx, .. } = setup().await;
+    let user = ctx.payer.pubkey();
+
+    let result = process(&mut ctx, &[set_paused_ix(&user, true)], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+    let result = process(&mut ctx, &[initialize_config_ix(&user)], &[]).await;
+    assert!(result.is_err(), "config can only be initialized once");
+}
+
+#[tokio::test]
+async fn admin_and_mint_authority_handover_take_two_steps() {
+    let Fixture { mut ctx, admin, mint } = setup().await;
+    let new_admin = Keypair::new();
+    let stranger = ctx.payer.pubkey();
+
+    process(&mut ctx, &[propose_admin_ix(&admin.pubkey(), &new_admin.pubkey())], &[&admin]).await.unwrap();
+    let result = process(&mut ctx, &[accept_admin_ix(&stranger)], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+    process(&mut ctx, &[accept_admin_ix(&new_admin.pubkey())], &[&new_admin]).await.unwrap();
+    let config: Config = fetch(&mut ctx, pda(&[b"config"])).await;
+    assert_eq!(config.admin, new_admin.pubkey());
+    assert_eq!(config.pending_admin, Pubkey::default());
+
+    process(&mut ctx, &[propose_mint_authority_ix(&admin.pubkey(), &mint, &stranger)], &[&admin]).await.unwrap();
+    let result = process(&mut ctx, &[accept_mint_authority_ix(&new_admin.pubkey(), &mint)], &[&new_admin]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+    process(&mut ctx, &[accept_mint_authority_ix(&stranger, &mint)], &[]).await.unwrap();
+
+    // 新的铸币权限账户可以铸币，原账户不行
+    process(&mut ctx, &[mint_spl_ix(&stranger, &mint, &stranger, 1)], &[]).await.unwrap();
+    let result = process(&mut ctx, &[mint_spl_ix(&admin.pubkey(), &mint, &stranger, 1)], &[&admin]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+}
+
+#[tokio::test]
+async fn multisig_executes_proposals_after_threshold() {
+    let Fixture { mut ctx, admin, .. } = setup().await;
+    let member = Keypair::new();
+    let signer = pda(&[b"multisig_signer"]);
+
+    // 2-of-2 多签接管管理员权限
+    let accept = accept_admin_ix(&signer);
+    process(
+        &mut ctx,
+        &[
+            create_multisig_ix(&admin.pubkey(), vec![admin.pubkey(), member.pubkey()], 2),
+            propose_admin_ix(&admin.pubkey(), &signer),
+            create_proposal_ix(&admin.pubkey(), 0, &accept),
+        ],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+
+    let result = process(&mut ctx, &[execute_proposal_ix(0, &accept)], &[]).await;
+    assert_error(result, CustomError::NotEnoughApprovals.into());
+    // 非成员不能批准
+    let stranger = ctx.payer.pubkey();
+    let result = process(&mut ctx, &[approve_proposal_ix(&stranger, 0)], &[]).await;
+    assert_error(result, CustomError::NotMultisigSigner.into());
+
+    process(&mut ctx, &[approve_proposal_ix(&member.pubkey(), 0)], &[&member]).await.unwrap();
+    process(&mut ctx, &[execute_proposal_ix(0, &accept)], &[]).await.unwrap();
+    let config: Config = fetch(&mut ctx, pda(&[b"config"])).await;
+    assert_eq!(config.admin, signer);
+    let proposal: Proposal = fetch(&mut ctx, pda(&[b"proposal", &0u64.to_le_bytes()])).await;
+    assert!(proposal.executed);
+
+    let result = process(&mut ctx, &[execute_proposal_ix(0, &accept)], &[]).await;
+    assert_error(result, CustomError::ProposalExecuted.into());
+
+    // 原管理员不能再直接修改配置，只能通过提案
+    let result = process(&mut ctx, &[set_paused_ix(&admin.pubkey(), true)], &[&admin]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+    let pause = set_paused_ix(&signer, true);
+    process(&mut ctx, &[create_proposal_ix(&member.pubkey(), 1, &pause)], &[&member]).await.unwrap();
+    process(&mut ctx, &[approve_proposal_ix(&admin.pubkey(), 1)], &[&admin]).await.unwrap();
+    process(&mut ctx, &[execute_proposal_ix(1, &pause)], &[]).await.unwrap();
+    let config: Config = fetch(&mut ctx, pda(&[b"config"])).await;
+    assert!(config.paused);
+}
+
+#[tokio::test]
+async fn spl_fees_are_routed_to_treasury_and_collected() {
+    let Fixture { mut ctx, admin, mint } = setup().await;
+    let user = ctx.payer.pubkey();
+    let treasury_ata = ata(&pda(&[b"treasury"]), &mint);
+
+    // 手续费ATA在加入白名单时由管理员创建，用户不支付它的租金
+    assert_eq!(token_balance(&mut ctx, treasury_ata).await, 0);
+    let before_lamports = lamports(&mut ctx, user).await;
+    process(&mut ctx, &[deposit_spl_ix(&user, &mint, 100), withdraw_spl_ix(&user, &mint)], &[]).await.unwrap();
+    // 用户只为自己的仓位账户、质押PDA和质押ATA支付租金
+    let vault_authority = pda(&[b"stack", user.as_ref()]);
+    let mut rent_paid = 0;
+    for account in [pda(&[user.as_ref(), mint.as_ref()]), vault_authority, ata(&vault_authority, &mint)] {
+        rent_paid += lamports(&mut ctx, account).await;
+    }
+    assert_eq!(lamports(&mut ctx, user).await, before_lamports - rent_paid - 5_000);
+
+    // 充值 0.3%、提现 1%，向上取整
+    process(&mut ctx, &[set_fees_ix(&admin.pubkey(), 30, 100)], &[&admin]).await.unwrap();
+    let before = token_balance(&mut ctx, ata(&user, &mint)).await;
+    process(&mut ctx, &[deposit_spl_ix(&user, &mint, 1_001)], &[]).await.unwrap();
+    let position: StackAccount = fetch(&mut ctx, pda(&[user.as_ref(), mint.as_ref()])).await;
+    assert_eq!(position.balance, 1_001 - 4);
+    assert_eq!(token_balance(&mut ctx, treasury_ata).await, 4);
+
+    process(&mut ctx, &[withdraw_spl_ix(&user, &mint)], &[]).await.unwrap();
+    assert_eq!(token_balance(&mut ctx, treasury_ata).await, 4 + 10);
+    assert_eq!(token_balance(&mut ctx, ata(&user, &mint)).await, before - 4 - 10);
+
+    // 扣除手续费后没有余额入账
+    let result = process(&mut ctx, &[deposit_spl_ix(&user, &mint, 1)], &[]).await;
+    assert_error(result, CustomError::ZeroAmount.into());
+
+    let result = process(&mut ctx, &[collect_fees_spl_ix(&user, &mint, &user)], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+    process(&mut ctx, &[collect_fees_spl_ix(&admin.pubkey(), &mint, &user)], &[&admin]).await.unwrap();
+    assert_eq!(token_balance(&mut ctx, treasury_ata).await, 0);
+    assert_eq!(token_balance(&mut ctx, ata(&user, &mint)).await, before);
+}
+
+#[tokio::test]
+async fn admin_creates_treasury_ata_for_existing_mints() {
+    let Fixture { mut ctx, admin, .. } = setup().await;
+    let user = ctx.payer.pubkey();
+    let tt6 = mint_pda("TT6");
+    let treasury_ata = ata(&pda(&[b"treasury"]), &tt6);
+
+    process(&mut ctx, &[create_token_ix(&admin.pubkey(), "TT6", 6)], &[&admin]).await.unwrap();
+    let result = process(&mut ctx, &[init_treasury_ata_ix(&user, &tt6)], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+    assert_eq!(lamports(&mut ctx, treasury_ata).await, 0);
+
+    // 可以重复执行
+    process(&mut ctx, &[init_treasury_ata_ix(&admin.pubkey(), &tt6)], &[&admin]).await.unwrap();
+    process(&mut ctx, &[init_treasury_ata_ix(&admin.pubkey(), &tt6)], &[&admin]).await.unwrap();
+    assert_eq!(token_balance(&mut ctx, treasury_ata).await, 0);
+}
+
+#[tokio::test]
+async fn share_vault_mints_receipts_and_shares_yield() {
+    let Fixture { mut ctx, admin, mint } = setup().await;
+    let user = ctx.payer.pubkey();
+    let share_vault_address = pda(&[b"share_vault", mint.as_ref()]);
+    let receipt_mint = pda(&[b"receipt", mint.as_ref()]);
+    let vault_ata = ata(&share_vault_address, &mint);
+
+    let result = process(&mut ctx, &[initialize_share_vault_ix(&user, &mint)], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+    process(&mut ctx, &[initialize_share_vault_ix(&admin.pubkey(), &mint)], &[&admin]).await.unwrap();
+
+    // 首次充值必须超过永久锁定的份额
+    let result = process(&mut ctx, &[deposit_shares_ix(&user, &mint, 1_000)], &[]).await;
+    assert_error(result, CustomError::DepositTooSmall.into());
+    let before = token_balance(&mut ctx, ata(&user, &mint)).await;
+    process(&mut ctx, &[deposit_shares_ix(&user, &mint, 10_000)], &[]).await.unwrap();
+    assert_eq!(token_balance(&mut ctx, ata(&user, &receipt_mint)).await, 9_000);
+    let vault: ShareVault = fetch(&mut ctx, share_vault_address).await;
+    assert_eq!(vault.total_shares, 10_000);
+
+    // 直接铸造到金库ATA的收益由现有份额分享，之后的充值按新的比例获得份额
+    process(
+        &mut ctx,
+        &[
+            mint_spl_ix(&admin.pubkey(), &mint, &share_vault_address, 5_000),
+            mint_spl_ix(&admin.pubkey(), &mint, &admin.pubkey(), 3_000),
+            deposit_shares_ix(&admin.pubkey(), &mint, 3_000),
+        ],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    assert_eq!(token_balance(&mut ctx, ata(&admin.pubkey(), &receipt_mint)).await, 2_000);
+    assert_eq!(token_balance(&mut ctx, vault_ata).await, 18_000);
+
+    let result = process(&mut ctx, &[withdraw_shares_ix(&user, &mint, 9_001)], &[]).await;
+    assert_error(result, CustomError::InsufficientBalance.into());
+    process(&mut ctx, &[withdraw_shares_ix(&user, &mint, 9_000)], &[]).await.unwrap();
+    // 9_000 * 18_001 / 12_001，向下取整
+    assert_eq!(token_balance(&mut ctx, ata(&user, &mint)).await, before - 10_000 + 13_499);
+    assert_eq!(token_balance(&mut ctx, ata(&user, &receipt_mint)).await, 0);
+    let vault: ShareVault = fetch(&mut ctx, share_vault_address).await;
+    assert_eq!(vault.total_shares, 3_000);
+    assert_eq!(token_balance(&mut ctx, vault_ata).await, 18_000 - 13_499);
+}
+
+#[tokio::test]
+async fn native_sol_is_wrapped_on_deposit_and_unwrapped_on_withdraw() {
+    let Fixture { mut ctx, admin, .. } = setup().await;
+    let user = ctx.payer.pubkey();
+    let vault = ata(&pda(&[b"stack", user.as_ref()]), &NATIVE_MINT);
+    let treasury_ata = ata(&pda(&[b"treasury"]), &NATIVE_MINT);
+
+    // WSOL 未加入白名单时不能充值
+    let result = process(&mut ctx, &[deposit_spl_native_ix(&user, LAMPORTS_PER_SOL)], &[]).await;
+    assert!(result.is_err());
+
+    process(
+        &mut ctx,
+        &[add_whitelisted_mint_ix(&admin.pubkey(), &NATIVE_MINT), set_fees_ix(&admin.pubkey(), 0, 100)],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    process(&mut ctx, &[deposit_spl_native_ix(&user, 2 * LAMPORTS_PER_SOL)], &[]).await.unwrap();
+    let position: StackAccount = fetch(&mut ctx, pda(&[user.as_ref(), NATIVE_MINT.as_ref()])).await;
+    assert_eq!(position.balance, 2 * LAMPORTS_PER_SOL);
+    assert_eq!(token_balance(&mut ctx, vault).await, 2 * LAMPORTS_PER_SOL);
+
+    // 提现 1% 手续费以 WSOL 留在金库，其余解包为 SOL，临时账户的租金原路退回
+    let before = lamports(&mut ctx, user).await;
+    process(&mut ctx, &[withdraw_spl_native_ix(&user)], &[]).await.unwrap();
+    let fee = 2 * LAMPORTS_PER_SOL / 100;
+    assert_eq!(lamports(&mut ctx, user).await, before + 2 * LAMPORTS_PER_SOL - fee - 5_000);
+    assert_eq!(token_balance(&mut ctx, vault).await, 0);
+    assert_eq!(token_balance(&mut ctx, treasury_ata).await, fee);
+    assert_eq!(lamports(&mut ctx, pda(&[b"unwrap", user.as_ref()])).await, 0);
+
+    let result = process(&mut ctx, &[withdraw_spl_native_ix(&user)], &[]).await;
+    assert_error(result, CustomError::InsufficientBalance.into());
+}
+
+#[tokio::test]
+async fn metadata_can_be_updated_until_frozen() {
+    let Fixture { mut ctx, admin, mint } = setup().await;
+    let params = UpdateTokenMetadataParams {
+        name: "Renamed Token".to_string(),
+        symbol: "RNT".to_string(),
+        uri: "https://example.com/renamed.json".to_string(),
+        creators: vec![TokenCreator { address: admin.pubkey(), share: 100 }],
+    };
+
+    let user = ctx.payer.pubkey();
+    let result = process(&mut ctx, &[update_token_metadata_ix(&user, &mint, params.clone())], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+
+    process(&mut ctx, &[update_token_metadata_ix(&admin.pubkey(), &mint, params.clone())], &[&admin]).await.unwrap();
+    let metadata: MetadataAccount = fetch(&mut ctx, metadata_pda(&mint)).await;
+    assert_eq!(metadata.name.trim_end_matches('\0'), "Renamed Token");
+    assert_eq!(metadata.symbol.trim_end_matches('\0'), "RNT");
+    assert_eq!(metadata.uri.trim_end_matches('\0'), "https://example.com/renamed.json");
+    assert_eq!(metadata.creators.as_ref().unwrap()[0].address, admin.pubkey());
+    assert!(metadata.is_mutable);
+
+    // mint PDA 仍由创建时的 symbol 推导，铸币不受影响
+    process(&mut ctx, &[mint_spl_ix(&admin.pubkey(), &mint, &user, 1)], &[&admin]).await.unwrap();
+
+    process(&mut ctx, &[freeze_metadata_ix(&admin.pubkey(), &mint)], &[&admin]).await.unwrap();
+    let metadata: MetadataAccount = fetch(&mut ctx, metadata_pda(&mint)).await;
+    assert!(!metadata.is_mutable);
+    let result = process(&mut ctx, &[update_token_metadata_ix(&admin.pubkey(), &mint, params)], &[&admin]).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn frozen_token_account_cannot_deposit_until_thawed() {
+    let Fixture { mut ctx, admin, mint } = setup().await;
+    let user = ctx.payer.pubkey();
+
+    let result = process(&mut ctx, &[freeze_account_ix(&user, &mint, &user)], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+
+    process(&mut ctx, &[freeze_account_ix(&admin.pubkey(), &mint, &user)], &[&admin]).await.unwrap();
+    let user_ata: TokenAccount = fetch(&mut ctx, ata(&user, &mint)).await;
+    assert!(user_ata.is_frozen());
+
+    let result = process(&mut ctx, &[deposit_spl_ix(&user, &mint, 100)], &[]).await;
+    assert_error(result, CustomError::AccountFrozen.into());
+
+    process(&mut ctx, &[thaw_account_ix(&admin.pubkey(), &mint, &user)], &[&admin]).await.unwrap();
+    let user_ata: TokenAccount = fetch(&mut ctx, ata(&user, &mint)).await;
+    assert!(!user_ata.is_frozen());
+
+    process(&mut ctx, &[deposit_spl_ix(&user, &mint, 100)], &[]).await.unwrap();
+    let position: StackAccount = fetch(&mut ctx, pda(&[user.as_ref(), mint.as_ref()])).await;
+    assert_eq!(position.balance, 100);
+}
+
+#[tokio::test]
+async fn mint_spl_enforces_supply_cap_and_epoch_emission() {
+    let Fixture { mut ctx, admin, .. } = setup().await;
+    let user = ctx.payer.pubkey();
+    let mint = mint_pda("CAP");
+    process(&mut ctx, &[create_capped_token_ix(&admin.pubkey(), "CAP", 0, 1_000, 600)], &[&admin]).await.unwrap();
+
+    let result = process(&mut ctx, &[create_capped_token_ix(&admin.pubkey(), "ZERO", 0, 0, 0)], &[&admin]).await;
+    assert_error(result, CustomError::InvalidMaxSupply.into());
+
+    // 同一个 epoch 内最多铸造 600
+    process(&mut ctx, &[mint_spl_ix(&admin.pubkey(), &mint, &user, 600)], &[&admin]).await.unwrap();
+    let result = process(&mut ctx, &[mint_spl_ix(&admin.pubkey(), &mint, &user, 1)], &[&admin]).await;
+    assert_error(result, CustomError::EpochEmissionExceeded.into());
+
+    // 销毁不会释放本 epoch 的铸币额度
+    process(&mut ctx, &[burn_spl_ix(&user, &mint, 100)], &[]).await.unwrap();
+    let result = process(&mut ctx, &[mint_spl_ix(&admin.pubkey(), &mint, &user, 1)], &[&admin]).await;
+    assert_error(result, CustomError::EpochEmissionExceeded.into());
+    let mint_config: MintConfig = fetch(&mut ctx, pda(&[b"mint_config", mint.as_ref()])).await;
+    assert_eq!(mint_config.epoch_minted, 600);
+
+    // 下一个 epoch 重新计算，但总量不能超过 1000
+    ctx.warp_to_epoch(mint_config.emission_epoch + 1).unwrap();
+    let result = process(&mut ctx, &[mint_spl_ix(&admin.pubkey(), &mint, &user, 501)], &[&admin]).await;
+    assert_error(result, CustomError::MaxSupplyExceeded.into());
+    process(&mut ctx, &[mint_spl_ix(&admin.pubkey(), &mint, &user, 500)], &[&admin]).await.unwrap();
+    let mint_account: Mint = fetch(&mut ctx, mint).await;
+    assert_eq!(mint_account.supply, 1_000);
+}
+
+#[tokio::test]
+async fn revoked_mint_authority_cannot_mint() {
+    let Fixture { mut ctx, admin, mint } = setup().await;
+    let user = ctx.payer.pubkey();
+
+    let result = process(&mut ctx, &[revoke_mint_authority_ix(&user, &mint)], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+
+    process(&mut ctx, &[revoke_mint_authority_ix(&admin.pubkey(), &mint)], &[&admin]).await.unwrap();
+    let mint_account: Mint = fetch(&mut ctx, mint).await;
+    assert!(mint_account.mint_authority.is_none());
+    // 冻结权限不受影响
+    assert_eq!(mint_account.freeze_authority, COption::Some(mint));
+
+    let result = process(&mut ctx, &[mint_spl_ix(&admin.pubkey(), &mint, &user, 1)], &[&admin]).await;
+    assert_error(result, CustomError::MintingRevoked.into());
+    let result = process(&mut ctx, &[revoke_mint_authority_ix(&admin.pubkey(), &mint)], &[&admin]).await;
+    assert_error(result, CustomError::MintingRevoked.into());
+}
+
+#[tokio::test]
+async fn burns_reduce_supply_without_touching_positions() {
+    let Fixture { mut ctx, admin, mint } = setup().await;
+    let user = ctx.payer.pubkey();
+    let minted = 1_000 * 10u64.pow(DECIMALS as u32);
+    let vault = ata(&pda(&[b"stack", user.as_ref()]), &mint);
+
+    // 持有人销毁自己的代币
+    process(&mut ctx, &[burn_spl_ix(&user, &mint, 100)], &[]).await.unwrap();
+    assert_eq!(token_balance(&mut ctx, ata(&user, &mint)).await, minted - 100);
+
+    // 质押 500，再直接向质押ATA转入 50，只有这 50 可以被管理员销毁
+    process(&mut ctx, &[deposit_spl_ix(&user, &mint, 500)], &[]).await.unwrap();
+    let user_ata = ata(&user, &mint);
+    let transfer = spl_token::instruction::transfer(&TOKEN_PROGRAM_ID, &user_ata, &vault, &user, &[], 50).unwrap();
+    process(&mut ctx, &[transfer], &[]).await.unwrap();
+
+    let result = process(&mut ctx, &[burn_from_vault_ix(&user, &user, &mint, 50)], &[]).await;
+    assert_error(result, CustomError::Unauthorized.into());
+    let result = process(&mut ctx, &[burn_from_vault_ix(&admin.pubkey(), &user, &mint, 51)], &[&admin]).await;
+    assert_error(result, CustomError::BurnExceedsSurplus.into());
+    process(&mut ctx, &[burn_from_vault_ix(&admin.pubkey(), &user, &mint, 50)], &[&admin]).await.unwrap();
+
+    let mint_account: Mint = fetch(&mut ctx, mint).await;
+    assert_eq!(mint_account.supply, minted - 150);
+    let mint_config: MintConfig = fetch(&mut ctx, pda(&[b"mint_config", mint.as_ref()])).await;
+    assert_eq!(mint_config.burned, 150);
+    let position: StackAccount = fetch(&mut ctx, pda(&[user.as_ref(), mint.as_ref()])).await;
+    assert_eq!(position.balance, 500);
+    assert_eq!(token_balance(&mut ctx, vault).await, 500);
+
+    // 仓位仍可全额提现
+    process(&mut ctx, &[withdraw_spl_ix(&user, &mint)], &[]).await.unwrap();
+    assert_eq!(token_balance(&mut ctx, ata(&user, &mint)).await, minted - 150);
+}
+
+// 按旧版本的字段顺序手工编码仓位：0 为初始的 balance + 两个bump，1 增加锁定时间，2 增加奖励字段
+fn legacy_position_data(version: usize, balance: u64, position_bump: u8, vault_bump: u8) -> Vec<u8> {
+    let mut data = StackAccount::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&balance.to_le_bytes());
+    data.extend_from_slice(&[position_bump, vault_bump]);
+    if version == 0 {
+        data.resize(8 + 16, 0); // 初始版本的 space
+    }
+    if version >= 1 {
+        data.extend_from_slice(&[0; 16]); // lock_start、lock_end
+    }
+    if version >= 2 {
+        data.extend_from_slice(&[0; 24]); // reward_debt、pending_rewards
+    }
+    assert_eq!(data.len(), LEGACY_STACK_ACCOUNT_SPACES[version]);
+    data
+}
+
+// 把奖励池的 total_staked 直接改写为 total_staked，模拟奖励池上线之前的仓位
+async fn set_total_staked(ctx: &mut ProgramTestContext, reward_pool: Pubkey, total_staked: u64) {
+    let mut pool: RewardPool = fetch(ctx, reward_pool).await;
+    pool.total_staked = total_staked;
+    let mut account = ctx.banks_client.get_account(reward_pool).await.unwrap().unwrap();
+    account.data.clear();
+    pool.try_serialize(&mut account.data).unwrap();
+    ctx.set_account(&reward_pool, &account.into());
+}
+
+async fn advance_clock(ctx: &mut ProgramTestContext, seconds: i64) {
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += seconds;
+    ctx.set_sysvar(&clock);
+}
+
+#[tokio::test]
+async fn legacy_position_is_migrated_in_place() {
+    let Fixture { mut ctx, admin, mint } = setup().await;
+    let user = ctx.payer.pubkey();
+    let (_, vault_bump) = Pubkey::find_program_address(&[b"stack", user.as_ref()], &task_2::ID);
+    let (position, position_bump) = Pubkey::find_program_address(&[user.as_ref(), mint.as_ref()], &task_2::ID);
+    let reward_pool = pda(&[b"reward_pool", mint.as_ref()]);
+    let user_ata = ata(&user, &mint);
+
+    // TT1 同时作为奖励代币，每秒发放 10；admin 质押 100，奖励池从一开始就在累计
+    process(
+        &mut ctx,
+        &[
+            mint_spl_ix(&admin.pubkey(), &mint, &admin.pubkey(), 1_000_100),
+            configure_reward_pool_ix(&admin.pubkey(), &mint, &mint, 10),
+            fund_reward_pool_ix(&admin.pubkey(), &mint, &mint, 1_000_000),
+            deposit_spl_ix(&admin.pubkey(), &mint, 100),
+        ],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+
+    for (version, &space) in LEGACY_STACK_ACCOUNT_SPACES.iter().enumerate() {
+        advance_clock(&mut ctx, 1_000).await;
+
+        // 质押ATA中存有 100 枚，仓位账户替换为旧版本布局
+        process(&mut ctx, &[deposit_spl_ix(&user, &mint, 100)], &[]).await.unwrap();
+        let current = ctx.banks_client.get_account(position).await.unwrap().unwrap();
+        let data = if version < REWARD_STACK_ACCOUNT_VERSION as usize {
+            // 奖励池之前的仓位不在 total_staked 中
+            set_total_staked(&mut ctx, reward_pool, 100).await;
+            legacy_position_data(version, 100, position_bump, vault_bump)
+        } else {
+            // 版本2是当前布局去掉 version 和预留空间，保留已结算的 reward_debt
+            current.data[..space].to_vec()
+        };
+        let legacy_rent = Rent::default().minimum_balance(data.len());
+        let legacy = Account { lamports: legacy_rent, data, owner: task_2::ID, executable: false, rent_epoch: 0 };
+        ctx.set_account(&position, &legacy.into());
+
+        // 旧布局按各自的版本号读取，但迁移前不能直接使用
+        let raw = ctx.banks_client.get_account(position).await.unwrap().unwrap();
+        let old = StackAccount::try_deserialize_versioned(&raw.data).unwrap();
+        assert_eq!((old.version as usize, old.balance), (version, 100));
+        let result = process(&mut ctx, &[withdraw_spl_ix(&user, &mint)], &[]).await;
+        assert_error(result, anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into());
+
+        // 只能由仓位所有者迁移
+        let mut foreign = migrate_position_ix(&admin.pubkey(), Some(mint));
+        foreign.accounts[1].pubkey = position;
+        let result = process(&mut ctx, &[foreign], &[&admin]).await;
+        assert_error(result, CustomError::Unauthorized.into());
+
+        // spl 仓位必须带上该代币的奖励池
+        let mut without_pool = migrate_position_ix(&user, Some(mint));
+        without_pool.accounts[2].pubkey = task_2::ID;
+        let result = process(&mut ctx, &[without_pool], &[]).await;
+        assert_error(result, CustomError::RewardPoolMismatch.into());
+
+        let before = lamports(&mut ctx, user).await;
+        process(&mut ctx, &[migrate_position_ix(&user, Some(mint))], &[]).await.unwrap();
+        let raw = ctx.banks_client.get_account(position).await.unwrap().unwrap();
+        assert_eq!(raw.data.len(), 8 + StackAccount::INIT_SPACE);
+        assert_eq!(raw.lamports, Rent::default().minimum_balance(raw.data.len()));
+        assert!(before - lamports(&mut ctx, user).await >= raw.lamports - legacy_rent);
+        let migrated: StackAccount = fetch(&mut ctx, position).await;
+        assert_eq!((migrated.version, migrated.balance), (STACK_ACCOUNT_VERSION, 100));
+        assert_eq!((migrated.stack_account_pump, migrated.stack_account_pda_pump), (position_bump, vault_bump));
+        let pool: RewardPool = fetch(&mut ctx, reward_pool).await;
+        assert_eq!(pool.total_staked, 200);
+        // 迁移后的仓位不能领取迁移之前的奖励
+        let result = process(&mut ctx, &[claim_rewards_ix(&user, &mint, &mint)], &[]).await;
+        assert_error(result, CustomError::NoRewardsToClaim.into());
+
+        let result = process(&mut ctx, &[migrate_position_ix(&user, Some(mint))], &[]).await;
+        assert_error(result, CustomError::PositionAlreadyMigrated.into());
+
+        // 100 秒内与 admin 平分奖励
+        advance_clock(&mut ctx, 100).await;
+        let before = token_balance(&mut ctx, user_ata).await;
+        process(&mut ctx, &[claim_rewards_ix(&user, &mint, &mint)], &[]).await.unwrap();
+        assert_eq!(token_balance(&mut ctx, user_ata).await, before + 500);
+
+        // 迁移后仓位照常提现，total_staked 只扣除自己的余额
+        let before = token_balance(&mut ctx, user_ata).await;
+        process(&mut ctx, &[withdraw_spl_ix(&user, &mint)], &[]).await.unwrap();
+        let withdrawn: StackAccount = fetch(&mut ctx, position).await;
+        assert_eq!(withdrawn.balance, 0);
+        assert_eq!(token_balance(&mut ctx, user_ata).await, before + 100);
+        let pool: RewardPool = fetch(&mut ctx, reward_pool).await;
+        assert_eq!(pool.total_staked, 100);
+    }
+}
+
+// 打包后的 SPL Token 账户数据，直接写入bank
+fn token_program_account<T: Pack>(state: T) -> Account {
+    let mut data = vec![0; T::LEN];
+    state.pack_into_slice(&mut data);
+    Account { lamports: Rent::default().minimum_balance(data.len()), data, owner: TOKEN_PROGRAM_ID, executable: false, rent_epoch: 0 }
+}
+
+#[tokio::test]
+async fn legacy_spl_position_is_withdrawn_and_closed() {
+    let Fixture { mut ctx, admin, .. } = setup().await;
+    let user = ctx.payer.pubkey();
+    let legacy_mint = pda(&[b"mint"]);
+    let (vault_authority, vault_bump) = Pubkey::find_program_address(&[b"stack", user.as_ref()], &task_2::ID);
+    let (position, position_bump) = Pubkey::find_program_address(&[user.as_ref()], &task_2::ID);
+    let vault = ata(&vault_authority, &legacy_mint);
+    let user_ata = ata(&user, &legacy_mint);
+
+    // 初始版本：全局 [b"mint"] 代币由自身PDA铸造，质押ATA中存有 500 枚，[user] 仓位为 24 字节布局
+    let mint_state = SplMint {
+        mint_authority: COption::Some(legacy_mint),
+        supply: 500,
+        decimals: DECIMALS,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    ctx.set_account(&legacy_mint, &token_program_account(mint_state).into());
+    for (address, owner, amount) in [(vault, vault_authority, 500), (user_ata, user, 0)] {
+        let state = SplTokenAccount { mint: legacy_mint, owner, amount, state: AccountState::Initialized, ..SplTokenAccount::default() };
+        ctx.set_account(&address, &token_program_account(state).into());
+    }
+    let set_position = |ctx: &mut ProgramTestContext, version: usize| {
+        let data = legacy_position_data(version, 500, position_bump, vault_bump);
+        let lamports = Rent::default().minimum_balance(data.len());
+        ctx.set_account(&position, &Account { lamports, data, owner: task_2::ID, executable: false, rent_epoch: 0 }.into());
+    };
+
+    // 只接受初始版本的布局
+    set_position(&mut ctx, 2);
+    let result = process(&mut ctx, &[withdraw_spl_legacy_ix(&user)], &[]).await;
+    assert_error(result, CustomError::NotLegacyPosition.into());
+    set_position(&mut ctx, 0);
+    // 初始版本按 space = 8 + 16 分配仓位账户
+    let raw = ctx.banks_client.get_account(position).await.unwrap().unwrap();
+    assert_eq!(raw.data.len(), 8 + 16);
+    let legacy_rent = raw.lamports;
+
+    // 暂停时同样不能提取
+    process(&mut ctx, &[set_paused_ix(&admin.pubkey(), true)], &[&admin]).await.unwrap();
+    let result = process(&mut ctx, &[withdraw_spl_legacy_ix(&user)], &[]).await;
+    assert_error(result, CustomError::ProgramPaused.into());
+    process(&mut ctx, &[set_paused_ix(&admin.pubkey(), false)], &[&admin]).await.unwrap();
+
+    // 其他用户不能提取该仓位
+    let mut foreign = withdraw_spl_legacy_ix(&admin.pubkey());
+    foreign.accounts[2].pubkey = position;
+    let result = process(&mut ctx, &[foreign], &[&admin]).await;
+    assert_error(result, anchor_lang::error::ErrorCode::ConstraintSeeds.into());
+
+    let before = lamports(&mut ctx, user).await;
+    process(&mut ctx, &[withdraw_spl_legacy_ix(&user)], &[]).await.unwrap();
+    assert_eq!(token_balance(&mut ctx, user_ata).await, 500);
+    assert_eq!(token_balance(&mut ctx, vault).await, 0);
+    assert!(ctx.banks_client.get_account(position).await.unwrap().is_none());
+    assert!(lamports(&mut ctx, user).await > before + legacy_rent / 2);
+
+    // 旧仓位已关闭，不能重复提取
+    let result = process(&mut ctx, &[withdraw_spl_legacy_ix(&user)], &[]).await;
+    assert!(result.is_err());
+}
@@ -10,84 +10,366 @@ declare_id!("62GKaiorngxb3x15sqHL8SPZEiz2EyxkiRUJVUQcZ9Zf");
     质押：用户支付solana，并将用户支付的数量记录下来，确保可以获取用户deposit的总余额
     2. withdraw
     提款：一次性提取用户支付的所有solana
+    3. withdraw_amount
+    部分提款：提取指定数量的solana，超过质押余额时报错
+    4. deposit_locked
+    锁仓质押：质押并设置锁定时长，锁定结束前不能提款，也不能再做未锁定的充值
+    5. close_position
+    关闭仓位：余额为0时关闭数据账户和质押账户，退回租金
+    6. initialize_config / set_paused / update_config
+    全局配置：管理员可以暂停充值和提现；应急模式下只允许提现
+    7. propose_admin / accept_admin
+    管理员两步移交：现管理员提名，新管理员接受后生效（可以移交给 task_2 的多签签名PDA）
+    8. collect_fees
+    手续费：充值和提现按配置的万分比收取，存入金库PDA，管理员提取
+    9. migrate_position
+    迁移：质押账户带布局版本和预留空间，旧布局账户由用户扩容（补交租金）后继续使用
 */
 #[program]
 pub mod task_1 {
     use super::*;
 
-    // 用户质押 SOL（充值）
+    // 初始化全局配置（仅程序升级权限账户可操作，只能执行一次），签名者成为管理员
+    pub fn initialize_config(ctx: Context<InitializeConfig>, params: ConfigParams) -> Result<()> {
+        params.validate()?;
+
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.authority.key();
+        config.pending_admin = Pubkey::default();
+        config.paused = false;
+        config.apply(&params);
+        config.bump = ctx.bumps.config;
+
+        msg!("Config initialized, admin {}", config.admin);
+        config.emit_updated()
+    }
+
+    // 暂停或恢复全部充值和提现（仅管理员可操作）
+    pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.paused = paused;
+
+        msg!("Program paused: {}", paused);
+        config.emit_updated()
+    }
+
+    // 修改应急模式和手续费参数（仅管理员可操作）
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        params.validate()?;
+
+        let config = &mut ctx.accounts.config;
+        config.apply(&params);
+
+        msg!(
+            "Config updated: emergency {} deposit fee {} bps withdraw fee {} bps",
+            config.emergency,
+            config.deposit_fee_bps,
+            config.withdraw_fee_bps
+        );
+        config.emit_updated()
+    }
+
+    // 提名新管理员（仅管理员可操作），新管理员调用 accept_admin 后生效；提名默认地址表示取消
+    pub fn propose_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.pending_admin = new_admin;
+
+        msg!("Admin {} proposed {} as new admin", config.admin, new_admin);
+        config.emit_updated()
+    }
+
+    // 被提名的管理员接受管理权限
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        msg!("Admin changed from {} to {}", config.admin, config.pending_admin);
+        config.admin = config.pending_admin;
+        config.pending_admin = Pubkey::default();
+
+        config.emit_updated()
+    }
+
+    // 用户质押 SOL（充值），锁定中的仓位只能用 deposit_locked 追加
     pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-        let user: AccountInfo<'_> = ctx.accounts.user.to_account_info();
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.stack_account.require_flexible_deposit(now)?;
+
+        deposit_lamports(ctx, amount)
+    }
+
+    // 用户锁仓质押 SOL，lock_duration 为锁定秒数
+    pub fn deposit_locked(ctx: Context<Deposit>, amount: u64, lock_duration: i64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.stack_account.lock(now, lock_duration)?;
+
+        deposit_lamports(ctx, amount)
+    }
+
+    // 用户提款（提取全部 SOL）
+    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+        // 判断质押账户余额
+        let balance = ctx.accounts.stack_account.balance;
+        require!(balance > 0, CustomError::InsufficientBalance);
+
+        withdraw_lamports(ctx, balance)
+    }
+
+    // 用户部分提款（提取指定数量的 SOL）
+    pub fn withdraw_amount(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        // 判断提款金额是否超过质押余额
+        require!(amount > 0, CustomError::ZeroAmount);
+        let balance = ctx.accounts.stack_account.balance;
+        require!(balance > 0, CustomError::InsufficientBalance);
+        require!(amount <= balance, CustomError::WithdrawAmountExceedsBalance);
+
+        withdraw_lamports(ctx, amount)
+    }
+
+    // 关闭已清空的仓位，数据账户由 close 约束关闭，质押账户的租金转回用户
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        let user = ctx.accounts.user.to_account_info();
         let stack_account = ctx.accounts.stack_account_pda.to_account_info();
         let system = ctx.accounts.system_program.to_account_info();
-        let stack_account_pda_pump = ctx.accounts.stack_account.stack_account_pda_pump;
-        let stack_account_pump = ctx.accounts.stack_account.stack_account_pump;
+        let refunded = stack_account.lamports();
 
-        // 用户充值到质押账户
+        let bump = ctx.accounts.stack_account.stack_account_pda_pump;
+        let seeds: &[&[u8]] = &[b"stack", user.key.as_ref(), &[bump]];
+        let signer = &[seeds];
         anchor_lang::system_program::transfer(
-            CpiContext::new(
+            CpiContext::new_with_signer(
                 system,
                 anchor_lang::system_program::Transfer {
-                    from: user,
-                    to: stack_account,
+                    from: stack_account,
+                    to: user,
                 },
+                signer,
             ),
-            amount,
+            refunded,
         )?;
 
-        // 更新余额
-        ctx.accounts.stack_account.balance += amount;
-        // 记录bump
-        if (stack_account_pda_pump == 0) {
-            ctx.accounts.stack_account.stack_account_pda_pump = ctx.bumps.stack_account_pda;
-        }
+        msg!("Closed stack account, refunded {} lamports of vault rent", refunded);
+        emit!(ClosePositionEvent {
+            user: ctx.accounts.user.key(),
+            refunded,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
 
-        if (stack_account_pump == 0) {
-            ctx.accounts.stack_account.stack_account_pump = ctx.bumps.stack_account;
+    // 把任一旧版本布局的质押账户扩容到当前布局，新增字段按0补齐，新增的租金由用户支付
+    pub fn migrate_position(ctx: Context<MigratePosition>) -> Result<()> {
+        let user = ctx.accounts.user.to_account_info();
+        let position = ctx.accounts.stack_account.to_account_info();
+
+        let mut stack_account = StackAccount::try_deserialize_versioned(&position.try_borrow_data()?)?;
+        require!(stack_account.version < STACK_ACCOUNT_VERSION, CustomError::PositionAlreadyMigrated);
+
+        // 补足新布局所需的租金后扩容
+        let space = 8 + StackAccount::INIT_SPACE;
+        let rent_paid = Rent::get()?.minimum_balance(space).saturating_sub(position.lamports());
+        if rent_paid > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: user.clone(),
+                        to: position.clone(),
+                    },
+                ),
+                rent_paid,
+            )?;
         }
+        position.realloc(space, false)?;
+
+        stack_account.version = STACK_ACCOUNT_VERSION;
+        stack_account.try_serialize(&mut &mut position.try_borrow_mut_data()?[..])?;
 
-        msg!("Deposit {} lamports to user stack account", amount);
+        msg!("Migrated position {} to version {}, rent paid {}", position.key(), STACK_ACCOUNT_VERSION, rent_paid);
+        emit!(PositionMigratedEvent {
+            user: user.key(),
+            position: position.key(),
+            version: STACK_ACCOUNT_VERSION,
+            rent_paid,
+            slot: Clock::get()?.slot,
+        });
         Ok(())
     }
 
-    // 用户提款（提取全部 SOL）
-    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
-        let user = ctx.accounts.user.to_account_info();
-        let stack_account: AccountInfo<'_> = ctx.accounts.stack_account_pda.to_account_info();
-        let system = ctx.accounts.system_program.to_account_info();
+    // 提取金库中的手续费（仅管理员可操作），金库保留免租金的最低余额
+    pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+        let treasury = ctx.accounts.treasury.to_account_info();
+        let rent_exempt = Rent::get()?.minimum_balance(0);
+        let amount = treasury.lamports().saturating_sub(rent_exempt);
+        require!(amount > 0, CustomError::NoFeesToCollect);
 
-        // 查询
-        let balance = ctx.accounts.stack_account.balance;
-        let pda_balance = ctx.accounts.stack_account_pda.get_lamports();
+        let seeds: &[&[u8]] = &[b"treasury", &[ctx.bumps.treasury]];
+        let signer = &[seeds];
+        anchor_lang::system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: treasury,
+                    to: ctx.accounts.recipient.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+        )?;
 
-        // 判断质押账户余额
-        require!(balance > 0, CustomError::InsufficientBalance);
+        msg!("Collected {} lamports of fees to {}", amount, ctx.accounts.recipient.key());
+        emit!(FeesCollectedEvent {
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+}
 
-        // 将余额返回给用户
-        // let bump = Pubkey::find_program_address(&[b"stack", user.key.as_ref()], &id()).1;
-        let bump = ctx.accounts.stack_account.stack_account_pda_pump;
+// 把 amount lamports（扣除手续费后）充值到质押账户，并同步增加记录的余额
+fn deposit_lamports(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+    ctx.accounts.config.require_deposits_allowed()?;
+    require!(amount > 0, CustomError::ZeroAmount);
 
-        msg!("find_program_address program_id {} bump {} balance:{} pda_balance:{}", &id(), bump, balance, pda_balance);
-        // drop(pda_balance); // 释放borrow 仅为log
+    // 手续费从充值金额中扣除，扣除后必须仍有余额入账
+    let fee = ctx.accounts.config.deposit_fee(amount)?;
+    let net = amount.checked_sub(fee).ok_or(CustomError::MathOverflow)?;
+    require!(net > 0, CustomError::ZeroAmount);
 
-        let seeds: &[&[u8]] = &[b"stack", user.key.as_ref(), &[bump]];
-        let signer = &[&seeds[..]];
+    let user: AccountInfo<'_> = ctx.accounts.user.to_account_info();
+    let stack_account = ctx.accounts.stack_account_pda.to_account_info();
+    let system = ctx.accounts.system_program.to_account_info();
+    let stack_account_pda_pump = ctx.accounts.stack_account.stack_account_pda_pump;
+    let stack_account_pump = ctx.accounts.stack_account.stack_account_pump;
+
+    // 用户充值到质押账户
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            system.clone(),
+            anchor_lang::system_program::Transfer {
+                from: user.clone(),
+                to: stack_account,
+            },
+        ),
+        net,
+    )?;
+    // 手续费转入金库
+    if fee > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system,
+                anchor_lang::system_program::Transfer {
+                    from: user,
+                    to: ctx.accounts.treasury.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+    }
+
+    // 更新余额
+    ctx.accounts.stack_account.balance = ctx
+        .accounts
+        .stack_account
+        .balance
+        .checked_add(net)
+        .ok_or(CustomError::MathOverflow)?;
+    // 记录bump
+    if stack_account_pda_pump == 0 {
+        ctx.accounts.stack_account.stack_account_pda_pump = ctx.bumps.stack_account_pda;
+    }
+
+    if stack_account_pump == 0 {
+        ctx.accounts.stack_account.stack_account_pump = ctx.bumps.stack_account;
+    }
+    ctx.accounts.stack_account.version = STACK_ACCOUNT_VERSION;
+
+    msg!("Deposit {} lamports to user stack account, fee {}", amount, fee);
+    emit!(DepositEvent {
+        user: ctx.accounts.user.key(),
+        amount,
+        fee,
+        balance: ctx.accounts.stack_account.balance,
+        lock_end: ctx.accounts.stack_account.lock_end,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 从质押账户转出 amount lamports 给用户，并同步扣减记录的余额
+fn withdraw_lamports(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    let config = Config::load_or_default(&ctx.accounts.config)?;
+    config.require_withdrawals_allowed()?;
+
+    let user = ctx.accounts.user.to_account_info();
+    let stack_account: AccountInfo<'_> = ctx.accounts.stack_account_pda.to_account_info();
+    let system = ctx.accounts.system_program.to_account_info();
+
+    // 锁定期内不能提款
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.stack_account.require_unlocked(now)?;
+
+    // 查询
+    let balance = ctx.accounts.stack_account.balance;
+    let pda_balance = ctx.accounts.stack_account_pda.get_lamports();
+
+    // 将余额返回给用户
+    // let bump = Pubkey::find_program_address(&[b"stack", user.key.as_ref()], &id()).1;
+    let bump = ctx.accounts.stack_account.stack_account_pda_pump;
+
+    msg!("find_program_address program_id {} bump {} balance:{} pda_balance:{}", &id(), bump, balance, pda_balance);
+    // drop(pda_balance); // 释放borrow 仅为log
+
+    // 手续费从提取数量中扣除
+    let fee = config.withdraw_fee(amount)?;
+    let net = amount.checked_sub(fee).ok_or(CustomError::MathOverflow)?;
+
+    let seeds: &[&[u8]] = &[b"stack", user.key.as_ref(), &[bump]];
+    let signer = &[seeds];
+    anchor_lang::system_program::transfer(
+        CpiContext::new_with_signer(
+            system.clone(),
+            anchor_lang::system_program::Transfer {
+                from: stack_account.clone(),
+                to: user,
+            },
+            signer,
+        ),
+        net,
+    )?;
+    if fee > 0 {
         anchor_lang::system_program::transfer(
             CpiContext::new_with_signer(
                 system,
                 anchor_lang::system_program::Transfer {
                     from: stack_account,
-                    to: user,
+                    to: ctx.accounts.treasury.to_account_info(),
                 },
                 signer,
             ),
-            balance,
+            fee,
         )?;
-        ctx.accounts.stack_account.balance -= balance;
-
-        msg!("Withdrew {} lamports to user wallet", balance);
-        Ok(())
     }
+    ctx.accounts.stack_account.balance = ctx
+        .accounts
+        .stack_account
+        .balance
+        .checked_sub(amount)
+        .ok_or(CustomError::MathOverflow)?;
+
+    msg!(
+        "Withdrew {} lamports to user wallet, fee {}, remaining balance {}",
+        net,
+        fee,
+        ctx.accounts.stack_account.balance
+    );
+    emit!(WithdrawEvent {
+        user: ctx.accounts.user.key(),
+        amount,
+        fee,
+        balance: ctx.accounts.stack_account.balance,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
 }
 
 /** 充值结构体 */
@@ -110,9 +392,21 @@ pub struct Deposit<'info> {
         payer = user, 
         seeds = [user.key.as_ref()],
         bump, 
-        space = 8 + 16 // #[drive(init space)] or std::mem::size_of::<StackAccount>()
+        space = 8 + StackAccount::INIT_SPACE
     )]
     pub stack_account: Account<'info, StackAccount>,  // 数据账户，可以随意更改不用签名
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>, // 全局配置，检查暂停状态
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"treasury"],
+        owner = system_program::ID, // 系统账户
+        bump,
+        space = 0
+    )]
+    /// CHECK: This is a PDA verified in constraints.
+    pub treasury: AccountInfo<'info>, // 手续费金库
     pub system_program: Program<'info, System>,
 }
 
@@ -121,20 +415,279 @@ pub struct Deposit<'info> {
 pub struct Withdraw<'info> {
     #[account(mut, signer)]
     pub user: Signer<'info>, // 提现用户
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"stack", user.key.as_ref()],
+        bump = stack_account.stack_account_pda_pump, // 使用记录的bump重新推导，确保是当前用户的质押账户
+    )]
     /// CHECK: This is a PDA verified in constraints.
     pub stack_account_pda: AccountInfo<'info>, // 存储SOL的普通质押账户
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [user.key.as_ref()],
+        bump = stack_account.stack_account_pump, // 数据账户必须属于当前签名用户
+    )]
+    pub stack_account: Account<'info, StackAccount>, // 质押数据账户
+    #[account(seeds = [b"config"], bump)]
+    /// CHECK: Loaded with Config::load_or_default, may not be initialized yet.
+    pub config: UncheckedAccount<'info>, // 全局配置，未初始化时视为未暂停、不收手续费
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"treasury"],
+        owner = system_program::ID, // 系统账户
+        bump,
+        space = 0
+    )]
+    /// CHECK: This is a PDA verified in constraints.
+    pub treasury: AccountInfo<'info>, // 手续费金库
+    pub system_program: Program<'info, System>,
+}
+
+/** 关闭仓位结构体 */
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(mut, signer)]
+    pub user: Signer<'info>, // 仓位用户，接收退回的租金
+    #[account(
+        mut,
+        seeds = [b"stack", user.key.as_ref()],
+        bump = stack_account.stack_account_pda_pump,
+    )]
+    /// CHECK: This is a PDA verified in constraints.
+    pub stack_account_pda: AccountInfo<'info>, // 存储SOL的普通质押账户
+    #[account(
+        mut,
+        seeds = [user.key.as_ref()],
+        bump = stack_account.stack_account_pump,
+        constraint = stack_account.balance == 0 @ CustomError::PositionNotEmpty, // 余额清空后才能关闭
+        close = user,
+    )]
     pub stack_account: Account<'info, StackAccount>, // 质押数据账户
     pub system_program: Program<'info, System>,
 }
 
-/** 质押账户结构体 */
+/** 迁移旧布局质押账户结构体 */
+#[derive(Accounts)]
+pub struct MigratePosition<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>, // 仓位所有者，支付扩容的租金
+    #[account(
+        mut,
+        seeds = [user.key.as_ref()], // 只能迁移自己的仓位
+        bump,
+        owner = crate::ID,
+    )]
+    /// CHECK: Old layouts cannot be loaded as StackAccount; seeds and owner are checked here.
+    pub stack_account: UncheckedAccount<'info>, // 待迁移的质押数据账户
+    pub system_program: Program<'info, System>,
+}
+
+/** 初始化配置结构体 */
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut, signer)]
+    pub authority: Signer<'info>, // 程序升级权限账户
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"config"],
+        bump,
+        space = 8 + Config::INIT_SPACE,
+    )]
+    pub config: Account<'info, Config>, // 全局配置账户
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Task1>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ CustomError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+/** 修改配置结构体 */
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>, // 配置管理员
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+/** 接受管理员结构体 */
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub pending_admin: Signer<'info>, // 被提名的管理员
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.pending_admin == pending_admin.key() @ CustomError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+/** 提取手续费结构体 */
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    pub admin: Signer<'info>, // 配置管理员
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump,
+        owner = system_program::ID,
+    )]
+    /// CHECK: This is a PDA verified in constraints.
+    pub treasury: AccountInfo<'info>, // 手续费金库
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>, // 接收手续费的钱包
+    pub system_program: Program<'info, System>,
+}
+
+// Config 账户、ConfigParams 和手续费计算与 task_2 共用
+stake_config::config_account!(CustomError, ConfigUpdatedEvent);
+
+/** 质押账户结构体
+    新字段从 reserved 中分配并提升 version，不需要改变账户大小
+*/
 #[account]
+#[derive(InitSpace)]
 pub struct StackAccount {
     pub balance: u64, // 记录用户的质押金额
     pub stack_account_pump: u8, 
-    pub stack_account_pda_pump: u8
+    pub stack_account_pda_pump: u8,
+    pub lock_start: i64,    // 锁定开始时间（unix秒），0表示未锁定
+    pub lock_end: i64,      // 锁定结束时间（unix秒），之前不能提款
+    pub version: u8,        // 布局版本，旧布局账户按大小读取为对应的旧版本号
+    pub reserved: [u8; 64], // 预留空间
+}
+
+// 当前质押账户布局版本
+pub const STACK_ACCOUNT_VERSION: u8 = 2;
+// 旧布局账户大小（含 discriminator），下标即读取时的版本号
+pub const LEGACY_STACK_ACCOUNT_SPACES: [usize; 2] = [
+    8 + 16,                // 0：初始版本按 space = 8 + 16 分配，balance 和两个bump 之后有6字节未使用
+    8 + 8 + 1 + 1 + 8 + 8, // 1：增加锁定时间
+];
+
+// 最长锁定时长：4年
+pub const MAX_LOCK_DURATION: i64 = 4 * 365 * 24 * 60 * 60;
+
+impl StackAccount {
+    // 读取任意版本的质押账户：按账户大小识别旧布局，缺少的字段按0补齐，version 设为对应的旧版本号
+    pub fn try_deserialize_versioned(data: &[u8]) -> Result<Self> {
+        if data.len() >= 8 + Self::INIT_SPACE {
+            return Self::try_deserialize(&mut &data[..]);
+        }
+        let version = LEGACY_STACK_ACCOUNT_SPACES
+            .iter()
+            .position(|&space| space == data.len())
+            .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        let mut padded = data.to_vec();
+        padded.resize(8 + Self::INIT_SPACE, 0);
+        let mut stack_account = Self::try_deserialize(&mut padded.as_slice())?;
+        stack_account.version = version as u8;
+        Ok(stack_account)
+    }
+
+    // 锁定整个仓位，新的锁定只能延长不能缩短
+    // 锁定会作用于仓位的全部余额，已有未锁定的余额时拒绝，需要先提取
+    pub fn lock(&mut self, now: i64, lock_duration: i64) -> Result<()> {
+        require!(
+            lock_duration > 0 && lock_duration <= MAX_LOCK_DURATION,
+            CustomError::InvalidLockDuration
+        );
+        require!(self.balance == 0 || now < self.lock_end, CustomError::UnlockedBalance);
+        let lock_end = now.checked_add(lock_duration).ok_or(CustomError::MathOverflow)?;
+        if lock_end > self.lock_end {
+            self.lock_start = now;
+            self.lock_end = lock_end;
+        }
+        msg!("Position locked from {} until {}", self.lock_start, self.lock_end);
+        Ok(())
+    }
+
+    // 未锁定的充值不能进入锁定中的仓位，否则会被一起锁定到 lock_end
+    pub fn require_flexible_deposit(&self, now: i64) -> Result<()> {
+        require!(now >= self.lock_end, CustomError::DepositIntoLockedPosition);
+        Ok(())
+    }
+
+    // 检查仓位是否已解锁
+    pub fn require_unlocked(&self, now: i64) -> Result<()> {
+        if now < self.lock_end {
+            msg!("Position is locked until {} ({} seconds remaining)", self.lock_end, self.lock_end - now);
+            return err!(CustomError::PositionLocked);
+        }
+        Ok(())
+    }
+}
+
+/** 充值事件 */
+#[event]
+pub struct DepositEvent {
+    pub user: Pubkey,
+    pub amount: u64,   // 本次充值金额（含手续费）
+    pub fee: u64,      // 转入金库的手续费
+    pub balance: u64,  // 充值后的质押余额
+    pub lock_end: i64, // 锁定结束时间，0表示未锁定
+    pub slot: u64,
+}
+
+/** 提现事件 */
+#[event]
+pub struct WithdrawEvent {
+    pub user: Pubkey,
+    pub amount: u64,  // 本次从仓位扣除的金额（含手续费）
+    pub fee: u64,     // 转入金库的手续费
+    pub balance: u64, // 提现后的质押余额
+    pub slot: u64,
+}
+
+/** 关闭仓位事件 */
+#[event]
+pub struct ClosePositionEvent {
+    pub user: Pubkey,
+    pub refunded: u64, // 质押账户退回的租金
+    pub slot: u64,
+}
+
+/** 提取手续费事件 */
+#[event]
+pub struct FeesCollectedEvent {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub slot: u64,
+}
+
+/** 仓位迁移事件 */
+#[event]
+pub struct PositionMigratedEvent {
+    pub user: Pubkey,
+    pub position: Pubkey, // 迁移的质押数据账户
+    pub version: u8,      // 迁移后的布局版本
+    pub rent_paid: u64,   // 用户补交的租金
+    pub slot: u64,
+}
+
+/** 全局配置变更事件 */
+#[event]
+pub struct ConfigUpdatedEvent {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey, // 被提名的新管理员，默认地址表示没有
+    pub paused: bool,
+    pub emergency: bool,
+    pub deposit_fee_bps: u16,
+    pub withdraw_fee_bps: u16,
+    pub slot: u64,
 }
 
 /** 自定义异常 */
@@ -142,4 +695,155 @@ pub struct StackAccount {
 pub enum CustomError {
     #[msg("Insufficient balance for withdrawal.")]
     InsufficientBalance,
+    #[msg("Withdraw amount exceeds the staked balance.")]
+    WithdrawAmountExceedsBalance,
+    #[msg("Position is still locked.")]
+    PositionLocked,
+    #[msg("Lock duration must be positive and at most four years.")]
+    InvalidLockDuration,
+    #[msg("Position still holds a balance and cannot be closed.")]
+    PositionNotEmpty,
+    #[msg("Arithmetic overflow or underflow.")]
+    MathOverflow,
+    #[msg("Amount must be greater than zero.")]
+    ZeroAmount,
+    #[msg("Signer is not authorized for this operation.")]
+    Unauthorized,
+    #[msg("Program is paused.")]
+    ProgramPaused,
+    #[msg("Deposits are disabled in emergency mode.")]
+    DepositsDisabled,
+    #[msg("Fee must be at most 10000 basis points.")]
+    InvalidFee,
+    #[msg("Treasury holds no fees to collect.")]
+    NoFeesToCollect,
+    #[msg("Position already uses the current layout.")]
+    PositionAlreadyMigrated,
+    #[msg("Position holds an unlocked balance; withdraw it before locking.")]
+    UnlockedBalance,
+    #[msg("Position is locked; add to it with a locked deposit.")]
+    DepositIntoLockedPosition,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    fn position() -> StackAccount {
+        StackAccount {
+            balance: 0,
+            stack_account_pump: 0,
+            stack_account_pda_pump: 0,
+            lock_start: 0,
+            lock_end: 0,
+            version: STACK_ACCOUNT_VERSION,
+            reserved: [0; 64],
+        }
+    }
+
+    // 按旧版本的字段顺序手工编码，不经过当前的 StackAccount
+    fn legacy_bytes(version: usize) -> Vec<u8> {
+        let mut data = StackAccount::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.extend_from_slice(&[254, 253]);
+        if version == 0 {
+            data.resize(8 + 16, 0); // 初始版本的 space
+        }
+        if version >= 1 {
+            data.extend_from_slice(&10i64.to_le_bytes());
+            data.extend_from_slice(&20i64.to_le_bytes());
+        }
+        assert_eq!(data.len(), LEGACY_STACK_ACCOUNT_SPACES[version]);
+        data
+    }
+
+    #[test]
+    fn each_legacy_layout_reads_with_its_own_version() {
+        let baseline = StackAccount::try_deserialize_versioned(&legacy_bytes(0)).unwrap();
+        assert_eq!((baseline.version, baseline.balance, baseline.lock_end), (0, 1_000, 0));
+        assert_eq!((baseline.stack_account_pump, baseline.stack_account_pda_pump), (254, 253));
+
+        let locked = StackAccount::try_deserialize_versioned(&legacy_bytes(1)).unwrap();
+        assert_eq!((locked.version, locked.balance), (1, 1_000));
+        assert_eq!((locked.lock_start, locked.lock_end), (10, 20));
+    }
+
+    #[test]
+    fn current_layout_round_trips_and_other_sizes_fail() {
+        let mut data = Vec::new();
+        position().try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + StackAccount::INIT_SPACE);
+        let current = StackAccount::try_deserialize_versioned(&data).unwrap();
+        assert_eq!(current.version, STACK_ACCOUNT_VERSION);
+
+        for len in [0, 8, LEGACY_STACK_ACCOUNT_SPACES[0] + 1, LEGACY_STACK_ACCOUNT_SPACES[1] + 1] {
+            assert!(StackAccount::try_deserialize_versioned(&data[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn lock_rejects_invalid_duration() {
+        assert_eq!(position().lock(0, 0).unwrap_err(), CustomError::InvalidLockDuration.into());
+        assert_eq!(position().lock(0, MAX_LOCK_DURATION + 1).unwrap_err(), CustomError::InvalidLockDuration.into());
+    }
+
+    #[test]
+    fn lock_end_overflow_is_rejected() {
+        assert_eq!(position().lock(i64::MAX, 1).unwrap_err(), CustomError::MathOverflow.into());
+    }
+
+    #[test]
+    fn locked_position_refuses_withdrawal() {
+        let mut position = position();
+        position.lock(100, 60).unwrap();
+        assert_eq!(position.require_unlocked(159).unwrap_err(), CustomError::PositionLocked.into());
+        assert!(position.require_unlocked(160).is_ok());
+    }
+
+    #[test]
+    fn lock_rejects_an_unlocked_balance() {
+        // 未锁定的余额不能被新的锁定覆盖
+        let mut position = position();
+        position.balance = 1_000;
+        assert_eq!(position.lock(100, 60).unwrap_err(), CustomError::UnlockedBalance.into());
+
+        // 锁定期内可以追加锁仓并延长，到期后的余额视为未锁定
+        position.lock_end = 150;
+        position.lock(100, 60).unwrap();
+        assert_eq!(position.lock_end, 160);
+        assert_eq!(position.lock(160, 60).unwrap_err(), CustomError::UnlockedBalance.into());
+    }
+
+    #[test]
+    fn flexible_deposit_rejects_a_locked_position() {
+        // 锁定期内的未锁定充值会被一起锁定，到期后恢复正常
+        let mut position = position();
+        assert!(position.require_flexible_deposit(100).is_ok());
+        position.lock(100, 60).unwrap();
+        assert_eq!(
+            position.require_flexible_deposit(159).unwrap_err(),
+            CustomError::DepositIntoLockedPosition.into()
+        );
+        assert!(position.require_flexible_deposit(160).is_ok());
+    }
+
+    #[test]
+    fn emergency_mode_only_blocks_deposits() {
+        let mut config = Config {
+            admin: Pubkey::default(),
+            pending_admin: Pubkey::default(),
+            paused: false,
+            emergency: true,
+            deposit_fee_bps: 0,
+            withdraw_fee_bps: 0,
+            bump: 0,
+        };
+        assert_eq!(config.require_deposits_allowed().unwrap_err(), CustomError::DepositsDisabled.into());
+        assert!(config.require_withdrawals_allowed().is_ok());
+
+        config.paused = true;
+        assert_eq!(config.require_deposits_allowed().unwrap_err(), CustomError::ProgramPaused.into());
+        assert_eq!(config.require_withdrawals_allowed().unwrap_err(), CustomError::ProgramPaused.into());
+    }
 }
@@ -25,6 +25,22 @@ describe("task1", () => {
   // 充值金额
   const depositAmount = 0.1 * LAMPORTS_PER_SOL; // 0.1 SOL
 
+  // 全局配置账户
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+  // 手续费金库
+  const [treasuryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("treasury")],
+    program.programId
+  );
+  // 程序升级权限数据账户
+  const [programData] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111"),
+  );
+
   it("Creates a StackAccount PDA", async () => {
     // 生成PDA
     [stackPDA] = await PublicKey.findProgramAddressSync(
@@ -43,6 +59,37 @@ describe("task1", () => {
     console.log("[", user.publicKey, "]user balance:", userBalance / LAMPORTS_PER_SOL);
   });
 
+  it("Initialize config", async () => {
+    // 部署钱包为升级权限账户，初始化后成为管理员
+    await program.methods
+      .initializeConfig({ emergency: false, depositFeeBps: 0, withdrawFeeBps: 0 })
+      .accounts({
+        authority: user.publicKey,
+        config: configPda,
+        program: program.programId,
+        programData: programData,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    const config = await program.account.config.fetch(configPda);
+    assert.isTrue(config.admin.equals(user.publicKey));
+    assert.isFalse(config.paused);
+
+    // 手续费不能超过 100%
+    try {
+      await program.methods
+        .updateConfig({ emergency: false, depositFeeBps: 10_001, withdrawFeeBps: 0 })
+        .accounts({ admin: user.publicKey, config: configPda })
+        .signers([user])
+        .rpc();
+      assert.fail("fee above 10000 bps should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InvalidFee");
+    }
+  });
+
   it("Deposit", async () => {
     const tx = await program.methods
       .deposit(new anchor.BN(depositAmount))
@@ -50,6 +97,8 @@ describe("task1", () => {
         user: user.publicKey,
         stackAccountPda: stackPDA,
         stackAccount: stackAccountData,
+        config: configPda,
+        treasury: treasuryPda,
         systemProgram: SystemProgram.programId,
       })
       .signers([user])
@@ -67,6 +116,8 @@ describe("task1", () => {
         user: user.publicKey,
         stackAccountPda: stackPDA,
         stackAccount: stackAccountData,
+        config: configPda,
+        treasury: treasuryPda,
         systemProgram: SystemProgram.programId,
       })
       .signers([user])
@@ -77,6 +128,188 @@ describe("task1", () => {
     console.log("[", stackAccountData, "]deposit transaction successful! tx: ", tx, "stack balance: ", account.balance.toNumber);
   });
 
+  it("Deposit emits DepositEvent", async () => {
+    // 监听充值事件
+    let listener: number;
+    const event = new Promise<any>((resolve) => {
+      listener = program.addEventListener("depositEvent", (e) => resolve(e));
+    });
+
+    await program.methods
+      .deposit(new anchor.BN(depositAmount))
+      .accounts({
+        user: user.publicKey,
+        stackAccountPda: stackPDA,
+        stackAccount: stackAccountData,
+        config: configPda,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    const deposited = await event;
+    await program.removeEventListener(listener);
+
+    const account = await program.account.stackAccount.fetch(stackAccountData);
+    assert.ok(deposited.user.equals(user.publicKey));
+    assert.strictEqual(deposited.amount.toNumber(), depositAmount);
+    assert.strictEqual(deposited.balance.toNumber(), account.balance.toNumber());
+  });
+
+  it("Withdraw rejects a foreign user's accounts", async () => {
+    // 攻击者钱包
+    const attacker = Keypair.generate();
+    const airdropSig = await program.provider.connection.requestAirdrop(attacker.publicKey, LAMPORTS_PER_SOL);
+    await program.provider.connection.confirmTransaction(airdropSig);
+
+    const [attackerStackPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stack"), attacker.publicKey.toBuffer()],
+      program.programId
+    );
+    const [attackerStackAccountData] = PublicKey.findProgramAddressSync(
+      [attacker.publicKey.toBuffer()],
+      program.programId
+    );
+
+    // 攻击者先质押，拥有自己的数据账户
+    await program.methods
+      .deposit(new anchor.BN(depositAmount))
+      .accounts({
+        user: attacker.publicKey,
+        stackAccountPda: attackerStackPDA,
+        stackAccount: attackerStackAccountData,
+        config: configPda,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([attacker])
+      .rpc();
+
+    // 使用他人的数据账户
+    try {
+      await program.methods
+        .withdraw()
+        .accounts({
+          user: attacker.publicKey,
+          stackAccountPda: attackerStackPDA,
+          stackAccount: stackAccountData,
+          config: configPda,
+          treasury: treasuryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([attacker])
+        .rpc();
+      assert.fail("withdraw with a foreign ledger should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "ConstraintSeeds");
+    }
+
+    // 使用他人的质押账户
+    try {
+      await program.methods
+        .withdraw()
+        .accounts({
+          user: attacker.publicKey,
+          stackAccountPda: stackPDA,
+          stackAccount: attackerStackAccountData,
+          config: configPda,
+          treasury: treasuryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([attacker])
+        .rpc();
+      assert.fail("withdraw from a foreign vault should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "ConstraintSeeds");
+    }
+  });
+
+  it("Withdraw amount", async () => {
+    const withdrawAmount = 0.05 * LAMPORTS_PER_SOL; // 0.05 SOL
+    const before = await program.account.stackAccount.fetch(stackAccountData);
+
+    // 部分提款
+    const tx = await program.methods
+      .withdrawAmount(new anchor.BN(withdrawAmount))
+      .accounts({
+        user: user.publicKey,
+        stackAccountPda: stackPDA,
+        stackAccount: stackAccountData,
+        config: configPda,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    // 检查质押余额已扣减
+    const after = await program.account.stackAccount.fetch(stackAccountData);
+    console.log("[", stackAccountData, "]withdraw amount transaction successful! tx: ", tx, "stack balance: ", after.balance.toNumber());
+    assert.strictEqual(after.balance.toNumber(), before.balance.toNumber() - withdrawAmount);
+  });
+
+  it("Withdraw amount exceeds balance", async () => {
+    const account = await program.account.stackAccount.fetch(stackAccountData);
+
+    // 提款金额超过质押余额
+    try {
+      await program.methods
+        .withdrawAmount(account.balance.addn(1))
+        .accounts({
+          user: user.publicKey,
+          stackAccountPda: stackPDA,
+          stackAccount: stackAccountData,
+          config: configPda,
+          treasury: treasuryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      assert.fail("withdraw amount above balance should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "WithdrawAmountExceedsBalance");
+    }
+  });
+
+  it("Deposit and withdraw amount reject zero", async () => {
+    try {
+      await program.methods
+        .deposit(new anchor.BN(0))
+        .accounts({
+          user: user.publicKey,
+          stackAccountPda: stackPDA,
+          stackAccount: stackAccountData,
+          config: configPda,
+          treasury: treasuryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      assert.fail("zero deposit should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "ZeroAmount");
+    }
+
+    try {
+      await program.methods
+        .withdrawAmount(new anchor.BN(0))
+        .accounts({
+          user: user.publicKey,
+          stackAccountPda: stackPDA,
+          stackAccount: stackAccountData,
+          config: configPda,
+          treasury: treasuryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      assert.fail("zero withdraw should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "ZeroAmount");
+    }
+  });
+
   it("Withdraw", async () => {
     // 获取 stackAccount 的当前余额
     const stackAccountBalance = await program.account.stackAccount.fetch(stackAccountData);
@@ -89,6 +322,8 @@ describe("task1", () => {
         user: user.publicKey,
         stackAccountPda: stackPDA,
         stackAccount: stackAccountData,
+        config: configPda,
+        treasury: treasuryPda,
         systemProgram: SystemProgram.programId,
       })
       .signers([user])
@@ -105,8 +340,339 @@ describe("task1", () => {
     assert.isAbove(updateUserBalance, balanceBeforeWithdraw);
   });
 
+  it("Withdraw from an empty position", async () => {
+    try {
+      await program.methods
+        .withdraw()
+        .accounts({
+          user: user.publicKey,
+          stackAccountPda: stackPDA,
+          stackAccount: stackAccountData,
+          config: configPda,
+          treasury: treasuryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      assert.fail("withdraw from an empty position should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InsufficientBalance");
+    }
+  });
+
+  it("New positions use the current layout and need no migration", async () => {
+    const position = await program.account.stackAccount.fetch(stackAccountData);
+    assert.strictEqual(position.version, 2);
+    try {
+      await program.methods
+        .migratePosition()
+        .accounts({ user: user.publicKey, stackAccount: stackAccountData, systemProgram: SystemProgram.programId })
+        .signers([user])
+        .rpc();
+      assert.fail("migrating a current position should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "PositionAlreadyMigrated");
+    }
+  });
+
+  it("Close position", async () => {
+    const userBalanceBefore = await program.provider.connection.getBalance(user.publicKey);
 
-});
+    // 余额清空后关闭仓位，退回租金
+    await program.methods
+      .closePosition()
+      .accounts({
+        user: user.publicKey,
+        stackAccountPda: stackPDA,
+        stackAccount: stackAccountData,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
 
+    assert.isNull(await program.provider.connection.getAccountInfo(stackAccountData));
+    assert.isNull(await program.provider.connection.getAccountInfo(stackPDA));
+    const userBalanceAfter = await program.provider.connection.getBalance(user.publicKey);
+    assert.isAbove(userBalanceAfter, userBalanceBefore);
+  });
 
+  it("Deposit locked and withdraw refused", async () => {
+    // 新用户锁仓质押
+    const locker = Keypair.generate();
+    const airdropSig = await program.provider.connection.requestAirdrop(locker.publicKey, LAMPORTS_PER_SOL);
+    await program.provider.connection.confirmTransaction(airdropSig);
 
+    const [lockerStackPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stack"), locker.publicKey.toBuffer()],
+      program.programId
+    );
+    const [lockerStackAccountData] = PublicKey.findProgramAddressSync(
+      [locker.publicKey.toBuffer()],
+      program.programId
+    );
+    const lockDuration = 3600; // 锁定1小时
+
+    // 锁定时长必须为正数
+    try {
+      await program.methods
+        .depositLocked(new anchor.BN(depositAmount), new anchor.BN(0))
+        .accounts({
+          user: locker.publicKey,
+          stackAccountPda: lockerStackPDA,
+          stackAccount: lockerStackAccountData,
+          config: configPda,
+          treasury: treasuryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([locker])
+        .rpc();
+      assert.fail("zero lock duration should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InvalidLockDuration");
+    }
+
+    await program.methods
+      .depositLocked(new anchor.BN(depositAmount), new anchor.BN(lockDuration))
+      .accounts({
+        user: locker.publicKey,
+        stackAccountPda: lockerStackPDA,
+        stackAccount: lockerStackAccountData,
+        config: configPda,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([locker])
+      .rpc();
+
+    const account = await program.account.stackAccount.fetch(lockerStackAccountData);
+    assert.strictEqual(account.lockEnd.sub(account.lockStart).toNumber(), lockDuration);
+
+    // 锁定期内的未锁定充值会被一起锁定，必须改用 depositLocked
+    try {
+      await program.methods
+        .deposit(new anchor.BN(depositAmount))
+        .accounts({
+          user: locker.publicKey,
+          stackAccountPda: lockerStackPDA,
+          stackAccount: lockerStackAccountData,
+          config: configPda,
+          treasury: treasuryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([locker])
+        .rpc();
+      assert.fail("flexible deposit into a locked position should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "DepositIntoLockedPosition");
+    }
+
+    // 锁定期内提款失败
+    try {
+      await program.methods
+        .withdraw()
+        .accounts({
+          user: locker.publicKey,
+          stackAccountPda: lockerStackPDA,
+          stackAccount: lockerStackAccountData,
+          config: configPda,
+          treasury: treasuryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([locker])
+        .rpc();
+      assert.fail("withdraw of locked funds should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "PositionLocked");
+    }
+
+    // 有余额的仓位不能关闭
+    try {
+      await program.methods
+        .closePosition()
+        .accounts({
+          user: locker.publicKey,
+          stackAccountPda: lockerStackPDA,
+          stackAccount: lockerStackAccountData,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([locker])
+        .rpc();
+      assert.fail("closing a non-empty position should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "PositionNotEmpty");
+    }
+  });
+
+  it("Deposit locked rejects an unlocked balance", async () => {
+    const accounts = {
+      user: user.publicKey,
+      stackAccountPda: stackPDA,
+      stackAccount: stackAccountData,
+      config: configPda,
+      treasury: treasuryPda,
+      systemProgram: SystemProgram.programId,
+    };
+    await program.methods.deposit(new anchor.BN(depositAmount)).accounts(accounts).signers([user]).rpc();
+
+    // 锁定会作用于整个仓位，已有未锁定的余额时需要先提取
+    try {
+      await program.methods
+        .depositLocked(new anchor.BN(depositAmount), new anchor.BN(3600))
+        .accounts(accounts)
+        .signers([user])
+        .rpc();
+      assert.fail("locking a position with an unlocked balance should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "UnlockedBalance");
+    }
+    await program.methods.withdraw().accounts(accounts).signers([user]).rpc();
+  });
+
+  it("Pause and emergency mode", async () => {
+    const accounts = {
+      user: user.publicKey,
+      stackAccountPda: stackPDA,
+      stackAccount: stackAccountData,
+      config: configPda,
+      treasury: treasuryPda,
+      systemProgram: SystemProgram.programId,
+    };
+    await program.methods.deposit(new anchor.BN(depositAmount)).accounts(accounts).signers([user]).rpc();
+
+    // 暂停后充值和提现都失败
+    await program.methods.setPaused(true).accounts({ admin: user.publicKey, config: configPda }).signers([user]).rpc();
+    for (const method of [program.methods.deposit(new anchor.BN(depositAmount)), program.methods.withdraw()]) {
+      try {
+        await method.accounts(accounts).signers([user]).rpc();
+        assert.fail("paused program should reject the instruction");
+      } catch (err) {
+        assert.strictEqual(err.error.errorCode.code, "ProgramPaused");
+      }
+    }
+
+    // 非管理员不能修改配置
+    const stranger = Keypair.generate();
+    try {
+      await program.methods.setPaused(false).accounts({ admin: stranger.publicKey, config: configPda }).signers([stranger]).rpc();
+      assert.fail("non-admin should not unpause");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "Unauthorized");
+    }
+
+    // 应急模式：恢复后只允许提现
+    await program.methods.setPaused(false).accounts({ admin: user.publicKey, config: configPda }).signers([user]).rpc();
+    await program.methods
+      .updateConfig({ emergency: true, depositFeeBps: 0, withdrawFeeBps: 0 })
+      .accounts({ admin: user.publicKey, config: configPda })
+      .signers([user])
+      .rpc();
+    try {
+      await program.methods.deposit(new anchor.BN(depositAmount)).accounts(accounts).signers([user]).rpc();
+      assert.fail("emergency mode should reject deposits");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "DepositsDisabled");
+    }
+    await program.methods.withdraw().accounts(accounts).signers([user]).rpc();
+    const account = await program.account.stackAccount.fetch(stackAccountData);
+    assert.strictEqual(account.balance.toNumber(), 0);
+
+    await program.methods
+      .updateConfig({ emergency: false, depositFeeBps: 0, withdrawFeeBps: 0 })
+      .accounts({ admin: user.publicKey, config: configPda })
+      .signers([user])
+      .rpc();
+  });
+
+  it("Fees are routed to the treasury and collected by the admin", async () => {
+    const connection = program.provider.connection;
+    const accounts = {
+      user: user.publicKey,
+      stackAccountPda: stackPDA,
+      stackAccount: stackAccountData,
+      config: configPda,
+      treasury: treasuryPda,
+      systemProgram: SystemProgram.programId,
+    };
+    // 充值 1%、提现 0.5%，向上取整
+    await program.methods
+      .updateConfig({ emergency: false, depositFeeBps: 100, withdrawFeeBps: 50 })
+      .accounts({ admin: user.publicKey, config: configPda })
+      .signers([user])
+      .rpc();
+
+    const treasuryBefore = await connection.getBalance(treasuryPda);
+    const balanceBefore = (await program.account.stackAccount.fetch(stackAccountData)).balance.toNumber();
+    await program.methods.deposit(new anchor.BN(10_001)).accounts(accounts).signers([user]).rpc();
+    await program.methods.withdrawAmount(new anchor.BN(1_000)).accounts(accounts).signers([user]).rpc();
+
+    const balanceAfter = (await program.account.stackAccount.fetch(stackAccountData)).balance.toNumber();
+    assert.strictEqual(balanceAfter - balanceBefore, 10_001 - 101 - 1_000);
+    assert.strictEqual((await connection.getBalance(treasuryPda)) - treasuryBefore, 101 + 5);
+
+    // 只有管理员可以提取，金库保留免租金余额
+    const stranger = Keypair.generate();
+    try {
+      await program.methods
+        .collectFees()
+        .accounts({ admin: stranger.publicKey, config: configPda, treasury: treasuryPda, recipient: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+      assert.fail("non-admin should not collect fees");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "Unauthorized");
+    }
+    await program.methods
+      .collectFees()
+      .accounts({ admin: user.publicKey, config: configPda, treasury: treasuryPda, recipient: user.publicKey })
+      .signers([user])
+      .rpc();
+    const rentExempt = await connection.getMinimumBalanceForRentExemption(0);
+    assert.strictEqual(await connection.getBalance(treasuryPda), rentExempt);
+    try {
+      await program.methods
+        .collectFees()
+        .accounts({ admin: user.publicKey, config: configPda, treasury: treasuryPda, recipient: user.publicKey })
+        .signers([user])
+        .rpc();
+      assert.fail("empty treasury should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "NoFeesToCollect");
+    }
+
+    await program.methods
+      .updateConfig({ emergency: false, depositFeeBps: 0, withdrawFeeBps: 0 })
+      .accounts({ admin: user.publicKey, config: configPda })
+      .signers([user])
+      .rpc();
+  });
+
+  it("Admin handover takes two steps", async () => {
+    const newAdmin = Keypair.generate();
+    const airdropSig = await program.provider.connection.requestAirdrop(newAdmin.publicKey, LAMPORTS_PER_SOL);
+    await program.provider.connection.confirmTransaction(airdropSig);
+
+    await program.methods
+      .proposeAdmin(newAdmin.publicKey)
+      .accounts({ admin: user.publicKey, config: configPda })
+      .signers([user])
+      .rpc();
+    // 只有被提名的账户可以接受
+    try {
+      await program.methods.acceptAdmin().accounts({ pendingAdmin: user.publicKey, config: configPda }).signers([user]).rpc();
+      assert.fail("only the pending admin can accept");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "Unauthorized");
+    }
+    await program.methods.acceptAdmin().accounts({ pendingAdmin: newAdmin.publicKey, config: configPda }).signers([newAdmin]).rpc();
+    const config = await program.account.config.fetch(configPda);
+    assert.strictEqual(config.admin.toBase58(), newAdmin.publicKey.toBase58());
+    assert.strictEqual(config.pendingAdmin.toBase58(), PublicKey.default.toBase58());
+
+    try {
+      await program.methods.setPaused(true).accounts({ admin: user.publicKey, config: configPda }).signers([user]).rpc();
+      assert.fail("former admin should be rejected");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "Unauthorized");
+    }
+  });
+});
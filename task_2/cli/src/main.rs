@@ -0,0 +1,722 @@
+//! stake-cli：task_1 / task_2 的运维命令行工具
+//!
+//! 数量参数均为最小单位（lamports 或代币的最小单位）。
+//! `--sign-only` 只签名不发送，配合 `--blockhash` 可以完全离线使用，输出 base64 编码的交易。
+
+use std::error::Error;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signer},
+    transaction::Transaction,
+};
+use stake_client::{task_1, task_2, TOKEN_PROGRAM_ID};
+
+#[derive(Parser)]
+#[command(name = "stake-cli", version, about = "Operate the task_1 / task_2 staking programs")]
+struct Cli {
+    /// RPC 地址
+    #[arg(short, long, global = true, default_value = "http://127.0.0.1:8899")]
+    url: String,
+
+    /// 签名钱包的 keypair 文件
+    #[arg(short, long, global = true, default_value = "~/.config/solana/id.json")]
+    keypair: String,
+
+    /// SOL 质押使用的程序（spl 相关命令只支持 task-2）
+    #[arg(long, global = true, value_enum, default_value_t = Program::Task2)]
+    program: Program,
+
+    /// 只签名不发送，输出 base64 交易（需要 --blockhash）
+    #[arg(long, global = true, requires = "blockhash")]
+    sign_only: bool,
+
+    /// 发送前输出 base64 交易
+    #[arg(long, global = true)]
+    dump_transaction: bool,
+
+    /// 使用指定的 blockhash，不从 RPC 获取
+    #[arg(long, global = true)]
+    blockhash: Option<Hash>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Program {
+    #[value(name = "task-1")]
+    Task1,
+    #[value(name = "task-2")]
+    Task2,
+}
+
+// 全局配置参数
+#[derive(Args)]
+struct ConfigArgs {
+    /// 应急模式：允许提现，禁止充值和铸币
+    #[arg(long)]
+    emergency: bool,
+    /// 充值手续费（万分比）
+    #[arg(long, default_value_t = 0)]
+    deposit_fee_bps: u16,
+    /// 提现手续费（万分比）
+    #[arg(long, default_value_t = 0)]
+    withdraw_fee_bps: u16,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 初始化全局配置，当前钱包须为程序升级权限账户，并成为管理员
+    InitConfig {
+        #[command(flatten)]
+        params: ConfigArgs,
+    },
+    /// 修改应急模式和手续费（仅管理员）
+    UpdateConfig {
+        #[command(flatten)]
+        params: ConfigArgs,
+    },
+    /// 暂停或恢复充值、铸币和提现（仅管理员）
+    SetPaused {
+        #[arg(long, action = ArgAction::Set)]
+        paused: bool,
+    },
+    /// 提名新管理员（仅管理员），新管理员执行 accept-admin 后生效
+    ProposeAdmin {
+        #[arg(long)]
+        new_admin: Pubkey,
+    },
+    /// 当前钱包接受管理员权限
+    AcceptAdmin,
+    /// 查看全局配置
+    ShowConfig,
+    /// 提取手续费（仅管理员），指定 --mint 时提取该代币的手续费（task_2）
+    CollectFees {
+        /// 接收钱包，默认当前钱包
+        #[arg(long)]
+        to: Option<Pubkey>,
+        #[arg(long)]
+        mint: Option<Pubkey>,
+        /// 代币程序，默认从 mint 账户读取；--sign-only 时必须指定
+        #[arg(long)]
+        token_program: Option<Pubkey>,
+    },
+    /// 为已在白名单中的代币创建手续费ATA（task_2，仅管理员）
+    InitTreasuryAta {
+        #[arg(long)]
+        mint: Pubkey,
+        /// 代币程序，默认从 mint 账户读取；--sign-only 时必须指定
+        #[arg(long)]
+        token_program: Option<Pubkey>,
+    },
+    /// 创建多签（task_2，仅管理员）
+    CreateMultisig {
+        /// 多签成员，逗号分隔
+        #[arg(long, value_delimiter = ',', required = true)]
+        signers: Vec<Pubkey>,
+        #[arg(long)]
+        threshold: u8,
+    },
+    /// 批准提案（task_2，多签成员）
+    ApproveProposal {
+        #[arg(long)]
+        index: u64,
+    },
+    /// 执行已达到门限的提案（task_2），需要在线读取提案
+    ExecuteProposal {
+        #[arg(long)]
+        index: u64,
+    },
+    /// 提名新的铸币权限账户（task_2，当前铸币权限账户）
+    ProposeMintAuthority {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        new_authority: Pubkey,
+    },
+    /// 当前钱包接受铸币权限（task_2）
+    AcceptMintAuthority {
+        #[arg(long)]
+        mint: Pubkey,
+    },
+    /// 销毁当前钱包 ATA 中的代币（task_2）
+    Burn {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        /// 代币程序，默认从 mint 账户读取；--sign-only 时必须指定
+        #[arg(long)]
+        token_program: Option<Pubkey>,
+    },
+    /// 销毁用户质押ATA中超出仓位余额的代币（task_2，仅管理员）
+    BurnFromVault {
+        /// 质押用户钱包
+        #[arg(long)]
+        user: Pubkey,
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        /// 代币程序，默认从 mint 账户读取；--sign-only 时必须指定
+        #[arg(long)]
+        token_program: Option<Pubkey>,
+    },
+    /// 永久撤销铸币权限（task_2，仅铸币权限账户），不可撤销
+    RevokeMintAuthority {
+        #[arg(long)]
+        mint: Pubkey,
+        /// 代币程序，默认从 mint 账户读取；--sign-only 时必须指定
+        #[arg(long)]
+        token_program: Option<Pubkey>,
+    },
+    /// 按 symbol 创建代币（task_2，仅配置管理员），当前钱包获得铸币权限
+    CreateToken {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        symbol: String,
+        #[arg(long, default_value = "")]
+        uri: String,
+        #[arg(long, default_value_t = 9)]
+        decimals: u8,
+        /// 总供应量上限（最小单位），创建后不可修改
+        #[arg(long)]
+        max_supply: u64,
+        /// 每个 epoch 最多铸造的数量（最小单位），0表示不限
+        #[arg(long, default_value_t = 0)]
+        epoch_emission: u64,
+        /// 代币程序，默认 SPL Token
+        #[arg(long)]
+        token_program: Option<Pubkey>,
+    },
+    /// 修改代币元数据（task_2，仅管理员）
+    UpdateMetadata {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        symbol: String,
+        #[arg(long, default_value = "")]
+        uri: String,
+        /// 创作者，格式 地址:分成百分比，逗号分隔，分成合计须为 100
+        #[arg(long, value_delimiter = ',', value_parser = parse_creator)]
+        creators: Vec<task_2::TokenCreator>,
+    },
+    /// 永久锁定代币元数据（task_2，仅管理员），不可撤销
+    FreezeMetadata {
+        #[arg(long)]
+        mint: Pubkey,
+    },
+    /// 冻结持有人的代币账户（task_2，仅管理员），冻结后不能转账和充值
+    FreezeAccount {
+        #[arg(long)]
+        mint: Pubkey,
+        /// 持有人钱包，冻结其关联代币账户
+        #[arg(long)]
+        owner: Pubkey,
+        /// 代币程序，默认从 mint 账户读取；--sign-only 时必须指定
+        #[arg(long)]
+        token_program: Option<Pubkey>,
+    },
+    /// 解冻持有人的代币账户（task_2，仅管理员）
+    ThawAccount {
+        #[arg(long)]
+        mint: Pubkey,
+        /// 持有人钱包，解冻其关联代币账户
+        #[arg(long)]
+        owner: Pubkey,
+        /// 代币程序，默认从 mint 账户读取；--sign-only 时必须指定
+        #[arg(long)]
+        token_program: Option<Pubkey>,
+    },
+    /// 铸币（task_2），只有创建者可以操作
+    Mint {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        /// 接收钱包，默认当前钱包
+        #[arg(long)]
+        to: Option<Pubkey>,
+        /// 代币程序，默认从 mint 账户读取；--sign-only 时必须指定
+        #[arg(long)]
+        token_program: Option<Pubkey>,
+    },
+    /// 质押 SOL
+    Deposit {
+        #[arg(long)]
+        amount: u64,
+        /// 锁定秒数
+        #[arg(long)]
+        lock: Option<i64>,
+    },
+    /// 提现 SOL，不指定数量时提取全部
+    Withdraw {
+        #[arg(long)]
+        amount: Option<u64>,
+    },
+    /// 质押 spl 代币（task_2）
+    DepositSpl {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        /// 锁定秒数
+        #[arg(long)]
+        lock: Option<i64>,
+        /// 代币程序，默认从 mint 账户读取；--sign-only 时必须指定
+        #[arg(long)]
+        token_program: Option<Pubkey>,
+    },
+    /// 提取全部 spl 质押（task_2）
+    WithdrawSpl {
+        #[arg(long)]
+        mint: Pubkey,
+        /// 代币程序，默认从 mint 账户读取；--sign-only 时必须指定
+        #[arg(long)]
+        token_program: Option<Pubkey>,
+    },
+    /// 提取初始版本（按钱包记录、[b"mint"] 代币）的 spl 仓位并关闭旧数据账户（task_2）
+    WithdrawSplLegacy,
+    /// 直接用 SOL 质押 WSOL 仓位（task_2），lamports 自动包装，WSOL 需在白名单内
+    DepositSplNative {
+        #[arg(long)]
+        amount: u64,
+    },
+    /// 提取全部 WSOL 仓位并解包为 SOL（task_2）
+    WithdrawSplNative,
+    /// 为白名单代币创建份额金库和凭证代币（task_2，仅管理员）
+    InitShareVault {
+        #[arg(long)]
+        mint: Pubkey,
+        /// 代币程序，默认从 mint 账户读取；--sign-only 时必须指定
+        #[arg(long)]
+        token_program: Option<Pubkey>,
+    },
+    /// 存入份额金库，按比例获得凭证代币（task_2）
+    DepositShares {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        /// 代币程序，默认从 mint 账户读取；--sign-only 时必须指定
+        #[arg(long)]
+        token_program: Option<Pubkey>,
+    },
+    /// 销毁凭证代币，从份额金库取回资产（task_2）
+    WithdrawShares {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        shares: u64,
+        /// 代币程序，默认从 mint 账户读取；--sign-only 时必须指定
+        #[arg(long)]
+        token_program: Option<Pubkey>,
+    },
+    /// 查看份额金库的资产、份额和兑换比例（task_2）
+    ShowShareVault {
+        #[arg(long)]
+        mint: Pubkey,
+    },
+    /// 把旧布局仓位迁移到当前布局，当前钱包支付新增租金；指定 --mint 时迁移 spl 仓位（task_2）
+    MigratePosition {
+        #[arg(long)]
+        mint: Option<Pubkey>,
+    },
+    /// 查看仓位，指定 --mint 时查看 spl 仓位
+    ShowPosition {
+        /// 仓位所有者，默认当前钱包
+        #[arg(long)]
+        owner: Option<Pubkey>,
+        #[arg(long)]
+        mint: Option<Pubkey>,
+    },
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let rpc = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+    let signer = read_keypair_file(expand_home(&cli.keypair)).map_err(|err| format!("{}: {err}", cli.keypair))?;
+    let user = signer.pubkey();
+
+    if cli.program == Program::Task1
+        && !matches!(
+            cli.command,
+            Command::InitConfig { .. }
+                | Command::UpdateConfig { .. }
+                | Command::SetPaused { .. }
+                | Command::ProposeAdmin { .. }
+                | Command::AcceptAdmin
+                | Command::ShowConfig
+                | Command::CollectFees { mint: None, .. }
+                | Command::Deposit { .. }
+                | Command::Withdraw { .. }
+                | Command::MigratePosition { mint: None }
+                | Command::ShowPosition { .. }
+        )
+    {
+        return Err(
+            "only config commands, collect-fees, deposit, withdraw, migrate-position and show-position are available with --program task-1"
+                .into(),
+        );
+    }
+
+    let instructions = match &cli.command {
+        Command::InitConfig { params } => match cli.program {
+            Program::Task1 => vec![task_1::initialize_config(&user, params.task_1())],
+            Program::Task2 => vec![task_2::initialize_config(&user, params.task_2())],
+        },
+        Command::UpdateConfig { params } => match cli.program {
+            Program::Task1 => vec![task_1::update_config(&user, params.task_1())],
+            Program::Task2 => vec![task_2::update_config(&user, params.task_2())],
+        },
+        Command::SetPaused { paused } => match cli.program {
+            Program::Task1 => vec![task_1::set_paused(&user, *paused)],
+            Program::Task2 => vec![task_2::set_paused(&user, *paused)],
+        },
+        Command::ProposeAdmin { new_admin } => match cli.program {
+            Program::Task1 => vec![task_1::propose_admin(&user, new_admin)],
+            Program::Task2 => vec![task_2::propose_admin(&user, new_admin)],
+        },
+        Command::AcceptAdmin => match cli.program {
+            Program::Task1 => vec![task_1::accept_admin(&user)],
+            Program::Task2 => vec![task_2::accept_admin(&user)],
+        },
+        Command::ShowConfig => {
+            if cli.sign_only {
+                return Err("show-config needs an RPC connection and cannot be used with --sign-only".into());
+            }
+            return show_config(&cli, &rpc);
+        }
+        Command::CollectFees { to, mint, token_program } => {
+            let recipient = to.unwrap_or(user);
+            match (cli.program, mint) {
+                (Program::Task1, _) => vec![task_1::collect_fees(&user, &recipient)],
+                (Program::Task2, None) => vec![task_2::collect_fees(&user, &recipient)],
+                (Program::Task2, Some(mint)) => {
+                    let token_program = resolve_token_program(&cli, &rpc, mint, *token_program)?;
+                    if cli.sign_only {
+                        vec![task_2::collect_fees_spl(&user, mint, &recipient, &token_program)]
+                    } else {
+                        // 接收者的ATA不存在时先创建
+                        task_2::collect_fees_spl_instructions(&rpc, &user, mint, &recipient, &token_program)?
+                    }
+                }
+            }
+        }
+        Command::InitTreasuryAta { mint, token_program } => {
+            let token_program = resolve_token_program(&cli, &rpc, mint, *token_program)?;
+            vec![task_2::init_treasury_ata(&user, mint, &token_program)]
+        }
+        Command::CreateMultisig { signers, threshold } => {
+            if !cli.sign_only {
+                println!("Multisig signer: {}", task_2::multisig_signer().0);
+            }
+            vec![task_2::create_multisig(&user, signers.clone(), *threshold)]
+        }
+        Command::ApproveProposal { index } => vec![task_2::approve_proposal(&user, *index)],
+        Command::ExecuteProposal { index } => {
+            if cli.sign_only {
+                return Err("execute-proposal needs an RPC connection and cannot be used with --sign-only".into());
+            }
+            vec![task_2::execute_proposal(&task_2::fetch_proposal(&rpc, *index)?)]
+        }
+        Command::ProposeMintAuthority { mint, new_authority } => {
+            vec![task_2::propose_mint_authority(&user, mint, new_authority)]
+        }
+        Command::AcceptMintAuthority { mint } => vec![task_2::accept_mint_authority(&user, mint)],
+        Command::Burn { mint, amount, token_program } => {
+            let token_program = resolve_token_program(&cli, &rpc, mint, *token_program)?;
+            vec![task_2::burn_spl(&user, mint, *amount, &token_program)]
+        }
+        Command::BurnFromVault { user: owner, mint, amount, token_program } => {
+            let token_program = resolve_token_program(&cli, &rpc, mint, *token_program)?;
+            vec![task_2::burn_from_vault(&user, owner, mint, *amount, &token_program)]
+        }
+        Command::RevokeMintAuthority { mint, token_program } => {
+            let token_program = resolve_token_program(&cli, &rpc, mint, *token_program)?;
+            vec![task_2::revoke_mint_authority(&user, mint, &token_program)]
+        }
+        Command::CreateToken { name, symbol, uri, decimals, max_supply, epoch_emission, token_program } => {
+            let params = task_2::InitTokenParams {
+                name: name.clone(),
+                symbol: symbol.clone(),
+                uri: uri.clone(),
+                decimals: *decimals,
+                max_supply: *max_supply,
+                epoch_emission: *epoch_emission,
+            };
+            if !cli.sign_only {
+                println!("Mint: {}", task_2::mint(symbol).0);
+            }
+            vec![task_2::create_token(&user, params, &token_program.unwrap_or(TOKEN_PROGRAM_ID))]
+        }
+        Command::UpdateMetadata { mint, name, symbol, uri, creators } => {
+            let params = task_2::UpdateTokenMetadataParams {
+                name: name.clone(),
+                symbol: symbol.clone(),
+                uri: uri.clone(),
+                creators: creators.clone(),
+            };
+            vec![task_2::update_token_metadata(&user, mint, params)]
+        }
+        Command::FreezeMetadata { mint } => vec![task_2::freeze_metadata(&user, mint)],
+        Command::FreezeAccount { mint, owner, token_program } => {
+            let token_program = resolve_token_program(&cli, &rpc, mint, *token_program)?;
+            let token_account = stake_client::ata(owner, mint, &token_program);
+            vec![task_2::freeze_account(&user, mint, &token_account, &token_program)]
+        }
+        Command::ThawAccount { mint, owner, token_program } => {
+            let token_program = resolve_token_program(&cli, &rpc, mint, *token_program)?;
+            let token_account = stake_client::ata(owner, mint, &token_program);
+            vec![task_2::thaw_account(&user, mint, &token_account, &token_program)]
+        }
+        Command::Mint { mint, amount, to, token_program } => {
+            let token_program = resolve_token_program(&cli, &rpc, mint, *token_program)?;
+            vec![task_2::mint_spl(&user, mint, &to.unwrap_or(user), *amount, &token_program)]
+        }
+        Command::Deposit { amount, lock } => match (cli.program, lock) {
+            (Program::Task1, None) => vec![task_1::deposit(&user, *amount)],
+            (Program::Task1, Some(lock)) => vec![task_1::deposit_locked(&user, *amount, *lock)],
+            (Program::Task2, None) => vec![task_2::deposit(&user, *amount)],
+            (Program::Task2, Some(lock)) => vec![task_2::deposit_locked(&user, *amount, *lock)],
+        },
+        Command::Withdraw { amount } => match (cli.program, amount) {
+            (Program::Task1, None) => vec![task_1::withdraw(&user)],
+            (Program::Task1, Some(amount)) => vec![task_1::withdraw_amount(&user, *amount)],
+            (Program::Task2, None) => vec![task_2::withdraw(&user)],
+            (Program::Task2, Some(amount)) => vec![task_2::withdraw_amount(&user, *amount)],
+        },
+        Command::DepositSpl { mint, amount, lock, token_program } => {
+            let token_program = resolve_token_program(&cli, &rpc, mint, *token_program)?;
+            match lock {
+                None => vec![task_2::deposit_spl(&user, mint, *amount, &token_program)],
+                Some(lock) => vec![task_2::deposit_spl_locked(&user, mint, *amount, *lock, &token_program)],
+            }
+        }
+        Command::WithdrawSpl { mint, token_program } => {
+            let token_program = resolve_token_program(&cli, &rpc, mint, *token_program)?;
+            if cli.sign_only {
+                vec![task_2::withdraw_spl(&user, mint, &token_program)]
+            } else {
+                // 用户ATA不存在时先创建
+                task_2::withdraw_spl_instructions(&rpc, &user, mint, &token_program)?
+            }
+        }
+        Command::WithdrawSplLegacy => {
+            if cli.sign_only {
+                vec![task_2::withdraw_spl_legacy(&user)]
+            } else {
+                // 用户ATA不存在时先创建
+                task_2::withdraw_spl_legacy_instructions(&rpc, &user)?
+            }
+        }
+        Command::DepositSplNative { amount } => vec![task_2::deposit_spl_native(&user, *amount)],
+        Command::WithdrawSplNative => vec![task_2::withdraw_spl_native(&user)],
+        Command::MigratePosition { mint } => match cli.program {
+            Program::Task1 => vec![task_1::migrate_position(&user)],
+            Program::Task2 => vec![task_2::migrate_position(&user, mint.as_ref())],
+        },
+        Command::InitShareVault { mint, token_program } => {
+            let token_program = resolve_token_program(&cli, &rpc, mint, *token_program)?;
+            if !cli.sign_only {
+                println!("Receipt mint: {}", task_2::receipt_mint(mint).0);
+            }
+            vec![task_2::initialize_share_vault(&user, mint, &token_program)]
+        }
+        Command::DepositShares { mint, amount, token_program } => {
+            let token_program = resolve_token_program(&cli, &rpc, mint, *token_program)?;
+            vec![task_2::deposit_shares(&user, mint, *amount, &token_program)]
+        }
+        Command::WithdrawShares { mint, shares, token_program } => {
+            let token_program = resolve_token_program(&cli, &rpc, mint, *token_program)?;
+            if cli.sign_only {
+                vec![task_2::withdraw_shares(&user, mint, *shares, &token_program)]
+            } else {
+                // 用户ATA不存在时先创建
+                task_2::withdraw_shares_instructions(&rpc, &user, mint, *shares, &token_program)?
+            }
+        }
+        Command::ShowShareVault { mint } => {
+            if cli.sign_only {
+                return Err("show-share-vault needs an RPC connection and cannot be used with --sign-only".into());
+            }
+            return show_share_vault(&rpc, mint);
+        }
+        Command::ShowPosition { owner, mint } => {
+            if cli.sign_only {
+                return Err("show-position needs an RPC connection and cannot be used with --sign-only".into());
+            }
+            return show_position(&cli, &rpc, &owner.unwrap_or(user), mint.as_ref());
+        }
+    };
+
+    send(&cli, &rpc, &signer, &instructions)
+}
+
+// 签名交易，按参数输出 base64 或发送
+fn send(cli: &Cli, rpc: &RpcClient, signer: &Keypair, instructions: &[Instruction]) -> Result<(), Box<dyn Error>> {
+    let blockhash = match cli.blockhash {
+        Some(blockhash) => blockhash,
+        None => rpc.get_latest_blockhash()?,
+    };
+    let tx = Transaction::new_signed_with_payer(instructions, Some(&signer.pubkey()), &[signer], blockhash);
+
+    if cli.sign_only || cli.dump_transaction {
+        println!("{}", STANDARD.encode(bincode::serialize(&tx)?));
+    }
+    if cli.sign_only {
+        return Ok(());
+    }
+
+    let signature = rpc.send_and_confirm_transaction(&tx)?;
+    println!("Signature: {signature}");
+    Ok(())
+}
+
+// 未指定代币程序时从 mint 账户读取；离线模式读不到 mint，不猜测默认值，
+// 否则 Token-2022 的mint会得到一笔已签名但无法执行的交易
+fn resolve_token_program(
+    cli: &Cli,
+    rpc: &RpcClient,
+    mint: &Pubkey,
+    token_program: Option<Pubkey>,
+) -> Result<Pubkey, Box<dyn Error>> {
+    match token_program {
+        Some(token_program) => Ok(token_program),
+        None if cli.sign_only => {
+            Err(format!("--sign-only cannot read the token program of mint {mint}; pass --token-program").into())
+        }
+        None => Ok(stake_client::token_program_for_mint(rpc, mint)?),
+    }
+}
+
+impl ConfigArgs {
+    fn task_1(&self) -> task_1::ConfigParams {
+        task_1::ConfigParams {
+            emergency: self.emergency,
+            deposit_fee_bps: self.deposit_fee_bps,
+            withdraw_fee_bps: self.withdraw_fee_bps,
+        }
+    }
+
+    fn task_2(&self) -> task_2::ConfigParams {
+        task_2::ConfigParams {
+            emergency: self.emergency,
+            deposit_fee_bps: self.deposit_fee_bps,
+            withdraw_fee_bps: self.withdraw_fee_bps,
+        }
+    }
+}
+
+fn show_config(cli: &Cli, rpc: &RpcClient) -> Result<(), Box<dyn Error>> {
+    let (address, admin, pending_admin, paused, emergency, deposit_fee_bps, withdraw_fee_bps) = match cli.program {
+        Program::Task1 => {
+            let config = task_1::fetch_config(rpc)?;
+            (
+                task_1::config().0,
+                config.admin,
+                config.pending_admin,
+                config.paused,
+                config.emergency,
+                config.deposit_fee_bps,
+                config.withdraw_fee_bps,
+            )
+        }
+        Program::Task2 => {
+            let config = task_2::fetch_config(rpc)?;
+            (
+                task_2::config().0,
+                config.admin,
+                config.pending_admin,
+                config.paused,
+                config.emergency,
+                config.deposit_fee_bps,
+                config.withdraw_fee_bps,
+            )
+        }
+    };
+    println!("Config:    {address}");
+    println!("Admin:     {admin}");
+    if pending_admin != Pubkey::default() {
+        println!("Pending:   {pending_admin}");
+    }
+    println!("Paused:    {paused}");
+    println!("Emergency: {emergency}");
+    println!("Fees:      deposit {deposit_fee_bps} bps, withdraw {withdraw_fee_bps} bps");
+    Ok(())
+}
+
+fn show_position(cli: &Cli, rpc: &RpcClient, owner: &Pubkey, mint: Option<&Pubkey>) -> Result<(), Box<dyn Error>> {
+    match (cli.program, mint) {
+        (Program::Task1, None) => {
+            let position = task_1::fetch_position(rpc, owner)?;
+            println!("Position: {}", task_1::stack_account(owner).0);
+            println!("Version:  {}", position.version);
+            println!("Balance:  {} lamports", position.balance);
+            println!("Locked:   {} .. {}", position.lock_start, position.lock_end);
+        }
+        (Program::Task2, None) => {
+            let position = task_2::fetch_sol_position(rpc, owner)?;
+            println!("Position: {}", task_2::sol_stack_account(owner).0);
+            println!("Version:  {}", position.version);
+            println!("Balance:  {} lamports", position.balance);
+            println!("Locked:   {} .. {}", position.lock_start, position.lock_end);
+        }
+        (Program::Task2, Some(mint)) => {
+            let position = task_2::fetch_spl_position(rpc, owner, mint)?;
+            println!("Position: {}", task_2::spl_stack_account(owner, mint).0);
+            println!("Mint:     {mint}");
+            println!("Version:  {}", position.version);
+            println!("Balance:  {}", position.balance);
+            println!("Locked:   {} .. {}", position.lock_start, position.lock_end);
+            println!("Rewards:  {} pending", position.pending_rewards);
+        }
+        (Program::Task1, Some(_)) => return Err("task_1 has no spl positions".into()),
+    }
+    Ok(())
+}
+
+fn show_share_vault(rpc: &RpcClient, mint: &Pubkey) -> Result<(), Box<dyn Error>> {
+    let token_program = stake_client::token_program_for_mint(rpc, mint)?;
+    let vault = task_2::fetch_share_vault(rpc, mint)?;
+    let rate = task_2::fetch_exchange_rate(rpc, mint, &token_program)?;
+    println!("Vault:    {}", task_2::share_vault(mint).0);
+    println!("Receipt:  {}", vault.receipt_mint);
+    println!("Assets:   {}", rate.total_assets);
+    println!("Shares:   {}", rate.total_shares);
+    println!("Rate:     {} assets per {} shares", rate.assets_per_share, task_2::RATE_SCALE);
+    Ok(())
+}
+
+// 解析 地址:分成百分比 格式的创作者
+fn parse_creator(value: &str) -> Result<task_2::TokenCreator, String> {
+    let (address, share) = value.split_once(':').ok_or_else(|| format!("expected ADDRESS:SHARE, got {value}"))?;
+    Ok(task_2::TokenCreator {
+        address: address.parse().map_err(|err| format!("{address}: {err}"))?,
+        share: share.parse().map_err(|err| format!("{share}: {err}"))?,
+    })
+}
+
+// 展开 keypair 路径中的 ~
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{home}/{rest}"),
+        _ => path.to_string(),
+    }
+}
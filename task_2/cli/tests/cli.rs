@@ -0,0 +1,406 @@
+use std::process::{Command, Output};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_sdk::{
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::{write_keypair_file, Keypair, Signer},
+    transaction::Transaction,
+};
+use stake_client::{task_1, task_2, TOKEN_PROGRAM_ID};
+
+// 使用临时 keypair 离线运行 stake-cli
+fn stake_cli(keypair: &Keypair, args: &[&str]) -> Output {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("id.json");
+    write_keypair_file(keypair, &path).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_stake-cli"))
+        .arg("--keypair")
+        .arg(&path)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+// 解析 --sign-only 输出的 base64 交易
+fn signed_transaction(keypair: &Keypair, args: &[&str]) -> Transaction {
+    let output = stake_cli(keypair, args);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let encoded = String::from_utf8(output.stdout).unwrap();
+    let tx: Transaction = bincode::deserialize(&STANDARD.decode(encoded.trim()).unwrap()).unwrap();
+    tx.verify().unwrap();
+    tx
+}
+
+#[test]
+fn sign_only_deposit_matches_client_instruction() {
+    let keypair = Keypair::new();
+    let blockhash = Hash::new_unique();
+    let tx = signed_transaction(
+        &keypair,
+        &["--sign-only", "--blockhash", &blockhash.to_string(), "deposit", "--amount", "1000"],
+    );
+
+    assert_eq!(tx.message.recent_blockhash, blockhash);
+    assert_eq!(tx.message.account_keys[0], keypair.pubkey());
+    let expected = task_2::deposit(&keypair.pubkey(), 1000);
+    let ix = &tx.message.instructions[0];
+    assert_eq!(tx.message.account_keys[ix.program_id_index as usize], task_2::PROGRAM_ID);
+    assert_eq!(ix.data, expected.data);
+}
+
+#[test]
+fn sign_only_task_1_withdraw_amount() {
+    let keypair = Keypair::new();
+    let blockhash = Hash::new_unique().to_string();
+    let tx = signed_transaction(
+        &keypair,
+        &["--sign-only", "--blockhash", &blockhash, "--program", "task-1", "withdraw", "--amount", "5"],
+    );
+
+    let ix = &tx.message.instructions[0];
+    assert_eq!(tx.message.account_keys[ix.program_id_index as usize], task_1::PROGRAM_ID);
+    assert_eq!(ix.data, task_1::withdraw_amount(&keypair.pubkey(), 5).data);
+}
+
+#[test]
+fn sign_only_spl_commands() {
+    let keypair = Keypair::new();
+    let blockhash = Hash::new_unique().to_string();
+    let mint = task_2::mint("TT1").0.to_string();
+    let token_program = TOKEN_PROGRAM_ID.to_string();
+
+    for args in [
+        vec!["create-token", "--name", "Test Token", "--symbol", "TT1", "--decimals", "6", "--max-supply", "1000000"],
+        vec!["mint", "--mint", &mint, "--amount", "100", "--token-program", &token_program],
+        vec!["deposit-spl", "--mint", &mint, "--amount", "100", "--lock", "60", "--token-program", &token_program],
+        vec!["withdraw-spl", "--mint", &mint, "--token-program", &token_program],
+    ] {
+        let mut full = vec!["--sign-only", "--blockhash", &blockhash];
+        full.extend(args);
+        let tx = signed_transaction(&keypair, &full);
+        let ix = &tx.message.instructions[0];
+        assert_eq!(tx.message.account_keys[ix.program_id_index as usize], task_2::PROGRAM_ID);
+    }
+}
+
+#[test]
+fn sign_only_config_commands() {
+    let keypair = Keypair::new();
+    let blockhash = Hash::new_unique().to_string();
+
+    let tx = signed_transaction(
+        &keypair,
+        &["--sign-only", "--blockhash", &blockhash, "--program", "task-1", "set-paused", "--paused", "true"],
+    );
+    assert_eq!(tx.message.instructions[0].data, task_1::set_paused(&keypair.pubkey(), true).data);
+
+    let tx = signed_transaction(
+        &keypair,
+        &["--sign-only", "--blockhash", &blockhash, "update-config", "--emergency", "--withdraw-fee-bps", "25"],
+    );
+    let params = task_2::ConfigParams { emergency: true, deposit_fee_bps: 0, withdraw_fee_bps: 25 };
+    assert_eq!(tx.message.instructions[0].data, task_2::update_config(&keypair.pubkey(), params).data);
+}
+
+#[test]
+fn sign_only_admin_and_multisig_commands() {
+    let keypair = Keypair::new();
+    let blockhash = Hash::new_unique().to_string();
+    let new_admin = task_2::multisig_signer().0;
+    let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    let tx = signed_transaction(
+        &keypair,
+        &["--sign-only", "--blockhash", &blockhash, "--program", "task-1", "propose-admin", "--new-admin", &new_admin.to_string()],
+    );
+    assert_eq!(tx.message.instructions[0].data, task_1::propose_admin(&keypair.pubkey(), &new_admin).data);
+
+    let signers = format!("{a},{b}");
+    let tx = signed_transaction(
+        &keypair,
+        &["--sign-only", "--blockhash", &blockhash, "create-multisig", "--signers", &signers, "--threshold", "2"],
+    );
+    assert_eq!(tx.message.instructions[0].data, task_2::create_multisig(&keypair.pubkey(), vec![a, b], 2).data);
+
+    let tx = signed_transaction(&keypair, &["--sign-only", "--blockhash", &blockhash, "approve-proposal", "--index", "7"]);
+    assert_eq!(tx.message.instructions[0].data, task_2::approve_proposal(&keypair.pubkey(), 7).data);
+    assert!(tx.message.account_keys.contains(&task_2::proposal(7).0));
+}
+
+#[test]
+fn sign_only_collect_fees() {
+    let keypair = Keypair::new();
+    let blockhash = Hash::new_unique().to_string();
+    let mint = task_2::mint("TT1").0;
+    let token_program = TOKEN_PROGRAM_ID.to_string();
+    let to = Pubkey::new_unique();
+
+    let tx = signed_transaction(
+        &keypair,
+        &["--sign-only", "--blockhash", &blockhash, "--program", "task-1", "collect-fees", "--to", &to.to_string()],
+    );
+    assert!(tx.message.account_keys.contains(&task_1::treasury().0));
+    assert!(tx.message.account_keys.contains(&to));
+
+    let tx = signed_transaction(
+        &keypair,
+        &["--sign-only", "--blockhash", &blockhash, "collect-fees", "--mint", &mint.to_string(), "--token-program", &token_program],
+    );
+    let expected = task_2::collect_fees_spl(&keypair.pubkey(), &mint, &keypair.pubkey(), &TOKEN_PROGRAM_ID);
+    assert_eq!(tx.message.instructions[0].data, expected.data);
+    assert!(tx.message.account_keys.contains(&task_2::treasury_ata(&mint, &TOKEN_PROGRAM_ID)));
+
+    let tx = signed_transaction(
+        &keypair,
+        &["--sign-only", "--blockhash", &blockhash, "init-treasury-ata", "--mint", &mint.to_string(), "--token-program", &token_program],
+    );
+    let expected = task_2::init_treasury_ata(&keypair.pubkey(), &mint, &TOKEN_PROGRAM_ID);
+    assert_eq!(tx.message.instructions[0].data, expected.data);
+    assert_eq!(tx.message.instructions[0].accounts.len(), expected.accounts.len());
+    assert!(tx.message.account_keys.contains(&task_2::treasury_ata(&mint, &TOKEN_PROGRAM_ID)));
+}
+
+#[test]
+fn sign_only_metadata_commands() {
+    let keypair = Keypair::new();
+    let blockhash = Hash::new_unique().to_string();
+    let mint = task_2::mint("TT1").0;
+    let creator = Pubkey::new_unique();
+
+    let creators = format!("{creator}:100");
+    let tx = signed_transaction(
+        &keypair,
+        &[
+            "--sign-only", "--blockhash", &blockhash, "update-metadata", "--mint", &mint.to_string(), "--name", "Renamed",
+            "--symbol", "RN", "--uri", "https://example.com/rn.json", "--creators", &creators,
+        ],
+    );
+    let params = task_2::UpdateTokenMetadataParams {
+        name: "Renamed".to_string(),
+        symbol: "RN".to_string(),
+        uri: "https://example.com/rn.json".to_string(),
+        creators: vec![task_2::TokenCreator { address: creator, share: 100 }],
+    };
+    assert_eq!(tx.message.instructions[0].data, task_2::update_token_metadata(&keypair.pubkey(), &mint, params).data);
+    assert!(tx.message.account_keys.contains(&task_2::metadata(&mint).0));
+
+    let tx = signed_transaction(&keypair, &["--sign-only", "--blockhash", &blockhash, "freeze-metadata", "--mint", &mint.to_string()]);
+    assert_eq!(tx.message.instructions[0].data, task_2::freeze_metadata(&keypair.pubkey(), &mint).data);
+
+    let output = stake_cli(
+        &keypair,
+        &["--sign-only", "--blockhash", &blockhash, "update-metadata", "--mint", &mint.to_string(), "--name", "N",
+            "--symbol", "S", "--creators", "not-a-creator"],
+    );
+    assert!(!output.status.success());
+}
+
+#[test]
+fn sign_only_supply_cap_commands() {
+    let keypair = Keypair::new();
+    let blockhash = Hash::new_unique().to_string();
+    let mint = task_2::mint("TT1").0;
+
+    let tx = signed_transaction(
+        &keypair,
+        &[
+            "--sign-only", "--blockhash", &blockhash, "create-token", "--name", "Test Token", "--symbol", "TT1",
+            "--max-supply", "1000000", "--epoch-emission", "5000",
+        ],
+    );
+    let params = task_2::InitTokenParams {
+        name: "Test Token".to_string(),
+        symbol: "TT1".to_string(),
+        uri: String::new(),
+        decimals: 9,
+        max_supply: 1_000_000,
+        epoch_emission: 5_000,
+    };
+    assert_eq!(tx.message.instructions[0].data, task_2::create_token(&keypair.pubkey(), params, &TOKEN_PROGRAM_ID).data);
+
+    // 不指定上限时无法创建代币
+    let output = stake_cli(&keypair, &["--sign-only", "--blockhash", &blockhash, "create-token", "--name", "T", "--symbol", "T"]);
+    assert!(!output.status.success());
+
+    let tx = signed_transaction(
+        &keypair,
+        &[
+            "--sign-only", "--blockhash", &blockhash, "revoke-mint-authority", "--mint", &mint.to_string(), "--token-program",
+            &TOKEN_PROGRAM_ID.to_string(),
+        ],
+    );
+    let expected = task_2::revoke_mint_authority(&keypair.pubkey(), &mint, &TOKEN_PROGRAM_ID);
+    assert_eq!(tx.message.instructions[0].data, expected.data);
+    assert!(tx.message.account_keys.contains(&task_2::mint_config(&mint).0));
+}
+
+#[test]
+fn sign_only_burn_commands() {
+    let keypair = Keypair::new();
+    let blockhash = Hash::new_unique().to_string();
+    let mint = task_2::mint("TT1").0;
+    let token_program = TOKEN_PROGRAM_ID.to_string();
+    let owner = Pubkey::new_unique();
+
+    let tx = signed_transaction(
+        &keypair,
+        &[
+            "--sign-only", "--blockhash", &blockhash, "burn", "--mint", &mint.to_string(), "--amount", "25", "--token-program",
+            &token_program,
+        ],
+    );
+    let expected = task_2::burn_spl(&keypair.pubkey(), &mint, 25, &TOKEN_PROGRAM_ID);
+    assert_eq!(tx.message.instructions[0].data, expected.data);
+    assert!(tx.message.account_keys.contains(&stake_client::ata(&keypair.pubkey(), &mint, &TOKEN_PROGRAM_ID)));
+
+    let tx = signed_transaction(
+        &keypair,
+        &[
+            "--sign-only", "--blockhash", &blockhash, "burn-from-vault", "--user", &owner.to_string(), "--mint",
+            &mint.to_string(), "--amount", "7", "--token-program", &token_program,
+        ],
+    );
+    let expected = task_2::burn_from_vault(&keypair.pubkey(), &owner, &mint, 7, &TOKEN_PROGRAM_ID);
+    assert_eq!(tx.message.instructions[0].data, expected.data);
+    assert!(tx.message.account_keys.contains(&task_2::spl_vault(&owner, &mint, &TOKEN_PROGRAM_ID)));
+}
+
+#[test]
+fn sign_only_migrate_position() {
+    let keypair = Keypair::new();
+    let blockhash = Hash::new_unique().to_string();
+    let mint = task_2::mint("TT1").0;
+
+    let tx = signed_transaction(&keypair, &["--sign-only", "--blockhash", &blockhash, "migrate-position"]);
+    assert_eq!(tx.message.instructions[0].data, task_2::migrate_position(&keypair.pubkey(), None).data);
+    assert!(tx.message.account_keys.contains(&task_2::sol_stack_account(&keypair.pubkey()).0));
+
+    let tx = signed_transaction(
+        &keypair,
+        &["--sign-only", "--blockhash", &blockhash, "migrate-position", "--mint", &mint.to_string()],
+    );
+    assert_eq!(tx.message.instructions[0].data, task_2::migrate_position(&keypair.pubkey(), Some(&mint)).data);
+    assert!(tx.message.account_keys.contains(&task_2::spl_stack_account(&keypair.pubkey(), &mint).0));
+
+    let tx = signed_transaction(&keypair, &["--sign-only", "--blockhash", &blockhash, "--program", "task-1", "migrate-position"]);
+    let ix = &tx.message.instructions[0];
+    assert_eq!(tx.message.account_keys[ix.program_id_index as usize], task_1::PROGRAM_ID);
+    assert_eq!(ix.data, task_1::migrate_position(&keypair.pubkey()).data);
+    assert!(tx.message.account_keys.contains(&task_1::stack_account(&keypair.pubkey()).0));
+}
+
+#[test]
+fn sign_only_freeze_and_thaw_account() {
+    let keypair = Keypair::new();
+    let blockhash = Hash::new_unique().to_string();
+    let mint = task_2::mint("TT1").0;
+    let holder = Pubkey::new_unique();
+    let holder_ata = stake_client::ata(&holder, &mint, &TOKEN_PROGRAM_ID);
+    let args = ["--mint", &mint.to_string(), "--owner", &holder.to_string(), "--token-program", &TOKEN_PROGRAM_ID.to_string()]
+        .map(String::from);
+
+    let mut freeze = vec!["--sign-only", "--blockhash", &blockhash, "freeze-account"];
+    freeze.extend(args.iter().map(String::as_str));
+    let tx = signed_transaction(&keypair, &freeze);
+    let expected = task_2::freeze_account(&keypair.pubkey(), &mint, &holder_ata, &TOKEN_PROGRAM_ID);
+    assert_eq!(tx.message.instructions[0].data, expected.data);
+    assert!(tx.message.account_keys.contains(&holder_ata));
+
+    let mut thaw = vec!["--sign-only", "--blockhash", &blockhash, "thaw-account"];
+    thaw.extend(args.iter().map(String::as_str));
+    let tx = signed_transaction(&keypair, &thaw);
+    let expected = task_2::thaw_account(&keypair.pubkey(), &mint, &holder_ata, &TOKEN_PROGRAM_ID);
+    assert_eq!(tx.message.instructions[0].data, expected.data);
+}
+
+#[test]
+fn sign_only_native_sol_commands() {
+    let keypair = Keypair::new();
+    let blockhash = Hash::new_unique().to_string();
+
+    let tx = signed_transaction(&keypair, &["--sign-only", "--blockhash", &blockhash, "deposit-spl-native", "--amount", "1000"]);
+    assert_eq!(tx.message.instructions[0].data, task_2::deposit_spl_native(&keypair.pubkey(), 1000).data);
+    assert!(tx.message.account_keys.contains(&task_2::NATIVE_MINT));
+
+    let tx = signed_transaction(&keypair, &["--sign-only", "--blockhash", &blockhash, "withdraw-spl-native"]);
+    assert_eq!(tx.message.instructions[0].data, task_2::withdraw_spl_native(&keypair.pubkey()).data);
+    assert!(tx.message.account_keys.contains(&task_2::unwrap_account(&keypair.pubkey()).0));
+}
+
+#[test]
+fn sign_only_withdraw_spl_legacy() {
+    let keypair = Keypair::new();
+    let blockhash = Hash::new_unique().to_string();
+
+    let tx = signed_transaction(&keypair, &["--sign-only", "--blockhash", &blockhash, "withdraw-spl-legacy"]);
+    assert_eq!(tx.message.instructions.len(), 1);
+    assert_eq!(tx.message.instructions[0].data, task_2::withdraw_spl_legacy(&keypair.pubkey()).data);
+    assert!(tx.message.account_keys.contains(&task_2::legacy_stack_account(&keypair.pubkey()).0));
+    assert!(tx.message.account_keys.contains(&task_2::legacy_mint().0));
+}
+
+#[test]
+fn sign_only_share_vault_commands() {
+    let keypair = Keypair::new();
+    let blockhash = Hash::new_unique().to_string();
+    let mint = task_2::mint("TT1").0;
+    let token_program = TOKEN_PROGRAM_ID.to_string();
+
+    let tx = signed_transaction(
+        &keypair,
+        &[
+            "--sign-only", "--blockhash", &blockhash, "deposit-shares", "--mint", &mint.to_string(), "--amount", "5000",
+            "--token-program", &token_program,
+        ],
+    );
+    let expected = task_2::deposit_shares(&keypair.pubkey(), &mint, 5000, &TOKEN_PROGRAM_ID);
+    assert_eq!(tx.message.instructions[0].data, expected.data);
+    assert!(tx.message.account_keys.contains(&task_2::receipt_mint(&mint).0));
+
+    let tx = signed_transaction(
+        &keypair,
+        &[
+            "--sign-only", "--blockhash", &blockhash, "withdraw-shares", "--mint", &mint.to_string(), "--shares", "400",
+            "--token-program", &token_program,
+        ],
+    );
+    let expected = task_2::withdraw_shares(&keypair.pubkey(), &mint, 400, &TOKEN_PROGRAM_ID);
+    assert_eq!(tx.message.instructions[0].data, expected.data);
+    assert!(tx.message.account_keys.contains(&task_2::share_vault_ata(&mint, &TOKEN_PROGRAM_ID)));
+
+    let output = stake_cli(&keypair, &["--sign-only", "--blockhash", &blockhash, "show-share-vault", "--mint", &mint.to_string()]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn sign_only_requires_token_program_for_existing_mints() {
+    // 离线时读不到mint的代币程序，不能默认 SPL Token
+    let blockhash = Hash::new_unique().to_string();
+    let mint = task_2::mint("TT1").0.to_string();
+    let output = stake_cli(
+        &Keypair::new(),
+        &["--sign-only", "--blockhash", &blockhash, "deposit-spl", "--mint", &mint, "--amount", "1"],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--token-program"));
+}
+
+#[test]
+fn sign_only_requires_blockhash() {
+    let output = stake_cli(&Keypair::new(), &["--sign-only", "deposit", "--amount", "1"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn task_1_rejects_spl_commands() {
+    let blockhash = Hash::new_unique().to_string();
+    let mint = task_2::mint("TT1").0.to_string();
+    let output = stake_cli(
+        &Keypair::new(),
+        &["--sign-only", "--blockhash", &blockhash, "--program", "task-1", "deposit-spl", "--mint", &mint, "--amount", "1"],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--program task-1"));
+}
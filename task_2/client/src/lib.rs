@@ -0,0 +1,101 @@
+//! task_1 / task_2 程序的 Rust 客户端
+//!
+//! - `task_1` / `task_2` 模块：PDA 推导和每条指令的构造函数
+//! - 账户读取：`fetch_account` 以及各模块的仓位查询
+//! - 交易构造：`with_ata_creation` 在缺少 ATA 时插入创建指令，`build_transaction` 签名
+
+pub mod task_1;
+pub mod task_2;
+
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{
+    get_associated_token_address_with_program_id, spl_associated_token_account::instruction::create_associated_token_account,
+};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+pub use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+pub use anchor_spl::token_2022::ID as TOKEN_2022_PROGRAM_ID;
+
+/** 客户端错误 */
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("rpc error: {0}")]
+    Rpc(Box<solana_rpc_client_api::client_error::Error>),
+    #[error("account {0} not found")]
+    AccountNotFound(Pubkey),
+    #[error("failed to deserialize account {0}: {1}")]
+    Deserialize(Pubkey, anchor_lang::error::Error),
+    #[error("account {0} is not a token mint")]
+    NotAMint(Pubkey),
+    #[error("exchange rate of share vault {0} overflows")]
+    ExchangeRateOverflow(Pubkey),
+}
+
+impl From<solana_rpc_client_api::client_error::Error> for ClientError {
+    fn from(err: solana_rpc_client_api::client_error::Error) -> Self {
+        ClientError::Rpc(Box::new(err))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+// 构造 anchor 指令
+pub(crate) fn anchor_ix(program_id: Pubkey, accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction { program_id, accounts: accounts.to_account_metas(None), data: args.data() }
+}
+
+/** ATA 地址，token_program 为 SPL Token 或 Token-2022 */
+pub fn ata(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+/** 读取并反序列化 anchor 账户 */
+pub fn fetch_account<T: AccountDeserialize>(rpc: &RpcClient, address: &Pubkey) -> Result<T> {
+    let account = rpc
+        .get_account_with_commitment(address, rpc.commitment())?
+        .value
+        .ok_or(ClientError::AccountNotFound(*address))?;
+    T::try_deserialize(&mut account.data.as_slice()).map_err(|err| ClientError::Deserialize(*address, err))
+}
+
+/** 读取mint所属的代币程序（Token 或 Token-2022） */
+pub fn token_program_for_mint(rpc: &RpcClient, mint: &Pubkey) -> Result<Pubkey> {
+    let account = rpc
+        .get_account_with_commitment(mint, rpc.commitment())?
+        .value
+        .ok_or(ClientError::AccountNotFound(*mint))?;
+    if account.owner != TOKEN_PROGRAM_ID && account.owner != TOKEN_2022_PROGRAM_ID {
+        return Err(ClientError::NotAMint(*mint));
+    }
+    Ok(account.owner)
+}
+
+/** 需要的 ATA（owner, mint, token_program）不存在时，在指令前插入创建指令，由 payer 支付租金 */
+pub fn with_ata_creation(
+    rpc: &RpcClient,
+    payer: &Pubkey,
+    atas: &[(Pubkey, Pubkey, Pubkey)],
+    instructions: Vec<Instruction>,
+) -> Result<Vec<Instruction>> {
+    let mut result = Vec::with_capacity(atas.len() + instructions.len());
+    for (owner, mint, token_program) in atas {
+        let address = ata(owner, mint, token_program);
+        if rpc.get_account_with_commitment(&address, rpc.commitment())?.value.is_none() {
+            result.push(create_associated_token_account(payer, owner, mint, token_program));
+        }
+    }
+    result.extend(instructions);
+    Ok(result)
+}
+
+/** 使用最新 blockhash 构造并签名交易，signers[0] 为手续费支付者 */
+pub fn build_transaction(rpc: &RpcClient, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Transaction> {
+    let blockhash = rpc.get_latest_blockhash()?;
+    Ok(Transaction::new_signed_with_payer(instructions, Some(&signers[0].pubkey()), signers, blockhash))
+}
@@ -0,0 +1,175 @@
+//! task_1（SOL 质押、全局配置）的 PDA 和指令
+
+use ::task_1::{accounts, instruction, Config, StackAccount};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::{bpf_loader_upgradeable, instruction::Instruction, pubkey::Pubkey, system_program};
+
+use crate::{anchor_ix, fetch_account, ClientError, Result};
+
+pub use ::task_1::ID as PROGRAM_ID;
+pub use ::task_1::{ConfigParams, STACK_ACCOUNT_VERSION};
+
+/** 存放 SOL 的质押账户 [b"stack", user] */
+pub fn stack_vault(user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"stack", user.as_ref()], &PROGRAM_ID)
+}
+
+/** 仓位数据账户 [user] */
+pub fn stack_account(user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[user.as_ref()], &PROGRAM_ID)
+}
+
+/** 全局配置 [b"config"] */
+pub fn config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], &PROGRAM_ID)
+}
+
+/** 手续费金库 [b"treasury"] */
+pub fn treasury() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"treasury"], &PROGRAM_ID)
+}
+
+/** 程序的 ProgramData 账户，记录升级权限 */
+pub fn program_data() -> Pubkey {
+    Pubkey::find_program_address(&[PROGRAM_ID.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+pub fn initialize_config(authority: &Pubkey, params: ConfigParams) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::InitializeConfig {
+            authority: *authority,
+            config: config().0,
+            program: PROGRAM_ID,
+            program_data: program_data(),
+            system_program: system_program::id(),
+        },
+        instruction::InitializeConfig { params },
+    )
+}
+
+pub fn set_paused(admin: &Pubkey, paused: bool) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::UpdateConfig { admin: *admin, config: config().0 },
+        instruction::SetPaused { paused },
+    )
+}
+
+pub fn update_config(admin: &Pubkey, params: ConfigParams) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::UpdateConfig { admin: *admin, config: config().0 },
+        instruction::UpdateConfig { params },
+    )
+}
+
+pub fn propose_admin(admin: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::UpdateConfig { admin: *admin, config: config().0 },
+        instruction::ProposeAdmin { new_admin: *new_admin },
+    )
+}
+
+pub fn accept_admin(pending_admin: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::AcceptAdmin { pending_admin: *pending_admin, config: config().0 },
+        instruction::AcceptAdmin {},
+    )
+}
+
+/** 把金库中的手续费提取到 recipient */
+pub fn collect_fees(admin: &Pubkey, recipient: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::CollectFees {
+            admin: *admin,
+            config: config().0,
+            treasury: treasury().0,
+            recipient: *recipient,
+            system_program: system_program::id(),
+        },
+        instruction::CollectFees {},
+    )
+}
+
+fn deposit_accounts(user: &Pubkey) -> accounts::Deposit {
+    accounts::Deposit {
+        user: *user,
+        stack_account_pda: stack_vault(user).0,
+        stack_account: stack_account(user).0,
+        config: config().0,
+        treasury: treasury().0,
+        system_program: system_program::id(),
+    }
+}
+
+fn withdraw_accounts(user: &Pubkey) -> accounts::Withdraw {
+    accounts::Withdraw {
+        user: *user,
+        stack_account_pda: stack_vault(user).0,
+        stack_account: stack_account(user).0,
+        config: config().0,
+        treasury: treasury().0,
+        system_program: system_program::id(),
+    }
+}
+
+pub fn deposit(user: &Pubkey, amount: u64) -> Instruction {
+    anchor_ix(PROGRAM_ID, deposit_accounts(user), instruction::Deposit { amount })
+}
+
+pub fn deposit_locked(user: &Pubkey, amount: u64, lock_duration: i64) -> Instruction {
+    anchor_ix(PROGRAM_ID, deposit_accounts(user), instruction::DepositLocked { amount, lock_duration })
+}
+
+pub fn withdraw(user: &Pubkey) -> Instruction {
+    anchor_ix(PROGRAM_ID, withdraw_accounts(user), instruction::Withdraw {})
+}
+
+pub fn withdraw_amount(user: &Pubkey, amount: u64) -> Instruction {
+    anchor_ix(PROGRAM_ID, withdraw_accounts(user), instruction::WithdrawAmount { amount })
+}
+
+pub fn close_position(user: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::ClosePosition {
+            user: *user,
+            stack_account_pda: stack_vault(user).0,
+            stack_account: stack_account(user).0,
+            system_program: system_program::id(),
+        },
+        instruction::ClosePosition {},
+    )
+}
+
+/** 把旧布局仓位扩容到当前布局，用户支付新增租金 */
+pub fn migrate_position(user: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::MigratePosition {
+            user: *user,
+            stack_account: stack_account(user).0,
+            system_program: system_program::id(),
+        },
+        instruction::MigratePosition {},
+    )
+}
+
+/** 读取用户的仓位，旧布局账户的 version 为对应的旧版本号 */
+pub fn fetch_position(rpc: &RpcClient, user: &Pubkey) -> Result<StackAccount> {
+    let address = stack_account(user).0;
+    let account = rpc
+        .get_account_with_commitment(&address, rpc.commitment())?
+        .value
+        .ok_or(ClientError::AccountNotFound(address))?;
+    StackAccount::try_deserialize_versioned(&account.data).map_err(|err| ClientError::Deserialize(address, err))
+}
+
+/** 读取全局配置 */
+pub fn fetch_config(rpc: &RpcClient) -> Result<Config> {
+    fetch_account(rpc, &config().0)
+}
@@ -0,0 +1,1093 @@
+//! task_2（SOL 与 spl 质押、代币创建、奖励池、全局配置、多签）的 PDA 和指令
+//!
+//! spl 相关指令都接收 token_program 参数，可以是 SPL Token 或 Token-2022。
+
+use ::task_2::instructions::{
+    config::Config,
+    multisig::{Multisig, Proposal, ProposalAccount},
+    reward::RewardPool,
+    spl::MintConfig,
+    stack::StackAccount,
+    vault::ShareVault,
+};
+use ::task_2::{accounts, instruction};
+use anchor_spl::{associated_token, metadata::mpl_token_metadata, token_interface::TokenAccount};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::{
+    bpf_loader_upgradeable,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+use crate::{anchor_ix, ata, fetch_account, with_ata_creation, ClientError, Result};
+
+pub use ::task_2::instructions::{
+    config::ConfigParams,
+    multisig::ProposalInstruction,
+    spl::{InitTokenParams, TokenCreator, UpdateTokenMetadataParams},
+    stack::{NATIVE_MINT, STACK_ACCOUNT_VERSION},
+    vault::{ExchangeRate, RATE_SCALE},
+};
+pub use ::task_2::ID as PROGRAM_ID;
+
+fn pda(seeds: &[&[u8]]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, &PROGRAM_ID)
+}
+
+/** 按 symbol 创建的mint [b"mint", symbol] */
+pub fn mint(symbol: &str) -> (Pubkey, u8) {
+    pda(&[b"mint", symbol.as_bytes()])
+}
+
+/** mint配置账户 [b"mint_config", mint] */
+pub fn mint_config(mint: &Pubkey) -> (Pubkey, u8) {
+    pda(&[b"mint_config", mint.as_ref()])
+}
+
+/** 全局配置 [b"config"] */
+pub fn config() -> (Pubkey, u8) {
+    pda(&[b"config"])
+}
+
+/** 多签账户 [b"multisig"] */
+pub fn multisig() -> (Pubkey, u8) {
+    pda(&[b"multisig"])
+}
+
+/** 多签签名PDA [b"multisig_signer"]，执行提案时签名 */
+pub fn multisig_signer() -> (Pubkey, u8) {
+    pda(&[b"multisig_signer"])
+}
+
+/** 提案 [b"proposal", index] */
+pub fn proposal(index: u64) -> (Pubkey, u8) {
+    pda(&[b"proposal", &index.to_le_bytes()])
+}
+
+/** 代币注册表 [b"registry"] */
+pub fn registry() -> (Pubkey, u8) {
+    pda(&[b"registry"])
+}
+
+/** Metaplex 元数据账户 */
+pub fn metadata(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"metadata", mpl_token_metadata::ID.as_ref(), mint.as_ref()],
+        &mpl_token_metadata::ID,
+    )
+}
+
+/** 质押白名单账户 [b"whitelist", mint] */
+pub fn whitelisted_mint(mint: &Pubkey) -> (Pubkey, u8) {
+    pda(&[b"whitelist", mint.as_ref()])
+}
+
+/** 奖励池 [b"reward_pool", mint] */
+pub fn reward_pool(stake_mint: &Pubkey) -> (Pubkey, u8) {
+    pda(&[b"reward_pool", stake_mint.as_ref()])
+}
+
+/** 手续费金库 [b"treasury"]，SOL 手续费存在这里，也是各代币手续费ATA的所有者 */
+pub fn treasury() -> (Pubkey, u8) {
+    pda(&[b"treasury"])
+}
+
+/** 某种代币的手续费ATA */
+pub fn treasury_ata(mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    ata(&treasury().0, mint, token_program)
+}
+
+/** 解包 WSOL 时使用的临时代币账户 [b"unwrap", user]，在 withdraw_spl_native 内创建并关闭 */
+pub fn unwrap_account(user: &Pubkey) -> (Pubkey, u8) {
+    pda(&[b"unwrap", user.as_ref()])
+}
+
+/** 份额金库 [b"share_vault", mint] */
+pub fn share_vault(mint: &Pubkey) -> (Pubkey, u8) {
+    pda(&[b"share_vault", mint.as_ref()])
+}
+
+/** 份额金库的凭证代币 [b"receipt", mint] */
+pub fn receipt_mint(mint: &Pubkey) -> (Pubkey, u8) {
+    pda(&[b"receipt", mint.as_ref()])
+}
+
+/** 份额金库存放资产的ATA */
+pub fn share_vault_ata(mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    ata(&share_vault(mint).0, mint, token_program)
+}
+
+/** 程序的 ProgramData 账户，记录升级权限 */
+pub fn program_data() -> Pubkey {
+    Pubkey::find_program_address(&[PROGRAM_ID.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+/** SOL 质押账户 [b"sol_stack", user] */
+pub fn sol_vault(user: &Pubkey) -> (Pubkey, u8) {
+    pda(&[b"sol_stack", user.as_ref()])
+}
+
+/** SOL 仓位数据账户 [b"sol_position", user] */
+pub fn sol_stack_account(user: &Pubkey) -> (Pubkey, u8) {
+    pda(&[b"sol_position", user.as_ref()])
+}
+
+/** 初始版本的 spl 仓位数据账户 [user]，由 withdraw_spl_legacy 提取并关闭 */
+pub fn legacy_stack_account(user: &Pubkey) -> (Pubkey, u8) {
+    pda(&[user.as_ref()])
+}
+
+/** 初始版本的全局代币 [b"mint"] */
+pub fn legacy_mint() -> (Pubkey, u8) {
+    pda(&[b"mint"])
+}
+
+/** spl 质押PDA [b"stack", user]，所有代币的质押ATA都归它所有 */
+pub fn spl_vault_authority(user: &Pubkey) -> (Pubkey, u8) {
+    pda(&[b"stack", user.as_ref()])
+}
+
+/** spl 仓位数据账户 [user, mint] */
+pub fn spl_stack_account(user: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    pda(&[user.as_ref(), mint.as_ref()])
+}
+
+/** 用户某种代币的质押ATA */
+pub fn spl_vault(user: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    ata(&spl_vault_authority(user).0, mint, token_program)
+}
+
+pub fn initialize_config(authority: &Pubkey, params: ConfigParams) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::InitializeConfig {
+            authority: *authority,
+            config: config().0,
+            program: PROGRAM_ID,
+            program_data: program_data(),
+            system_program: system_program::id(),
+        },
+        instruction::InitializeConfig { params },
+    )
+}
+
+pub fn set_paused(admin: &Pubkey, paused: bool) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::UpdateConfig { admin: *admin, config: config().0 },
+        instruction::SetPaused { paused },
+    )
+}
+
+pub fn update_config(admin: &Pubkey, params: ConfigParams) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::UpdateConfig { admin: *admin, config: config().0 },
+        instruction::UpdateConfig { params },
+    )
+}
+
+pub fn propose_admin(admin: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::UpdateConfig { admin: *admin, config: config().0 },
+        instruction::ProposeAdmin { new_admin: *new_admin },
+    )
+}
+
+pub fn accept_admin(pending_admin: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::AcceptAdmin { pending_admin: *pending_admin, config: config().0 },
+        instruction::AcceptAdmin {},
+    )
+}
+
+pub fn create_multisig(admin: &Pubkey, signers: Vec<Pubkey>, threshold: u8) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::CreateMultisig {
+            admin: *admin,
+            config: config().0,
+            multisig: multisig().0,
+            multisig_signer: multisig_signer().0,
+            system_program: system_program::id(),
+        },
+        instruction::CreateMultisig { signers, threshold },
+    )
+}
+
+/** 修改多签成员的指令，只能作为提案执行 */
+pub fn set_multisig_signers(signers: Vec<Pubkey>, threshold: u8) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::SetMultisigSigners { multisig_signer: multisig_signer().0, multisig: multisig().0 },
+        instruction::SetMultisigSigners { signers, threshold },
+    )
+}
+
+/** 把任意指令提交为提案，index 为多签当前的 proposal_count */
+pub fn create_proposal(proposer: &Pubkey, index: u64, ix: &Instruction) -> Instruction {
+    let instruction = ProposalInstruction {
+        program_id: ix.program_id,
+        accounts: ix
+            .accounts
+            .iter()
+            .map(|meta| ProposalAccount { pubkey: meta.pubkey, is_signer: meta.is_signer, is_writable: meta.is_writable })
+            .collect(),
+        data: ix.data.clone(),
+    };
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::CreateProposal {
+            proposer: *proposer,
+            multisig: multisig().0,
+            proposal: proposal(index).0,
+            system_program: system_program::id(),
+        },
+        instruction::CreateProposal { instruction },
+    )
+}
+
+pub fn approve_proposal(signer: &Pubkey, index: u64) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::ApproveProposal { signer: *signer, multisig: multisig().0, proposal: proposal(index).0 },
+        instruction::ApproveProposal {},
+    )
+}
+
+/** 执行提案，附加提案指令用到的账户和目标程序；多签签名PDA在 CPI 中签名，这里不标记为签名者 */
+pub fn execute_proposal(proposal: &Proposal) -> Instruction {
+    let signer = multisig_signer().0;
+    let mut ix = anchor_ix(
+        PROGRAM_ID,
+        accounts::ExecuteProposal {
+            multisig: multisig().0,
+            proposal: self::proposal(proposal.index).0,
+            multisig_signer: signer,
+        },
+        instruction::ExecuteProposal {},
+    );
+    ix.accounts.extend(
+        proposal
+            .accounts
+            .iter()
+            .filter(|account| account.pubkey != signer)
+            .map(|account| AccountMeta { pubkey: account.pubkey, is_signer: false, is_writable: account.is_writable }),
+    );
+    ix.accounts.push(AccountMeta::new_readonly(proposal.program_id, false));
+    ix
+}
+
+pub fn propose_mint_authority(authority: &Pubkey, mint: &Pubkey, new_authority: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::ProposeMintAuthority { authority: *authority, mint_config: mint_config(mint).0 },
+        instruction::ProposeMintAuthority { new_authority: *new_authority },
+    )
+}
+
+pub fn accept_mint_authority(pending_authority: &Pubkey, mint: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::AcceptMintAuthority { pending_authority: *pending_authority, mint_config: mint_config(mint).0 },
+        instruction::AcceptMintAuthority {},
+    )
+}
+
+/** 持有人销毁自己 ATA 中的代币 */
+pub fn burn_spl(holder: &Pubkey, mint: &Pubkey, amount: u64, token_program: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::BurnSpl {
+            holder: *holder,
+            mint_config: mint_config(mint).0,
+            mint: *mint,
+            holder_ata: ata(holder, mint, token_program),
+            token_program: *token_program,
+        },
+        instruction::BurnSpl { amount },
+    )
+}
+
+/** 管理员销毁用户质押ATA中超出仓位余额的代币，不修改用户仓位 */
+pub fn burn_from_vault(
+    admin: &Pubkey,
+    user: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    token_program: &Pubkey,
+) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::BurnFromVault {
+            admin: *admin,
+            config: config().0,
+            user: *user,
+            pda_stack_account: spl_vault_authority(user).0,
+            stack_account: spl_stack_account(user, mint).0,
+            mint_config: mint_config(mint).0,
+            mint: *mint,
+            vault_ata: spl_vault(user, mint, token_program),
+            token_program: *token_program,
+        },
+        instruction::BurnFromVault { amount },
+    )
+}
+
+/** 永久撤销铸币权限（仅当前铸币权限账户），mint authority 设为 None */
+pub fn revoke_mint_authority(authority: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::RevokeMintAuthority {
+            authority: *authority,
+            mint_config: mint_config(mint).0,
+            mint: *mint,
+            token_program: *token_program,
+        },
+        instruction::RevokeMintAuthority {},
+    )
+}
+
+pub fn create_token(payer: &Pubkey, params: InitTokenParams, token_program: &Pubkey) -> Instruction {
+    let mint = mint(&params.symbol).0;
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::CreateSpl {
+            payer: *payer,
+            config: config().0,
+            mint,
+            mint_config: mint_config(&mint).0,
+            registry: registry().0,
+            metadata: metadata(&mint).0,
+            rent: sysvar::rent::id(),
+            system_program: system_program::id(),
+            token_program: *token_program,
+            token_metadata_program: mpl_token_metadata::ID,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::CreateToken { metadata: params },
+    )
+}
+
+fn update_token_metadata_accounts(admin: &Pubkey, mint: &Pubkey) -> accounts::UpdateTokenMetadata {
+    accounts::UpdateTokenMetadata {
+        admin: *admin,
+        config: config().0,
+        mint_config: mint_config(mint).0,
+        mint: *mint,
+        metadata: metadata(mint).0,
+        token_metadata_program: mpl_token_metadata::ID,
+    }
+}
+
+/** 修改代币元数据（仅管理员），mint的地址不随 symbol 变化 */
+pub fn update_token_metadata(admin: &Pubkey, mint: &Pubkey, params: UpdateTokenMetadataParams) -> Instruction {
+    anchor_ix(PROGRAM_ID, update_token_metadata_accounts(admin, mint), instruction::UpdateTokenMetadata { params })
+}
+
+/** 永久锁定代币元数据（仅管理员），不可撤销 */
+pub fn freeze_metadata(admin: &Pubkey, mint: &Pubkey) -> Instruction {
+    anchor_ix(PROGRAM_ID, update_token_metadata_accounts(admin, mint), instruction::FreezeMetadata {})
+}
+
+fn freeze_token_account_accounts(
+    admin: &Pubkey,
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    token_program: &Pubkey,
+) -> accounts::FreezeTokenAccount {
+    accounts::FreezeTokenAccount {
+        admin: *admin,
+        config: config().0,
+        mint_config: mint_config(mint).0,
+        mint: *mint,
+        token_account: *token_account,
+        token_program: *token_program,
+    }
+}
+
+/** 冻结任意持有人的代币账户（仅管理员），mint PDA 作为 freeze authority 签名 */
+pub fn freeze_account(admin: &Pubkey, mint: &Pubkey, token_account: &Pubkey, token_program: &Pubkey) -> Instruction {
+    let accounts = freeze_token_account_accounts(admin, mint, token_account, token_program);
+    anchor_ix(PROGRAM_ID, accounts, instruction::FreezeAccount {})
+}
+
+/** 解冻代币账户（仅管理员） */
+pub fn thaw_account(admin: &Pubkey, mint: &Pubkey, token_account: &Pubkey, token_program: &Pubkey) -> Instruction {
+    let accounts = freeze_token_account_accounts(admin, mint, token_account, token_program);
+    anchor_ix(PROGRAM_ID, accounts, instruction::ThawAccount {})
+}
+
+pub fn mint_spl(
+    authority: &Pubkey,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+    token_program: &Pubkey,
+) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::MintSpl {
+            payer: *authority,
+            mint_config: mint_config(mint).0,
+            mint: *mint,
+            config: config().0,
+            recipient: *recipient,
+            recipient_ata: ata(recipient, mint, token_program),
+            system_program: system_program::id(),
+            token_program: *token_program,
+            token_metadata_program: mpl_token_metadata::ID,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::MintSpl { amount },
+    )
+}
+
+fn sol_deposit_accounts(user: &Pubkey) -> accounts::Deposit {
+    accounts::Deposit {
+        user: *user,
+        stack_account_pda: sol_vault(user).0,
+        stack_account: sol_stack_account(user).0,
+        config: config().0,
+        treasury: treasury().0,
+        system_program: system_program::id(),
+    }
+}
+
+fn sol_withdraw_accounts(user: &Pubkey) -> accounts::Withdraw {
+    accounts::Withdraw {
+        user: *user,
+        stack_account_pda: sol_vault(user).0,
+        stack_account: sol_stack_account(user).0,
+        config: config().0,
+        treasury: treasury().0,
+        system_program: system_program::id(),
+    }
+}
+
+pub fn deposit(user: &Pubkey, amount: u64) -> Instruction {
+    anchor_ix(PROGRAM_ID, sol_deposit_accounts(user), instruction::Deposit { amount })
+}
+
+pub fn deposit_locked(user: &Pubkey, amount: u64, lock_duration: i64) -> Instruction {
+    anchor_ix(PROGRAM_ID, sol_deposit_accounts(user), instruction::DepositLocked { amount, lock_duration })
+}
+
+pub fn withdraw(user: &Pubkey) -> Instruction {
+    anchor_ix(PROGRAM_ID, sol_withdraw_accounts(user), instruction::Withdraw {})
+}
+
+pub fn withdraw_amount(user: &Pubkey, amount: u64) -> Instruction {
+    anchor_ix(PROGRAM_ID, sol_withdraw_accounts(user), instruction::WithdrawAmount { amount })
+}
+
+pub fn close_position(user: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::ClosePosition {
+            user: *user,
+            stack_account_pda: sol_vault(user).0,
+            stack_account: sol_stack_account(user).0,
+            system_program: system_program::id(),
+        },
+        instruction::ClosePosition {},
+    )
+}
+
+pub fn add_whitelisted_mint(authority: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::AddWhitelistedMint {
+            authority: *authority,
+            mint: *mint,
+            whitelisted_mint: whitelisted_mint(mint).0,
+            reward_pool: reward_pool(mint).0,
+            treasury: treasury().0,
+            treasury_ata: treasury_ata(mint, token_program),
+            program: PROGRAM_ID,
+            program_data: program_data(),
+            system_program: system_program::id(),
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::AddWhitelistedMint {},
+    )
+}
+
+pub fn remove_whitelisted_mint(authority: &Pubkey, mint: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::RemoveWhitelistedMint {
+            authority: *authority,
+            whitelisted_mint: whitelisted_mint(mint).0,
+            program: PROGRAM_ID,
+            program_data: program_data(),
+        },
+        instruction::RemoveWhitelistedMint {},
+    )
+}
+
+fn spl_deposit_accounts(user: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> accounts::DepositSpl {
+    accounts::DepositSpl {
+        payer: *user,
+        pda_stack_account: spl_vault_authority(user).0,
+        stack_account: spl_stack_account(user, mint).0,
+        mint: *mint,
+        whitelisted_mint: whitelisted_mint(mint).0,
+        reward_pool: reward_pool(mint).0,
+        config: config().0,
+        payer_ata: ata(user, mint, token_program),
+        stack_account_ata: spl_vault(user, mint, token_program),
+        treasury: treasury().0,
+        treasury_ata: treasury_ata(mint, token_program),
+        system_program: system_program::id(),
+        token_program: *token_program,
+        associated_token_program: associated_token::ID,
+    }
+}
+
+pub fn deposit_spl(user: &Pubkey, mint: &Pubkey, amount: u64, token_program: &Pubkey) -> Instruction {
+    anchor_ix(PROGRAM_ID, spl_deposit_accounts(user, mint, token_program), instruction::DepositSpl { amount })
+}
+
+pub fn deposit_spl_locked(
+    user: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    lock_duration: i64,
+    token_program: &Pubkey,
+) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        spl_deposit_accounts(user, mint, token_program),
+        instruction::DepositSplLocked { amount, lock_duration },
+    )
+}
+
+pub fn withdraw_spl(user: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::WithdrawSpl {
+            payer: *user,
+            pda_stack_account: spl_vault_authority(user).0,
+            stack_account: spl_stack_account(user, mint).0,
+            mint: *mint,
+            reward_pool: reward_pool(mint).0,
+            config: config().0,
+            user_ata: ata(user, mint, token_program),
+            stack_account_ata: spl_vault(user, mint, token_program),
+            treasury: treasury().0,
+            treasury_ata: treasury_ata(mint, token_program),
+            system_program: system_program::id(),
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::WithdrawSpl {},
+    )
+}
+
+/** 提取初始版本的 spl 仓位并关闭旧数据账户，旧代币属于 SPL Token 程序 */
+pub fn withdraw_spl_legacy(user: &Pubkey) -> Instruction {
+    let token_program = crate::TOKEN_PROGRAM_ID;
+    let mint = legacy_mint().0;
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::WithdrawSplLegacy {
+            payer: *user,
+            pda_stack_account: spl_vault_authority(user).0,
+            legacy_stack_account: legacy_stack_account(user).0,
+            mint,
+            config: config().0,
+            user_ata: ata(user, &mint, &token_program),
+            stack_account_ata: spl_vault(user, &mint, &token_program),
+            token_program,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::WithdrawSplLegacy {},
+    )
+}
+
+/** 直接用 lamports 充值 WSOL 仓位，WSOL 需要先加入白名单 */
+pub fn deposit_spl_native(user: &Pubkey, amount: u64) -> Instruction {
+    let token_program = crate::TOKEN_PROGRAM_ID;
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::DepositSplNative {
+            payer: *user,
+            pda_stack_account: spl_vault_authority(user).0,
+            stack_account: spl_stack_account(user, &NATIVE_MINT).0,
+            mint: NATIVE_MINT,
+            whitelisted_mint: whitelisted_mint(&NATIVE_MINT).0,
+            reward_pool: reward_pool(&NATIVE_MINT).0,
+            config: config().0,
+            stack_account_ata: spl_vault(user, &NATIVE_MINT, &token_program),
+            treasury: treasury().0,
+            treasury_ata: treasury_ata(&NATIVE_MINT, &token_program),
+            system_program: system_program::id(),
+            token_program,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::DepositSplNative { amount },
+    )
+}
+
+/** 提取全部 WSOL 仓位，解包后以 SOL 退回用户钱包 */
+pub fn withdraw_spl_native(user: &Pubkey) -> Instruction {
+    let token_program = crate::TOKEN_PROGRAM_ID;
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::WithdrawSplNative {
+            payer: *user,
+            pda_stack_account: spl_vault_authority(user).0,
+            stack_account: spl_stack_account(user, &NATIVE_MINT).0,
+            mint: NATIVE_MINT,
+            reward_pool: reward_pool(&NATIVE_MINT).0,
+            config: config().0,
+            stack_account_ata: spl_vault(user, &NATIVE_MINT, &token_program),
+            unwrap_account: unwrap_account(user).0,
+            treasury: treasury().0,
+            treasury_ata: treasury_ata(&NATIVE_MINT, &token_program),
+            system_program: system_program::id(),
+            token_program,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::WithdrawSplNative {},
+    )
+}
+
+pub fn close_position_spl(user: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::ClosePositionSpl {
+            payer: *user,
+            pda_stack_account: spl_vault_authority(user).0,
+            stack_account: spl_stack_account(user, mint).0,
+            mint: *mint,
+            stack_account_ata: spl_vault(user, mint, token_program),
+            system_program: system_program::id(),
+            token_program: *token_program,
+        },
+        instruction::ClosePositionSpl {},
+    )
+}
+
+/** 把旧布局仓位扩容到当前布局，用户支付新增租金；mint 为 None 时迁移 SOL 仓位 */
+pub fn migrate_position(user: &Pubkey, mint: Option<&Pubkey>) -> Instruction {
+    let stack_account = match mint {
+        Some(mint) => spl_stack_account(user, mint).0,
+        None => sol_stack_account(user).0,
+    };
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::MigratePosition {
+            user: *user,
+            stack_account,
+            reward_pool: mint.map(|mint| reward_pool(mint).0),
+            system_program: system_program::id(),
+        },
+        instruction::MigratePosition { mint: mint.copied() },
+    )
+}
+
+/** 把 SOL 手续费提取到 recipient */
+pub fn collect_fees(admin: &Pubkey, recipient: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::CollectFees {
+            admin: *admin,
+            config: config().0,
+            treasury: treasury().0,
+            recipient: *recipient,
+            system_program: system_program::id(),
+        },
+        instruction::CollectFees {},
+    )
+}
+
+/** 为加入白名单时还没有手续费ATA的代币创建它 */
+pub fn init_treasury_ata(admin: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::InitTreasuryAta {
+            admin: *admin,
+            config: config().0,
+            treasury: treasury().0,
+            mint: *mint,
+            treasury_ata: treasury_ata(mint, token_program),
+            system_program: system_program::id(),
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::InitTreasuryAta {},
+    )
+}
+
+/** 把某种代币的手续费提取到 recipient 的 ATA */
+pub fn collect_fees_spl(admin: &Pubkey, mint: &Pubkey, recipient: &Pubkey, token_program: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::CollectFeesSpl {
+            admin: *admin,
+            config: config().0,
+            treasury: treasury().0,
+            mint: *mint,
+            treasury_ata: treasury_ata(mint, token_program),
+            recipient_ata: ata(recipient, mint, token_program),
+            token_program: *token_program,
+        },
+        instruction::CollectFeesSpl {},
+    )
+}
+
+/** 为白名单代币创建份额金库和凭证代币，凭证代币与资产使用同一个代币程序 */
+pub fn initialize_share_vault(admin: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::InitializeShareVault {
+            admin: *admin,
+            config: config().0,
+            mint: *mint,
+            whitelisted_mint: whitelisted_mint(mint).0,
+            share_vault: share_vault(mint).0,
+            receipt_mint: receipt_mint(mint).0,
+            vault_ata: share_vault_ata(mint, token_program),
+            system_program: system_program::id(),
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::InitializeShareVault {},
+    )
+}
+
+/** 存入 amount 资产，按比例获得凭证代币 */
+pub fn deposit_shares(user: &Pubkey, mint: &Pubkey, amount: u64, token_program: &Pubkey) -> Instruction {
+    let receipt_mint = receipt_mint(mint).0;
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::DepositShares {
+            payer: *user,
+            mint: *mint,
+            whitelisted_mint: whitelisted_mint(mint).0,
+            config: config().0,
+            share_vault: share_vault(mint).0,
+            receipt_mint,
+            payer_ata: ata(user, mint, token_program),
+            payer_receipt_ata: ata(user, &receipt_mint, token_program),
+            vault_ata: share_vault_ata(mint, token_program),
+            treasury: treasury().0,
+            treasury_ata: treasury_ata(mint, token_program),
+            system_program: system_program::id(),
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::DepositShares { amount },
+    )
+}
+
+/** 销毁 shares 份额凭证代币，资产转入用户的ATA */
+pub fn withdraw_shares(user: &Pubkey, mint: &Pubkey, shares: u64, token_program: &Pubkey) -> Instruction {
+    let receipt_mint = receipt_mint(mint).0;
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::WithdrawShares {
+            payer: *user,
+            mint: *mint,
+            config: config().0,
+            share_vault: share_vault(mint).0,
+            receipt_mint,
+            payer_receipt_ata: ata(user, &receipt_mint, token_program),
+            user_ata: ata(user, mint, token_program),
+            vault_ata: share_vault_ata(mint, token_program),
+            treasury: treasury().0,
+            treasury_ata: treasury_ata(mint, token_program),
+            system_program: system_program::id(),
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::WithdrawShares { shares },
+    )
+}
+
+/** 查询兑换比例，模拟交易后从返回数据中读取 ExchangeRate */
+pub fn exchange_rate(mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::QueryShareVault {
+            mint: *mint,
+            share_vault: share_vault(mint).0,
+            vault_ata: share_vault_ata(mint, token_program),
+            token_program: *token_program,
+        },
+        instruction::ExchangeRate {},
+    )
+}
+
+pub fn configure_reward_pool(
+    admin: &Pubkey,
+    stake_mint: &Pubkey,
+    reward_mint: &Pubkey,
+    reward_rate: u64,
+    token_program: &Pubkey,
+) -> Instruction {
+    let reward_pool = reward_pool(stake_mint).0;
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::ConfigureRewardPool {
+            admin: *admin,
+            reward_pool,
+            reward_mint: *reward_mint,
+            reward_vault: ata(&reward_pool, reward_mint, token_program),
+            system_program: system_program::id(),
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::ConfigureRewardPool { reward_rate },
+    )
+}
+
+pub fn fund_reward_pool(
+    admin: &Pubkey,
+    stake_mint: &Pubkey,
+    reward_mint: &Pubkey,
+    amount: u64,
+    token_program: &Pubkey,
+) -> Instruction {
+    let reward_pool = reward_pool(stake_mint).0;
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::FundRewardPool {
+            admin: *admin,
+            reward_pool,
+            reward_mint: *reward_mint,
+            admin_ata: ata(admin, reward_mint, token_program),
+            reward_vault: ata(&reward_pool, reward_mint, token_program),
+            token_program: *token_program,
+        },
+        instruction::FundRewardPool { amount },
+    )
+}
+
+pub fn claim_rewards(user: &Pubkey, stake_mint: &Pubkey, reward_mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    let reward_pool = reward_pool(stake_mint).0;
+    anchor_ix(
+        PROGRAM_ID,
+        accounts::ClaimRewards {
+            payer: *user,
+            reward_pool,
+            stack_account: spl_stack_account(user, stake_mint).0,
+            config: config().0,
+            reward_mint: *reward_mint,
+            reward_vault: ata(&reward_pool, reward_mint, token_program),
+            user_reward_ata: ata(user, reward_mint, token_program),
+            system_program: system_program::id(),
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::ClaimRewards {},
+    )
+}
+
+/** 提现 spl，用户的ATA不存在时（例如已关闭）先创建 */
+pub fn withdraw_spl_instructions(
+    rpc: &RpcClient,
+    user: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<Vec<Instruction>> {
+    with_ata_creation(rpc, user, &[(*user, *mint, *token_program)], vec![withdraw_spl(user, mint, token_program)])
+}
+
+/** 提取初始版本的 spl 仓位，用户的ATA不存在时先创建 */
+pub fn withdraw_spl_legacy_instructions(rpc: &RpcClient, user: &Pubkey) -> Result<Vec<Instruction>> {
+    let atas = [(*user, legacy_mint().0, crate::TOKEN_PROGRAM_ID)];
+    with_ata_creation(rpc, user, &atas, vec![withdraw_spl_legacy(user)])
+}
+
+/** 提取 spl 手续费，接收者的ATA不存在时由管理员先创建 */
+pub fn collect_fees_spl_instructions(
+    rpc: &RpcClient,
+    admin: &Pubkey,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<Vec<Instruction>> {
+    with_ata_creation(
+        rpc,
+        admin,
+        &[(*recipient, *mint, *token_program)],
+        vec![collect_fees_spl(admin, mint, recipient, token_program)],
+    )
+}
+
+/** 赎回份额，用户的资产ATA不存在时先创建 */
+pub fn withdraw_shares_instructions(
+    rpc: &RpcClient,
+    user: &Pubkey,
+    mint: &Pubkey,
+    shares: u64,
+    token_program: &Pubkey,
+) -> Result<Vec<Instruction>> {
+    with_ata_creation(rpc, user, &[(*user, *mint, *token_program)], vec![withdraw_shares(user, mint, shares, token_program)])
+}
+
+// 读取仓位，旧布局账户的 version 为对应的旧版本号
+fn fetch_position(rpc: &RpcClient, address: &Pubkey) -> Result<StackAccount> {
+    let account = rpc
+        .get_account_with_commitment(address, rpc.commitment())?
+        .value
+        .ok_or(ClientError::AccountNotFound(*address))?;
+    StackAccount::try_deserialize_versioned(&account.data).map_err(|err| ClientError::Deserialize(*address, err))
+}
+
+/** 读取用户的 SOL 仓位 */
+pub fn fetch_sol_position(rpc: &RpcClient, user: &Pubkey) -> Result<StackAccount> {
+    fetch_position(rpc, &sol_stack_account(user).0)
+}
+
+/** 读取用户某种代币的 spl 仓位 */
+pub fn fetch_spl_position(rpc: &RpcClient, user: &Pubkey, mint: &Pubkey) -> Result<StackAccount> {
+    fetch_position(rpc, &spl_stack_account(user, mint).0)
+}
+
+/** 读取用户初始版本的 spl 仓位（version 为0） */
+pub fn fetch_legacy_position(rpc: &RpcClient, user: &Pubkey) -> Result<StackAccount> {
+    fetch_position(rpc, &legacy_stack_account(user).0)
+}
+
+/** 读取全局配置 */
+pub fn fetch_config(rpc: &RpcClient) -> Result<Config> {
+    fetch_account(rpc, &config().0)
+}
+
+/** 读取多签账户 */
+pub fn fetch_multisig(rpc: &RpcClient) -> Result<Multisig> {
+    fetch_account(rpc, &multisig().0)
+}
+
+/** 读取提案 */
+pub fn fetch_proposal(rpc: &RpcClient, index: u64) -> Result<Proposal> {
+    fetch_account(rpc, &proposal(index).0)
+}
+
+/** 读取mint配置 */
+pub fn fetch_mint_config(rpc: &RpcClient, mint: &Pubkey) -> Result<MintConfig> {
+    fetch_account(rpc, &mint_config(mint).0)
+}
+
+/** 读取奖励池 */
+pub fn fetch_reward_pool(rpc: &RpcClient, stake_mint: &Pubkey) -> Result<RewardPool> {
+    fetch_account(rpc, &reward_pool(stake_mint).0)
+}
+
+/** 读取份额金库 */
+pub fn fetch_share_vault(rpc: &RpcClient, mint: &Pubkey) -> Result<ShareVault> {
+    fetch_account(rpc, &share_vault(mint).0)
+}
+
+/** 读取份额金库和金库ATA，按与 exchange_rate 指令相同的规则计算兑换比例 */
+pub fn fetch_exchange_rate(rpc: &RpcClient, mint: &Pubkey, token_program: &Pubkey) -> Result<ExchangeRate> {
+    let vault = fetch_share_vault(rpc, mint)?;
+    let total_assets = fetch_account::<TokenAccount>(rpc, &share_vault_ata(mint, token_program))?.amount;
+    let assets_per_share =
+        vault.assets_per_share(total_assets).map_err(|_| ClientError::ExchangeRateOverflow(share_vault(mint).0))?;
+    Ok(ExchangeRate { total_assets, total_shares: vault.total_shares, assets_per_share })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn sol_and_spl_positions_use_distinct_vaults() {
+        let user = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        assert_ne!(sol_vault(&user).0, spl_vault_authority(&user).0);
+        assert_ne!(sol_stack_account(&user).0, spl_stack_account(&user, &mint).0);
+    }
+
+    #[test]
+    fn deposit_spl_builds_expected_instruction() {
+        let user = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let ix = deposit_spl(&user, &mint, 42, &crate::TOKEN_PROGRAM_ID);
+
+        assert_eq!(ix.program_id, PROGRAM_ID);
+        assert_eq!(&ix.data[..8], instruction::DepositSpl::DISCRIMINATOR);
+        assert_eq!(ix.data[8..], 42u64.to_le_bytes());
+
+        // 只有用户签名
+        let signers: Vec<Pubkey> = ix.accounts.iter().filter(|meta| meta.is_signer).map(|meta| meta.pubkey).collect();
+        assert_eq!(signers, vec![user]);
+        assert_eq!(ix.accounts[6].pubkey, config().0);
+        assert_eq!(ix.accounts[7].pubkey, ata(&user, &mint, &crate::TOKEN_PROGRAM_ID));
+        assert_eq!(ix.accounts[8].pubkey, spl_vault(&user, &mint, &crate::TOKEN_PROGRAM_ID));
+        assert_eq!(ix.accounts[10].pubkey, treasury_ata(&mint, &crate::TOKEN_PROGRAM_ID));
+    }
+
+    #[test]
+    fn execute_proposal_passes_target_accounts_without_signers() {
+        let member = Pubkey::new_unique();
+        let target = set_paused(&multisig_signer().0, true);
+        let create = create_proposal(&member, 3, &target);
+        assert_eq!(create.accounts[2].pubkey, proposal(3).0);
+
+        let proposal = Proposal {
+            proposer: member,
+            index: 3,
+            program_id: target.program_id,
+            accounts: target
+                .accounts
+                .iter()
+                .map(|meta| ProposalAccount { pubkey: meta.pubkey, is_signer: meta.is_signer, is_writable: meta.is_writable })
+                .collect(),
+            data: target.data.clone(),
+            approvals: vec![true],
+            signer_set_seqno: 0,
+            executed: false,
+            bump: 0,
+        };
+        let ix = execute_proposal(&proposal);
+
+        // multisig、proposal、multisig_signer，然后是 config 和目标程序
+        assert!(ix.accounts.iter().all(|meta| !meta.is_signer));
+        assert_eq!(ix.accounts.len(), 5);
+        assert_eq!(ix.accounts[2].pubkey, multisig_signer().0);
+        assert_eq!(ix.accounts[3], AccountMeta::new(config().0, false));
+        assert_eq!(ix.accounts[4], AccountMeta::new_readonly(PROGRAM_ID, false));
+    }
+
+    #[test]
+    fn native_instructions_use_wsol_position() {
+        let user = Pubkey::new_unique();
+        let deposit = deposit_spl_native(&user, 5);
+        let withdraw = withdraw_spl_native(&user);
+
+        // 与 deposit_spl 质押 WSOL 使用同一个仓位和质押ATA
+        let spl = deposit_spl(&user, &NATIVE_MINT, 5, &crate::TOKEN_PROGRAM_ID);
+        assert_eq!(deposit.accounts[2].pubkey, spl.accounts[2].pubkey);
+        assert_eq!(deposit.accounts[7].pubkey, spl.accounts[8].pubkey);
+        assert_eq!(withdraw.accounts[7].pubkey, unwrap_account(&user).0);
+        assert!(withdraw.accounts[7].is_writable);
+    }
+
+    #[test]
+    fn share_vault_instructions_use_receipt_ata() {
+        let user = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let receipt = receipt_mint(&mint).0;
+        let deposit = deposit_shares(&user, &mint, 42, &crate::TOKEN_2022_PROGRAM_ID);
+        let withdraw = withdraw_shares(&user, &mint, 7, &crate::TOKEN_2022_PROGRAM_ID);
+
+        assert_eq!(&deposit.data[..8], instruction::DepositShares::DISCRIMINATOR);
+        assert_eq!(deposit.accounts[5].pubkey, receipt);
+        assert_eq!(deposit.accounts[7].pubkey, ata(&user, &receipt, &crate::TOKEN_2022_PROGRAM_ID));
+        assert_eq!(deposit.accounts[8].pubkey, share_vault_ata(&mint, &crate::TOKEN_2022_PROGRAM_ID));
+        assert_eq!(&withdraw.data[..8], instruction::WithdrawShares::DISCRIMINATOR);
+        assert_eq!(withdraw.data[8..], 7u64.to_le_bytes());
+        assert_eq!(withdraw.accounts[5].pubkey, ata(&user, &receipt, &crate::TOKEN_2022_PROGRAM_ID));
+    }
+}
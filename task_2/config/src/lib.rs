@@ -0,0 +1,125 @@
+//! task_1 和 task_2 共用的全局配置
+//!
+//! 手续费计算在这里实现一次；Config 账户和 ConfigParams 由 [`config_account!`] 在程序 crate 中展开，
+//! 这样账户的 owner / discriminator、错误码和事件仍属于各自的程序。
+
+// 手续费上限：100%
+pub const MAX_FEE_BPS: u16 = 10_000;
+
+// 按万分比计算手续费，向上取整（拆分成多笔小额操作不能绕过手续费），结果不超过 amount；溢出时返回 None
+pub fn fee_amount(amount: u64, fee_bps: u16) -> Option<u64> {
+    let fee = (amount as u128)
+        .checked_mul(fee_bps as u128)?
+        .checked_add(MAX_FEE_BPS as u128 - 1)?
+        / MAX_FEE_BPS as u128;
+    u64::try_from(fee).ok()
+}
+
+/** 在程序 crate 中定义全局配置账户 `Config` 和参数 `ConfigParams`
+    $error 为程序的错误枚举，需要 MathOverflow、ProgramPaused、DepositsDisabled、InvalidFee；
+    $event 为程序的配置变更事件，字段与 Config 相同（不含 bump）并带 slot
+*/
+#[macro_export]
+macro_rules! config_account {
+    ($error:ident, $event:ident) => {
+        /** 配置参数 */
+        #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+        pub struct ConfigParams {
+            pub emergency: bool,       // 应急模式：允许提现，禁止充值和铸币
+            pub deposit_fee_bps: u16,  // 充值手续费（万分比）
+            pub withdraw_fee_bps: u16, // 提现手续费（万分比）
+        }
+
+        impl ConfigParams {
+            fn validate(&self) -> Result<()> {
+                require!(
+                    self.deposit_fee_bps <= $crate::MAX_FEE_BPS && self.withdraw_fee_bps <= $crate::MAX_FEE_BPS,
+                    $error::InvalidFee
+                );
+                Ok(())
+            }
+        }
+
+        /** 全局配置账户结构体 */
+        #[account]
+        #[derive(InitSpace, Default)]
+        pub struct Config {
+            pub admin: Pubkey,         // 管理员
+            pub pending_admin: Pubkey, // 被提名的新管理员，默认地址表示没有
+            pub paused: bool,          // 暂停：禁止充值、铸币和提现
+            pub emergency: bool,       // 应急模式：允许提现，禁止充值和铸币
+            pub deposit_fee_bps: u16,  // 充值手续费（万分比）
+            pub withdraw_fee_bps: u16, // 提现手续费（万分比）
+            pub bump: u8,
+        }
+
+        impl Config {
+            // 读取提现路径上的配置账户（调用方用 seeds 约束地址）；initialize_config 之前按默认配置处理，
+            // 即未暂停、不收手续费，程序升级后用户不必等管理员初始化配置就能提现
+            pub fn load_or_default(info: &AccountInfo) -> Result<Self> {
+                if info.owner != &<Self as anchor_lang::Owner>::owner() || info.data_is_empty() {
+                    return Ok(Self::default());
+                }
+                Self::try_deserialize(&mut &info.try_borrow_data()?[..])
+            }
+
+            fn apply(&mut self, params: &ConfigParams) {
+                self.emergency = params.emergency;
+                self.deposit_fee_bps = params.deposit_fee_bps;
+                self.withdraw_fee_bps = params.withdraw_fee_bps;
+            }
+
+            fn emit_updated(&self) -> Result<()> {
+                emit!($event {
+                    admin: self.admin,
+                    pending_admin: self.pending_admin,
+                    paused: self.paused,
+                    emergency: self.emergency,
+                    deposit_fee_bps: self.deposit_fee_bps,
+                    withdraw_fee_bps: self.withdraw_fee_bps,
+                    slot: Clock::get()?.slot,
+                });
+                Ok(())
+            }
+
+            // 充值和铸币：暂停和应急模式下都不可用
+            pub fn require_deposits_allowed(&self) -> Result<()> {
+                require!(!self.paused, $error::ProgramPaused);
+                require!(!self.emergency, $error::DepositsDisabled);
+                Ok(())
+            }
+
+            // 充值手续费，从 amount 中扣除
+            pub fn deposit_fee(&self, amount: u64) -> Result<u64> {
+                $crate::fee_amount(amount, self.deposit_fee_bps).ok_or_else(|| error!($error::MathOverflow))
+            }
+
+            // 提现手续费，从提取数量中扣除
+            pub fn withdraw_fee(&self, amount: u64) -> Result<u64> {
+                $crate::fee_amount(amount, self.withdraw_fee_bps).ok_or_else(|| error!($error::MathOverflow))
+            }
+
+            // 提现和领取奖励：只在暂停时不可用
+            pub fn require_withdrawals_allowed(&self) -> Result<()> {
+                require!(!self.paused, $error::ProgramPaused);
+                Ok(())
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fee_rounds_up_and_never_exceeds_amount() {
+        assert_eq!(fee_amount(10_000, 30), Some(30));
+        assert_eq!(fee_amount(10_001, 30), Some(31));
+        assert_eq!(fee_amount(1, 1), Some(1));
+        assert_eq!(fee_amount(0, 30), Some(0));
+        assert_eq!(fee_amount(12_345, 0), Some(0));
+        assert_eq!(fee_amount(u64::MAX, MAX_FEE_BPS), Some(u64::MAX));
+        assert_eq!(fee_amount(u64::MAX, MAX_FEE_BPS - 1), Some(u64::MAX - u64::MAX / 10_000));
+    }
+}
@@ -0,0 +1,220 @@
+use anchor_lang::prelude::*;
+
+/** SOL 充值事件 */
+#[event]
+pub struct DepositEvent {
+    pub user: Pubkey,
+    pub amount: u64,   // 本次充值 lamports（含手续费）
+    pub fee: u64,      // 转入金库的手续费
+    pub balance: u64,  // 充值后的质押余额
+    pub lock_end: i64, // 锁定结束时间，0表示未锁定
+    pub slot: u64,
+}
+
+/** SOL 提现事件 */
+#[event]
+pub struct WithdrawEvent {
+    pub user: Pubkey,
+    pub amount: u64,  // 本次从仓位扣除的 lamports（含手续费）
+    pub fee: u64,     // 转入金库的手续费
+    pub balance: u64, // 提现后的质押余额
+    pub slot: u64,
+}
+
+/** 关闭 SOL 仓位事件 */
+#[event]
+pub struct ClosePositionEvent {
+    pub user: Pubkey,
+    pub refunded: u64, // 质押账户退回的租金
+    pub slot: u64,
+}
+
+/** 旧布局仓位迁移事件 */
+#[event]
+pub struct PositionMigratedEvent {
+    pub user: Pubkey,
+    pub position: Pubkey,     // 质押数据账户
+    pub mint: Option<Pubkey>, // spl 仓位的代币，SOL 仓位为 None
+    pub version: u8,          // 迁移后的布局版本
+    pub rent_paid: u64,       // 用户补交的租金
+    pub slot: u64,
+}
+
+/** spl 充值事件 */
+#[event]
+pub struct DepositSplEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,   // 用户转出数量（含手续费）
+    pub fee: u64,      // 转入金库的手续费
+    pub received: u64, // 扣除手续费和转账手续费后实际到账数量
+    pub balance: u64,  // 充值后的质押余额
+    pub lock_end: i64, // 锁定结束时间，0表示未锁定
+    pub slot: u64,
+}
+
+/** spl 提现事件 */
+#[event]
+pub struct WithdrawSplEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,  // 本次从仓位扣除的数量（含手续费）
+    pub fee: u64,     // 转入金库的手续费
+    pub balance: u64, // 提现后的质押余额
+    pub slot: u64,
+}
+
+/** 关闭 spl 仓位事件 */
+#[event]
+pub struct ClosePositionSplEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub refunded: u64, // 退回的租金 lamports
+    pub slot: u64,
+}
+
+/** 提取手续费事件 */
+#[event]
+pub struct FeesCollectedEvent {
+    pub mint: Pubkey,      // 代币，默认地址表示 SOL
+    pub recipient: Pubkey, // 接收手续费的账户
+    pub amount: u64,
+    pub slot: u64,
+}
+
+/** 份额金库充值事件 */
+#[event]
+pub struct DepositSharesEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,       // 用户转出数量（含手续费）
+    pub fee: u64,          // 转入金库的手续费
+    pub received: u64,     // 实际存入份额金库的数量
+    pub shares: u64,       // 铸造给用户的份额
+    pub total_assets: u64, // 充值后的金库资产
+    pub total_shares: u64, // 充值后的已发行份额
+    pub slot: u64,
+}
+
+/** 份额金库提现事件 */
+#[event]
+pub struct WithdrawSharesEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub shares: u64,       // 销毁的份额
+    pub amount: u64,       // 份额对应的资产（含手续费）
+    pub fee: u64,          // 转入金库的手续费
+    pub total_assets: u64, // 提现后的金库资产
+    pub total_shares: u64, // 提现后的已发行份额
+    pub slot: u64,
+}
+
+/** 创建代币事件 */
+#[event]
+pub struct CreateTokenEvent {
+    pub creator: Pubkey, // 创建者，同时是铸币权限账户
+    pub mint: Pubkey,
+    pub symbol: String,
+    pub decimals: u8,
+    pub max_supply: u64,     // 总供应量上限
+    pub epoch_emission: u64, // 每个 epoch 的铸币上限，0表示不限
+    pub slot: u64,
+}
+
+/** 元数据修改事件，锁定元数据时 name、symbol、uri 为空 */
+#[event]
+pub struct MetadataUpdatedEvent {
+    pub mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub is_mutable: bool, // false 表示元数据已永久锁定
+    pub slot: u64,
+}
+
+/** 代币账户冻结/解冻事件 */
+#[event]
+pub struct TokenAccountFreezeEvent {
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub owner: Pubkey,  // 代币账户持有人
+    pub frozen: bool,   // true 为冻结，false 为解冻
+    pub slot: u64,
+}
+
+/** 铸币事件 */
+#[event]
+pub struct MintSplEvent {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub supply: u64, // 铸币后的总供应量
+    pub slot: u64,
+}
+
+/** 铸币权限移交事件 */
+#[event]
+pub struct MintAuthorityEvent {
+    pub mint: Pubkey,
+    pub authority: Pubkey,         // 当前铸币权限账户
+    pub pending_authority: Pubkey, // 被提名的铸币权限账户，默认地址表示没有
+    pub slot: u64,
+}
+
+/** 销毁事件 */
+#[event]
+pub struct BurnEvent {
+    pub authority: Pubkey,     // 持有人，或管理员（from_vault 为 true 时）
+    pub mint: Pubkey,
+    pub token_account: Pubkey, // 被销毁代币的账户
+    pub amount: u64,
+    pub from_vault: bool,      // true 表示管理员从质押PDA的ATA中销毁
+    pub supply: u64,           // 销毁后的总供应量
+    pub burned: u64,           // 经本程序累计销毁的数量
+    pub slot: u64,
+}
+
+/** 永久撤销铸币权限事件 */
+#[event]
+pub struct MintRevokedEvent {
+    pub mint: Pubkey,
+    pub authority: Pubkey, // 执行撤销的铸币权限账户
+    pub supply: u64,       // 撤销时的总供应量，之后不再变化
+    pub slot: u64,
+}
+
+/** 领取奖励事件 */
+#[event]
+pub struct ClaimRewardsEvent {
+    pub user: Pubkey,
+    pub stake_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,  // 本次领取数量
+    pub pending: u64, // 剩余待领取数量
+    pub slot: u64,
+}
+
+/** 全局配置变更事件 */
+#[event]
+pub struct ConfigUpdatedEvent {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey, // 被提名的新管理员，默认地址表示没有
+    pub paused: bool,
+    pub emergency: bool,
+    pub deposit_fee_bps: u16,
+    pub withdraw_fee_bps: u16,
+    pub slot: u64,
+}
+
+/** 多签提案事件，创建、批准和执行时触发 */
+#[event]
+pub struct ProposalEvent {
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub program_id: Pubkey, // 提案指令的目标程序
+    pub approvals: u8,      // 当前批准数
+    pub executed: bool,
+    pub slot: u64,
+}
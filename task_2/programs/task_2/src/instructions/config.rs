@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+
+use crate::events::ConfigUpdatedEvent;
+use crate::instructions::stack::CustomError;
+use crate::program::Task2;
+
+// 初始化全局配置（仅程序升级权限账户可操作，只能执行一次），签名者成为管理员
+pub fn initialize_config(ctx: Context<InitializeConfig>, params: ConfigParams) -> Result<()> {
+    params.validate()?;
+
+    let config = &mut ctx.accounts.config;
+    config.admin = ctx.accounts.authority.key();
+    config.pending_admin = Pubkey::default();
+    config.paused = false;
+    config.apply(&params);
+    config.bump = ctx.bumps.config;
+
+    msg!("Config initialized, admin {}", config.admin);
+    config.emit_updated()
+}
+
+// 暂停或恢复全部充值、铸币和提现（仅管理员可操作）
+pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.paused = paused;
+
+    msg!("Program paused: {}", paused);
+    config.emit_updated()
+}
+
+// 修改应急模式和手续费参数（仅管理员可操作）
+pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+    params.validate()?;
+
+    let config = &mut ctx.accounts.config;
+    config.apply(&params);
+
+    msg!(
+        "Config updated: emergency {} deposit fee {} bps withdraw fee {} bps",
+        config.emergency,
+        config.deposit_fee_bps,
+        config.withdraw_fee_bps
+    );
+    config.emit_updated()
+}
+
+// 提名新管理员（仅管理员可操作），新管理员调用 accept_admin 后生效；提名默认地址表示取消
+pub fn propose_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.pending_admin = new_admin;
+
+    msg!("Admin {} proposed {} as new admin", config.admin, new_admin);
+    config.emit_updated()
+}
+
+// 被提名的管理员接受管理权限
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    msg!("Admin changed from {} to {}", config.admin, config.pending_admin);
+    config.admin = config.pending_admin;
+    config.pending_admin = Pubkey::default();
+
+    config.emit_updated()
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut, signer)]
+    pub authority: Signer<'info>, // 程序升级权限账户
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"config"],
+        bump,
+        space = 8 + Config::INIT_SPACE,
+    )]
+    pub config: Account<'info, Config>, // 全局配置账户
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, Task2>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ CustomError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>, // 配置管理员
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub pending_admin: Signer<'info>, // 被提名的管理员
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.pending_admin == pending_admin.key() @ CustomError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+// Config 账户、ConfigParams 和手续费计算与 task_1 共用
+stake_config::config_account!(CustomError, ConfigUpdatedEvent);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stake_config::MAX_FEE_BPS;
+
+    fn config(paused: bool, emergency: bool) -> Config {
+        Config {
+            admin: Pubkey::default(),
+            pending_admin: Pubkey::default(),
+            paused,
+            emergency,
+            deposit_fee_bps: 0,
+            withdraw_fee_bps: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn emergency_blocks_deposits_but_not_withdrawals() {
+        let config = config(false, true);
+        assert_eq!(config.require_deposits_allowed().unwrap_err(), CustomError::DepositsDisabled.into());
+        assert!(config.require_withdrawals_allowed().is_ok());
+    }
+
+    #[test]
+    fn pause_blocks_everything() {
+        for emergency in [false, true] {
+            let config = config(true, emergency);
+            assert_eq!(config.require_deposits_allowed().unwrap_err(), CustomError::ProgramPaused.into());
+            assert_eq!(config.require_withdrawals_allowed().unwrap_err(), CustomError::ProgramPaused.into());
+        }
+    }
+
+    #[test]
+    fn missing_config_is_treated_as_unpaused() {
+        let key = Pubkey::new_unique();
+        let owner = anchor_lang::system_program::ID;
+        let mut lamports = 0;
+        let mut data = [];
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        let config = Config::load_or_default(&info).unwrap();
+        assert!(config.require_withdrawals_allowed().is_ok());
+        assert_eq!(config.withdraw_fee(10_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn initialized_config_is_loaded() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = Vec::new();
+        config(true, false).try_serialize(&mut data).unwrap();
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &crate::ID, false, 0);
+        let config = Config::load_or_default(&info).unwrap();
+        assert_eq!(config.require_withdrawals_allowed().unwrap_err(), CustomError::ProgramPaused.into());
+    }
+
+    #[test]
+    fn fee_above_100_percent_is_rejected() {
+        let params = ConfigParams { emergency: false, deposit_fee_bps: MAX_FEE_BPS + 1, withdraw_fee_bps: 0 };
+        assert_eq!(params.validate().unwrap_err(), CustomError::InvalidFee.into());
+        let params = ConfigParams { emergency: false, deposit_fee_bps: 0, withdraw_fee_bps: MAX_FEE_BPS };
+        assert!(params.validate().is_ok());
+    }
+}
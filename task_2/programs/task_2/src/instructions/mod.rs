@@ -1,2 +1,8 @@
+pub mod config;
+pub mod multisig;
+pub mod reward;
 pub mod spl;
-pub mod stack;
\ No newline at end of file
+pub mod stack;
+pub mod treasury;
+pub mod vault;
+pub mod whitelist;
@@ -0,0 +1,380 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+use crate::events::ProposalEvent;
+use crate::instructions::config::Config;
+use crate::instructions::stack::CustomError;
+
+// 多签（M-of-N）
+// 多签签名PDA [b"multisig_signer"] 可以被设置为全局配置管理员（包括 task_1）或 mint 的铸币权限账户
+// 成员提交提案（任意一条指令），批准数达到门限后任何人都可以执行，执行时由多签签名PDA签名 CPI 调用该指令
+
+// 多签成员数量上限
+pub const MAX_MULTISIG_SIGNERS: usize = 10;
+// 提案指令的账户数量上限
+pub const MAX_PROPOSAL_ACCOUNTS: usize = 16;
+// 提案指令数据的长度上限
+pub const MAX_PROPOSAL_DATA: usize = 256;
+
+// 创建多签（仅配置管理员可操作，只能执行一次）
+pub fn create_multisig(ctx: Context<CreateMultisig>, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+    validate_signers(&signers, threshold)?;
+
+    let multisig = &mut ctx.accounts.multisig;
+    multisig.signers = signers;
+    multisig.threshold = threshold;
+    multisig.signer_set_seqno = 0;
+    multisig.proposal_count = 0;
+    multisig.signer_bump = ctx.bumps.multisig_signer;
+    multisig.bump = ctx.bumps.multisig;
+
+    msg!(
+        "Multisig created: {} of {}, signer {}",
+        threshold,
+        multisig.signers.len(),
+        ctx.accounts.multisig_signer.key()
+    );
+    Ok(())
+}
+
+// 修改多签成员和门限，只能由多签签名PDA通过提案调用；未执行的旧提案随之失效
+pub fn set_multisig_signers(ctx: Context<SetMultisigSigners>, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+    validate_signers(&signers, threshold)?;
+
+    let multisig = &mut ctx.accounts.multisig;
+    multisig.signers = signers;
+    multisig.threshold = threshold;
+    multisig.signer_set_seqno = multisig.signer_set_seqno.checked_add(1).ok_or(CustomError::MathOverflow)?;
+
+    msg!("Multisig signers changed: {} of {}", threshold, multisig.signers.len());
+    Ok(())
+}
+
+// 多签成员提交提案，提交者自动批准
+pub fn create_proposal(ctx: Context<CreateProposal>, instruction: ProposalInstruction) -> Result<()> {
+    require!(
+        instruction.accounts.len() <= MAX_PROPOSAL_ACCOUNTS && instruction.data.len() <= MAX_PROPOSAL_DATA,
+        CustomError::ProposalTooLarge
+    );
+
+    let multisig = &mut ctx.accounts.multisig;
+    let signer_index = multisig.signer_index(&ctx.accounts.proposer.key())?;
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.proposer = ctx.accounts.proposer.key();
+    proposal.index = multisig.proposal_count;
+    proposal.program_id = instruction.program_id;
+    proposal.accounts = instruction.accounts;
+    proposal.data = instruction.data;
+    proposal.approvals = vec![false; multisig.signers.len()];
+    proposal.approvals[signer_index] = true;
+    proposal.signer_set_seqno = multisig.signer_set_seqno;
+    proposal.executed = false;
+    proposal.bump = ctx.bumps.proposal;
+
+    multisig.proposal_count = multisig.proposal_count.checked_add(1).ok_or(CustomError::MathOverflow)?;
+
+    msg!("Proposal {} created by {}", proposal.index, proposal.proposer);
+    emit_proposal(proposal)
+}
+
+// 多签成员批准提案
+pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+    let signer_index = ctx.accounts.multisig.signer_index(&ctx.accounts.signer.key())?;
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.approvals[signer_index] = true;
+
+    msg!("Proposal {} approved by {}", proposal.index, ctx.accounts.signer.key());
+    emit_proposal(proposal)
+}
+
+// 执行已达到门限的提案，remaining_accounts 需要包含提案指令用到的全部账户和目标程序
+pub fn execute_proposal<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>) -> Result<()> {
+    let multisig = &ctx.accounts.multisig;
+    let proposal = &mut ctx.accounts.proposal;
+    require!(proposal.approval_count() >= multisig.threshold as usize, CustomError::NotEnoughApprovals);
+
+    // 先写回执行状态，CPI 重入时不能重复执行
+    proposal.executed = true;
+    proposal.exit(ctx.program_id)?;
+
+    let mut account_infos = ctx.remaining_accounts.to_vec();
+    account_infos.push(ctx.accounts.multisig_signer.to_account_info());
+    let seeds: &[&[u8]] = &[b"multisig_signer", &[multisig.signer_bump]];
+    invoke_signed(&proposal.to_instruction(), &account_infos, &[seeds])?;
+
+    msg!("Proposal {} executed", proposal.index);
+    emit_proposal(proposal)
+}
+
+// 成员不能为空、不能重复，门限在 1 和成员数量之间
+fn validate_signers(signers: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        !signers.is_empty() && signers.len() <= MAX_MULTISIG_SIGNERS,
+        CustomError::InvalidMultisig
+    );
+    require!(threshold > 0 && threshold as usize <= signers.len(), CustomError::InvalidMultisig);
+    for (i, signer) in signers.iter().enumerate() {
+        require!(!signers[..i].contains(signer), CustomError::InvalidMultisig);
+    }
+    Ok(())
+}
+
+fn emit_proposal(proposal: &Proposal) -> Result<()> {
+    emit!(ProposalEvent {
+        index: proposal.index,
+        proposer: proposal.proposer,
+        program_id: proposal.program_id,
+        approvals: proposal.approval_count() as u8,
+        executed: proposal.executed,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 提案指令参数
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct ProposalInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<ProposalAccount>,
+    pub data: Vec<u8>,
+}
+
+// 提案指令的账户
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq, InitSpace)]
+pub struct ProposalAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    #[account(mut, signer)]
+    pub admin: Signer<'info>, // 配置管理员
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"multisig"],
+        bump,
+        space = 8 + Multisig::INIT_SPACE,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    /// CHECK: PDA without data, only used to sign executed proposals.
+    #[account(seeds = [b"multisig_signer"], bump)]
+    pub multisig_signer: UncheckedAccount<'info>, // 多签签名PDA
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMultisigSigners<'info> {
+    #[account(seeds = [b"multisig_signer"], bump = multisig.signer_bump)]
+    pub multisig_signer: Signer<'info>, // 只能通过执行提案签名
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut, signer)]
+    pub proposer: Signer<'info>, // 多签成员，支付提案账户租金
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        init,
+        payer = proposer,
+        seeds = [b"proposal", multisig.proposal_count.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Proposal::INIT_SPACE,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    pub signer: Signer<'info>, // 多签成员
+
+    #[account(seeds = [b"multisig"], bump = multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        constraint = !proposal.executed @ CustomError::ProposalExecuted,
+        constraint = proposal.signer_set_seqno == multisig.signer_set_seqno @ CustomError::StaleProposal, // 成员变更后旧提案失效
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(seeds = [b"multisig"], bump = multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        constraint = !proposal.executed @ CustomError::ProposalExecuted,
+        constraint = proposal.signer_set_seqno == multisig.signer_set_seqno @ CustomError::StaleProposal,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: PDA without data, signs the proposal instruction via invoke_signed.
+    #[account(mut, seeds = [b"multisig_signer"], bump = multisig.signer_bump)]
+    pub multisig_signer: UncheckedAccount<'info>, // 多签签名PDA，可以持有 SOL 支付租金
+}
+
+/** 多签账户结构体 */
+#[account]
+#[derive(InitSpace)]
+pub struct Multisig {
+    #[max_len(MAX_MULTISIG_SIGNERS)]
+    pub signers: Vec<Pubkey>, // 多签成员
+    pub threshold: u8,         // 执行提案需要的批准数
+    pub signer_set_seqno: u32, // 成员变更次数，用于使旧提案失效
+    pub proposal_count: u64,   // 下一个提案的序号
+    pub signer_bump: u8,       // 多签签名PDA的bump
+    pub bump: u8,
+}
+
+impl Multisig {
+    // 成员在列表中的位置，不是成员时报错
+    pub fn signer_index(&self, signer: &Pubkey) -> Result<usize> {
+        self.signers
+            .iter()
+            .position(|s| s == signer)
+            .ok_or_else(|| error!(CustomError::NotMultisigSigner))
+    }
+}
+
+/** 提案账户结构体 */
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub proposer: Pubkey,
+    pub index: u64,         // 提案序号 [b"proposal", index]
+    pub program_id: Pubkey, // 提案指令的目标程序
+    #[max_len(MAX_PROPOSAL_ACCOUNTS)]
+    pub accounts: Vec<ProposalAccount>,
+    #[max_len(MAX_PROPOSAL_DATA)]
+    pub data: Vec<u8>,
+    #[max_len(MAX_MULTISIG_SIGNERS)]
+    pub approvals: Vec<bool>, // 与多签成员一一对应
+    pub signer_set_seqno: u32, // 提交时的成员变更次数
+    pub executed: bool,
+    pub bump: u8,
+}
+
+impl Proposal {
+    pub fn approval_count(&self) -> usize {
+        self.approvals.iter().filter(|approved| **approved).count()
+    }
+
+    pub fn to_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: self
+                .accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signer_set_is_validated() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        assert!(validate_signers(&[a, b], 2).is_ok());
+        assert_eq!(validate_signers(&[a, b], 0).unwrap_err(), CustomError::InvalidMultisig.into());
+        assert_eq!(validate_signers(&[a, b], 3).unwrap_err(), CustomError::InvalidMultisig.into());
+        assert_eq!(validate_signers(&[a, a], 1).unwrap_err(), CustomError::InvalidMultisig.into());
+        assert_eq!(validate_signers(&[], 0).unwrap_err(), CustomError::InvalidMultisig.into());
+        let too_many: Vec<Pubkey> = (0..=MAX_MULTISIG_SIGNERS).map(|_| Pubkey::new_unique()).collect();
+        assert_eq!(validate_signers(&too_many, 1).unwrap_err(), CustomError::InvalidMultisig.into());
+    }
+
+    #[test]
+    fn only_listed_signers_are_found() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let multisig = Multisig {
+            signers: vec![a, b],
+            threshold: 2,
+            signer_set_seqno: 0,
+            proposal_count: 0,
+            signer_bump: 0,
+            bump: 0,
+        };
+        assert_eq!(multisig.signer_index(&b).unwrap(), 1);
+        assert_eq!(
+            multisig.signer_index(&Pubkey::new_unique()).unwrap_err(),
+            CustomError::NotMultisigSigner.into()
+        );
+    }
+
+    #[test]
+    fn proposal_round_trips_its_instruction() {
+        let signer = Pubkey::new_unique();
+        let config = Pubkey::new_unique();
+        let proposal = Proposal {
+            proposer: Pubkey::new_unique(),
+            index: 0,
+            program_id: crate::ID,
+            accounts: vec![
+                ProposalAccount { pubkey: signer, is_signer: true, is_writable: false },
+                ProposalAccount { pubkey: config, is_signer: false, is_writable: true },
+            ],
+            data: vec![1, 2, 3],
+            approvals: vec![true, false, true],
+            signer_set_seqno: 0,
+            executed: false,
+            bump: 0,
+        };
+        assert_eq!(proposal.approval_count(), 2);
+
+        let ix = proposal.to_instruction();
+        assert_eq!(ix.program_id, crate::ID);
+        assert_eq!(ix.accounts, vec![AccountMeta::new_readonly(signer, true), AccountMeta::new(config, false)]);
+        assert_eq!(ix.data, vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,335 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken, // 处理关联代币账户的功能
+    token_interface::{
+        transfer_checked, // 带精度校验的转账（兼容 Token 与 Token-2022）
+        Mint,             // 代币铸造的结构体
+        TokenAccount,     // 代币账户的结构体
+        TokenInterface,   // Token / Token-2022 程序
+        TransferChecked,  // 转账指令的结构体
+    },
+};
+
+use crate::events::ClaimRewardsEvent;
+use crate::instructions::config::Config;
+use crate::instructions::stack::{CustomError, StackAccount};
+
+// 每份额累计奖励的精度
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+// 设置奖励代币和每秒发放数量（仅奖励池管理员可操作）
+pub fn configure_reward_pool(ctx: Context<ConfigureRewardPool>, reward_rate: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let reward_pool = &mut ctx.accounts.reward_pool;
+
+    // 修改发放速率前先按旧速率结算
+    reward_pool.update(now)?;
+    reward_pool.reward_mint = ctx.accounts.reward_mint.key();
+    reward_pool.reward_vault = ctx.accounts.reward_vault.key();
+    reward_pool.reward_rate = reward_rate;
+
+    msg!("Reward pool {} pays {} {} per second", reward_pool.key(), reward_rate, reward_pool.reward_mint);
+    Ok(())
+}
+
+// 管理员向奖励金库注资
+pub fn fund_reward_pool(ctx: Context<FundRewardPool>, amount: u64) -> Result<()> {
+    require!(amount > 0, CustomError::ZeroAmount);
+
+    transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.admin_ata.to_account_info(),
+                mint: ctx.accounts.reward_mint.to_account_info(),
+                to: ctx.accounts.reward_vault.to_account_info(),
+                authority: ctx.accounts.admin.to_account_info(),
+            },
+        ),
+        amount,
+        ctx.accounts.reward_mint.decimals,
+    )?;
+
+    msg!("Funded reward vault {} with {}", ctx.accounts.reward_vault.key(), amount);
+    Ok(())
+}
+
+// 用户领取累计奖励
+pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+    let config = Config::load_or_default(&ctx.accounts.config)?;
+    config.require_withdrawals_allowed()?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    let stack_account = &mut ctx.accounts.stack_account;
+
+    reward_pool.update(now)?;
+    stack_account.settle_rewards(reward_pool)?;
+
+    // 金库余额不足时先发放可用部分，剩余继续记账
+    let claim = stack_account.pending_rewards.min(ctx.accounts.reward_vault.amount);
+    require!(claim > 0, CustomError::NoRewardsToClaim);
+
+    // 交易签名seeds，由奖励池PDA签名
+    let stake_mint = reward_pool.stake_mint;
+    let seeds: &[&[u8]] = &[b"reward_pool", stake_mint.as_ref(), &[reward_pool.bump]];
+    let signer = &[seeds];
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.reward_vault.to_account_info(),
+                mint: ctx.accounts.reward_mint.to_account_info(),
+                to: ctx.accounts.user_reward_ata.to_account_info(),
+                authority: ctx.accounts.reward_pool.to_account_info(),
+            },
+            signer,
+        ),
+        claim,
+        ctx.accounts.reward_mint.decimals,
+    )?;
+
+    let stack_account = &mut ctx.accounts.stack_account;
+    stack_account.pending_rewards = stack_account.pending_rewards.checked_sub(claim).ok_or(CustomError::MathOverflow)?;
+    msg!("Claimed {} reward tokens, {} still pending", claim, ctx.accounts.stack_account.pending_rewards);
+    emit!(ClaimRewardsEvent {
+        user: ctx.accounts.payer.key(),
+        stake_mint: ctx.accounts.reward_pool.stake_mint,
+        reward_mint: ctx.accounts.reward_mint.key(),
+        amount: claim,
+        pending: ctx.accounts.stack_account.pending_rewards,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ConfigureRewardPool<'info> {
+    #[account(mut, signer)]
+    pub admin: Signer<'info>, // 奖励池管理员
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool", reward_pool.stake_mint.as_ref()],
+        bump = reward_pool.bump,
+        has_one = admin @ CustomError::Unauthorized,
+        // 奖励代币设置后不能更换
+        constraint = reward_pool.reward_mint == Pubkey::default() || reward_pool.reward_mint == reward_mint.key() @ CustomError::MintMismatch,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>, // 奖励代币
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        associated_token::mint = reward_mint,
+        associated_token::authority = reward_pool,
+        associated_token::token_program = token_program,
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>, // 奖励金库，归奖励池PDA所有
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct FundRewardPool<'info> {
+    #[account(mut, signer)]
+    pub admin: Signer<'info>, // 奖励池管理员
+
+    #[account(
+        seeds = [b"reward_pool", reward_pool.stake_mint.as_ref()],
+        bump = reward_pool.bump,
+        has_one = admin @ CustomError::Unauthorized,
+        has_one = reward_mint @ CustomError::MintMismatch,
+        has_one = reward_vault @ CustomError::VaultMismatch,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = admin,
+        token::token_program = token_program,
+    )]
+    pub admin_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut, signer)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool", reward_pool.stake_mint.as_ref()],
+        bump = reward_pool.bump,
+        has_one = reward_mint @ CustomError::MintMismatch,
+        has_one = reward_vault @ CustomError::VaultMismatch,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        mut,
+        seeds = [payer.key.as_ref(), reward_pool.stake_mint.as_ref()],
+        bump = stack_account.stack_account_pump, // 数据账户必须属于当前签名用户和代币
+    )]
+    pub stack_account: Account<'info, StackAccount>,
+
+    #[account(seeds = [b"config"], bump)]
+    /// CHECK: Loaded with Config::load_or_default, may not be initialized yet.
+    pub config: UncheckedAccount<'info>, // 全局配置，未初始化时视为未暂停、不收手续费
+
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = reward_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub user_reward_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/** 奖励池账户结构体，每种质押代币一个 */
+#[account]
+#[derive(InitSpace)]
+pub struct RewardPool {
+    pub admin: Pubkey,              // 奖励池管理员
+    pub stake_mint: Pubkey,         // 质押代币
+    pub reward_mint: Pubkey,        // 奖励代币，未设置时为默认值
+    pub reward_vault: Pubkey,       // 奖励金库
+    pub reward_rate: u64,           // 每秒发放的奖励数量
+    pub acc_reward_per_share: u128, // 每份质押累计奖励（乘以 REWARD_PRECISION）
+    pub last_update_ts: i64,        // 上次结算时间
+    pub total_staked: u64,          // 质押总量
+    pub bump: u8,
+}
+
+impl RewardPool {
+    // 将奖励累计到当前时间
+    pub fn update(&mut self, now: i64) -> Result<()> {
+        if now <= self.last_update_ts {
+            return Ok(());
+        }
+        if self.total_staked > 0 {
+            let elapsed = (now - self.last_update_ts) as u128;
+            let accrued = elapsed
+                .checked_mul(self.reward_rate as u128)
+                .and_then(|rewards| rewards.checked_mul(REWARD_PRECISION))
+                .ok_or(CustomError::MathOverflow)?
+                / self.total_staked as u128;
+            self.acc_reward_per_share =
+                self.acc_reward_per_share.checked_add(accrued).ok_or(CustomError::MathOverflow)?;
+        }
+        self.last_update_ts = now;
+        Ok(())
+    }
+}
+
+impl StackAccount {
+    // 按当前累计值结算仓位的待领取奖励，余额变动前调用
+    pub fn settle_rewards(&mut self, reward_pool: &RewardPool) -> Result<()> {
+        let accrued = self.accrued_rewards(reward_pool)?;
+        let earned = accrued
+            .checked_sub(self.reward_debt)
+            .and_then(|earned| u64::try_from(earned).ok())
+            .ok_or(CustomError::MathOverflow)?;
+        self.pending_rewards = self.pending_rewards.checked_add(earned).ok_or(CustomError::MathOverflow)?;
+        self.reward_debt = accrued;
+        Ok(())
+    }
+
+    // 余额变动后重置奖励债务
+    pub fn sync_reward_debt(&mut self, reward_pool: &RewardPool) -> Result<()> {
+        self.reward_debt = self.accrued_rewards(reward_pool)?;
+        Ok(())
+    }
+
+    // 按当前余额计算的累计奖励总量
+    fn accrued_rewards(&self, reward_pool: &RewardPool) -> Result<u128> {
+        let accrued = (self.balance as u128)
+            .checked_mul(reward_pool.acc_reward_per_share)
+            .ok_or(CustomError::MathOverflow)?;
+        Ok(accrued / REWARD_PRECISION)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::stack::STACK_ACCOUNT_VERSION;
+
+    fn pool(reward_rate: u64, total_staked: u64) -> RewardPool {
+        RewardPool {
+            admin: Pubkey::default(),
+            stake_mint: Pubkey::default(),
+            reward_mint: Pubkey::default(),
+            reward_vault: Pubkey::default(),
+            reward_rate,
+            acc_reward_per_share: 0,
+            last_update_ts: 0,
+            total_staked,
+            bump: 0,
+        }
+    }
+
+    fn position(balance: u64) -> StackAccount {
+        StackAccount {
+            balance,
+            stack_account_pump: 0,
+            stack_account_pda_pump: 0,
+            lock_start: 0,
+            lock_end: 0,
+            reward_debt: 0,
+            pending_rewards: 0,
+            version: STACK_ACCOUNT_VERSION,
+            reserved: [0; 64],
+        }
+    }
+
+    #[test]
+    fn rewards_accrue_pro_rata() {
+        let mut pool = pool(100, 4);
+        let mut position = position(1);
+        pool.update(10).unwrap();
+        position.settle_rewards(&pool).unwrap();
+        assert_eq!(position.pending_rewards, 250);
+    }
+
+    #[test]
+    fn accumulator_overflow_is_rejected() {
+        let mut pool = pool(u64::MAX, 1);
+        assert_eq!(pool.update(i64::MAX).unwrap_err(), CustomError::MathOverflow.into());
+    }
+
+    #[test]
+    fn pending_rewards_overflow_is_rejected() {
+        // 单份质押按 u64::MAX 速率累计 2 秒，待领取奖励超出 u64
+        let mut pool = pool(u64::MAX, 1);
+        let mut position = position(1);
+        pool.update(2).unwrap();
+        assert_eq!(position.settle_rewards(&pool).unwrap_err(), CustomError::MathOverflow.into());
+    }
+}
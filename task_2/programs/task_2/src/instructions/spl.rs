@@ -1,24 +1,68 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
 use anchor_spl::{
     associated_token::AssociatedToken, // 处理关联代币账户的功能
     metadata::{
-        create_metadata_accounts_v3,       // 创建元数据账户的功能
-        mpl_token_metadata::types::DataV2, // 元数据的结构体定义
-        CreateMetadataAccountsV3,          // 创建元数据账户的指令结构体
-        Metadata as Metaplex,              // Metadata
+        create_metadata_accounts_v3,                  // 创建元数据账户的功能
+        mpl_token_metadata::types::{Creator, DataV2}, // 元数据的结构体定义
+        update_metadata_accounts_v2,                  // 修改元数据的功能
+        CreateMetadataAccountsV3,                     // 创建元数据账户的指令结构体
+        Metadata as Metaplex,                         // Metadata
+        UpdateMetadataAccountsV2,                     // 修改元数据的指令结构体
     },
-    token::{
-        mint_to,      // 铸币功能
-        Mint,         // 代币铸造的结构体
-        MintTo,       // 铸币指令的结构体
-        Token,        // 代币的基本功能
-        TokenAccount, // 代币账户的结构体
+    token_interface::{
+        self,                                       // 冻结、解冻功能（与本模块指令同名，带路径调用）
+        burn,                                       // 销毁功能
+        mint_to,                                    // 铸币功能
+        set_authority,                              // 修改权限功能
+        spl_token_2022::instruction::AuthorityType, // 权限类型
+        Burn,                                       // 销毁指令的结构体
+        FreezeAccount,                              // 冻结指令的结构体
+        Mint,                                       // 代币铸造的结构体
+        MintTo,                                     // 铸币指令的结构体
+        SetAuthority,                               // 修改权限指令的结构体
+        ThawAccount,                                // 解冻指令的结构体
+        TokenAccount,                               // 代币账户的结构体
+        TokenInterface,                             // Token / Token-2022 程序
     },
 };
 
+use crate::events::{
+    BurnEvent, CreateTokenEvent, MetadataUpdatedEvent, MintAuthorityEvent, MintRevokedEvent, MintSplEvent,
+    TokenAccountFreezeEvent,
+};
+use crate::instructions::config::Config;
+use crate::instructions::stack::{CustomError, StackAccount};
+
+// Metaplex symbol 最大长度
+pub const MAX_SYMBOL_LENGTH: usize = 10;
+// 注册表可登记的mint数量上限
+pub const MAX_REGISTRY_MINTS: usize = 64;
+
 pub fn create_token(ctx: Context<CreateSpl>, metadata: InitTokenParams) -> Result<()> {
+    require!(metadata.max_supply > 0, CustomError::InvalidMaxSupply);
+
+    // 记录mint配置，mint_spl 根据 symbol 重新推导签名seeds
+    let mint_config = &mut ctx.accounts.mint_config;
+    mint_config.mint = ctx.accounts.mint.key();
+    mint_config.authority = ctx.accounts.payer.key(); // 创建者拥有铸币权限
+    mint_config.pending_authority = Pubkey::default();
+    mint_config.symbol = metadata.symbol.clone();
+    mint_config.mint_bump = ctx.bumps.mint;
+    mint_config.bump = ctx.bumps.mint_config;
+    // 供应量上限和每个 epoch 的铸币上限创建后不可修改
+    mint_config.max_supply = metadata.max_supply;
+    mint_config.epoch_emission = metadata.epoch_emission;
+    mint_config.emission_epoch = Clock::get()?.epoch;
+    mint_config.epoch_minted = 0;
+    mint_config.burned = 0;
+
+    // 登记到代币注册表，客户端可以枚举全部mint
+    let registry = &mut ctx.accounts.registry;
+    require!(registry.mints.len() < MAX_REGISTRY_MINTS, CustomError::RegistryFull);
+    registry.mints.push(ctx.accounts.mint.key());
 
     // Metadata 
+    let symbol = metadata.symbol.clone();
     let token_data: DataV2 = DataV2 {
         name: metadata.name,
         symbol: metadata.symbol,
@@ -31,7 +75,7 @@ pub fn create_token(ctx: Context<CreateSpl>, metadata: InitTokenParams) -> Resul
 
     // 签名
     let bump = ctx.bumps.mint;
-    let seeds = &[b"mint".as_ref(), &[bump]];
+    let seeds = &[b"mint".as_ref(), symbol.as_bytes(), &[bump]];
     let signer = [&seeds[..]];
 
     // ctx 构建Metadata账户
@@ -51,32 +95,317 @@ pub fn create_token(ctx: Context<CreateSpl>, metadata: InitTokenParams) -> Resul
 
     // tx 创建Metadata账户
     msg!("Creating token metadata...");
-    let _tx = create_metadata_accounts_v3(metadata_ctx, token_data, true, true, None)?;
+    create_metadata_accounts_v3(metadata_ctx, token_data, true, true, None)?;
 
-    msg!("SPL Token Mint Created: {}", ctx.accounts.mint.key());
+    msg!("SPL Token Mint Created: {} symbol {} decimals {}", ctx.accounts.mint.key(), symbol, metadata.decimals);
+    emit!(CreateTokenEvent {
+        creator: ctx.accounts.payer.key(),
+        mint: ctx.accounts.mint.key(),
+        symbol,
+        decimals: metadata.decimals,
+        max_supply: metadata.max_supply,
+        epoch_emission: metadata.epoch_emission,
+        slot: Clock::get()?.slot,
+    });
     Ok(())
 }
 
 pub fn mint_spl(ctx: Context<MintSpl>, amount: u64) -> Result<()> {
+    ctx.accounts.config.require_deposits_allowed()?;
+    require!(amount > 0, CustomError::ZeroAmount);
+    let supply = ctx.accounts.mint.supply;
+    ctx.accounts.mint_config.record_emission(supply, amount, Clock::get()?.epoch)?;
+
     // 签名
-    let bump = ctx.bumps.mint;
-    let seeds = &[b"mint".as_ref(), &[bump]];
+    let mint_config = &ctx.accounts.mint_config;
+    let seeds = &[b"mint".as_ref(), mint_config.symbol.as_bytes(), &[mint_config.mint_bump]];
     let signer = [&seeds[..]];
 
     let mint_to_ctx = CpiContext::new_with_signer(
         ctx.accounts.token_program.to_account_info(),
         MintTo {
             mint: ctx.accounts.mint.to_account_info(),
-            to: ctx.accounts.payer_ata.to_account_info(),
+            to: ctx.accounts.recipient_ata.to_account_info(),
             authority: ctx.accounts.mint.to_account_info(),
         },
         &signer,
     );
-    let _tx = mint_to(mint_to_ctx, amount)?;
-    msg!("Minted {} tokens to {}", amount, ctx.accounts.payer_ata.key());
+    mint_to(mint_to_ctx, amount)?;
+    msg!("Minted {} tokens to {}", amount, ctx.accounts.recipient_ata.key());
+
+    ctx.accounts.mint.reload()?;
+    emit!(MintSplEvent {
+        authority: ctx.accounts.payer.key(),
+        mint: ctx.accounts.mint.key(),
+        recipient: ctx.accounts.recipient.key(),
+        amount,
+        supply: ctx.accounts.mint.supply,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 提名新的铸币权限账户（仅当前铸币权限账户可操作），新账户调用 accept_mint_authority 后生效；提名默认地址表示取消
+pub fn propose_mint_authority(ctx: Context<ProposeMintAuthority>, new_authority: Pubkey) -> Result<()> {
+    let mint_config = &mut ctx.accounts.mint_config;
+    mint_config.pending_authority = new_authority;
+
+    msg!("Mint {} authority {} proposed {}", mint_config.mint, mint_config.authority, new_authority);
+    emit!(MintAuthorityEvent {
+        mint: mint_config.mint,
+        authority: mint_config.authority,
+        pending_authority: new_authority,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 被提名的账户接受铸币权限
+pub fn accept_mint_authority(ctx: Context<AcceptMintAuthority>) -> Result<()> {
+    let mint_config = &mut ctx.accounts.mint_config;
+    msg!("Mint {} authority changed from {} to {}", mint_config.mint, mint_config.authority, mint_config.pending_authority);
+    mint_config.authority = mint_config.pending_authority;
+    mint_config.pending_authority = Pubkey::default();
+
+    emit!(MintAuthorityEvent {
+        mint: mint_config.mint,
+        authority: mint_config.authority,
+        pending_authority: Pubkey::default(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 持有人销毁自己代币账户中的代币，总供应量随之减少
+pub fn burn_spl(ctx: Context<BurnSpl>, amount: u64) -> Result<()> {
+    require!(amount > 0, CustomError::ZeroAmount);
+    burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.mint.to_account_info(),
+                from: ctx.accounts.holder_ata.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let mint_config = &mut ctx.accounts.mint_config;
+    mint_config.burned = mint_config.burned.checked_add(amount).ok_or(CustomError::MathOverflow)?;
+    ctx.accounts.mint.reload()?;
+    msg!("Burned {} tokens from {}", amount, ctx.accounts.holder_ata.key());
+    emit!(BurnEvent {
+        authority: ctx.accounts.holder.key(),
+        mint: ctx.accounts.mint.key(),
+        token_account: ctx.accounts.holder_ata.key(),
+        amount,
+        from_vault: false,
+        supply: ctx.accounts.mint.supply,
+        burned: ctx.accounts.mint_config.burned,
+        slot: Clock::get()?.slot,
+    });
     Ok(())
 }
 
+// 管理员销毁用户质押PDA的ATA中不属于用户仓位的代币（例如直接转入的代币），由质押PDA签名
+// 不修改用户的质押账户，只能销毁超出仓位余额的部分，保证用户仍可全额提现
+pub fn burn_from_vault(ctx: Context<BurnFromVault>, amount: u64) -> Result<()> {
+    require!(amount > 0, CustomError::ZeroAmount);
+    let surplus = ctx.accounts.vault_ata.amount.saturating_sub(ctx.accounts.stack_account.balance);
+    require!(amount <= surplus, CustomError::BurnExceedsSurplus);
+
+    let user = ctx.accounts.user.key();
+    let bump = ctx.accounts.stack_account.stack_account_pda_pump;
+    let seeds: &[&[u8]] = &[b"stack", user.as_ref(), &[bump]];
+    burn(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.mint.to_account_info(),
+                from: ctx.accounts.vault_ata.to_account_info(),
+                authority: ctx.accounts.pda_stack_account.to_account_info(),
+            },
+            &[seeds],
+        ),
+        amount,
+    )?;
+
+    let mint_config = &mut ctx.accounts.mint_config;
+    mint_config.burned = mint_config.burned.checked_add(amount).ok_or(CustomError::MathOverflow)?;
+    ctx.accounts.mint.reload()?;
+    msg!("Burned {} tokens from vault {} of {}", amount, ctx.accounts.vault_ata.key(), user);
+    emit!(BurnEvent {
+        authority: ctx.accounts.admin.key(),
+        mint: ctx.accounts.mint.key(),
+        token_account: ctx.accounts.vault_ata.key(),
+        amount,
+        from_vault: true,
+        supply: ctx.accounts.mint.supply,
+        burned: ctx.accounts.mint_config.burned,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 永久撤销铸币权限（仅铸币权限账户可操作），mint authority 设为 None 后总供应量不再增加
+pub fn revoke_mint_authority(ctx: Context<RevokeMintAuthority>) -> Result<()> {
+    let mint_config = &mut ctx.accounts.mint_config;
+    let seeds = &[b"mint".as_ref(), mint_config.symbol.as_bytes(), &[mint_config.mint_bump]];
+    let signer = [&seeds[..]];
+    set_authority(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SetAuthority {
+                current_authority: ctx.accounts.mint.to_account_info(),
+                account_or_mint: ctx.accounts.mint.to_account_info(),
+            },
+            &signer,
+        ),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+    // 撤销后移交铸币权限没有意义，清除未完成的提名
+    mint_config.pending_authority = Pubkey::default();
+
+    msg!("Minting of {} revoked at supply {}", mint_config.mint, ctx.accounts.mint.supply);
+    emit!(MintRevokedEvent {
+        mint: mint_config.mint,
+        authority: ctx.accounts.authority.key(),
+        supply: ctx.accounts.mint.supply,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 修改代币元数据（仅管理员可操作），由mint PDA作为 update authority 签名
+// mint PDA 的seeds仍使用创建时的 symbol（MintConfig.symbol），修改元数据的 symbol 不影响签名
+pub fn update_token_metadata(ctx: Context<UpdateTokenMetadata>, params: UpdateTokenMetadataParams) -> Result<()> {
+    // 创作者由 Metaplex 校验（最多5个、分成合计100），未经创作者签名不能标记为已验证
+    let creators = if params.creators.is_empty() {
+        None
+    } else {
+        Some(
+            params
+                .creators
+                .iter()
+                .map(|creator| Creator { address: creator.address, verified: false, share: creator.share })
+                .collect(),
+        )
+    };
+    let data = DataV2 {
+        name: params.name.clone(),
+        symbol: params.symbol.clone(),
+        uri: params.uri.clone(),
+        seller_fee_basis_points: 0,
+        creators,
+        collection: None,
+        uses: None,
+    };
+    ctx.accounts.update_metadata(Some(data), None)?;
+
+    msg!("Updated metadata of mint {}: {} {} {}", ctx.accounts.mint.key(), params.name, params.symbol, params.uri);
+    emit!(MetadataUpdatedEvent {
+        mint: ctx.accounts.mint.key(),
+        name: params.name,
+        symbol: params.symbol,
+        uri: params.uri,
+        is_mutable: true,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 永久锁定代币元数据（仅管理员可操作），之后 update_token_metadata 会被 Metaplex 拒绝
+pub fn freeze_metadata(ctx: Context<UpdateTokenMetadata>) -> Result<()> {
+    ctx.accounts.update_metadata(None, Some(false))?;
+
+    msg!("Metadata of mint {} is now immutable", ctx.accounts.mint.key());
+    emit!(MetadataUpdatedEvent {
+        mint: ctx.accounts.mint.key(),
+        name: String::new(),
+        symbol: String::new(),
+        uri: String::new(),
+        is_mutable: false,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+impl<'info> UpdateTokenMetadata<'info> {
+    // 由mint PDA签名调用 Metaplex update_metadata_accounts_v2
+    fn update_metadata(&self, data: Option<DataV2>, is_mutable: Option<bool>) -> Result<()> {
+        let mint_config = &self.mint_config;
+        let seeds = &[b"mint".as_ref(), mint_config.symbol.as_bytes(), &[mint_config.mint_bump]];
+        let signer = [&seeds[..]];
+        update_metadata_accounts_v2(
+            CpiContext::new_with_signer(
+                self.token_metadata_program.to_account_info(),
+                UpdateMetadataAccountsV2 {
+                    metadata: self.metadata.to_account_info(),
+                    update_authority: self.mint.to_account_info(),
+                },
+                &signer,
+            ),
+            None,
+            data,
+            None,
+            is_mutable,
+        )
+    }
+}
+
+// 冻结任意持有人的代币账户（仅管理员可操作），由mint PDA作为 freeze authority 签名
+pub fn freeze_account(ctx: Context<FreezeTokenAccount>) -> Result<()> {
+    ctx.accounts.set_frozen(true)
+}
+
+// 解冻代币账户（仅管理员可操作）
+pub fn thaw_account(ctx: Context<FreezeTokenAccount>) -> Result<()> {
+    ctx.accounts.set_frozen(false)
+}
+
+impl<'info> FreezeTokenAccount<'info> {
+    // 由mint PDA签名调用 freeze_account / thaw_account，并记录事件
+    fn set_frozen(&self, frozen: bool) -> Result<()> {
+        let mint_config = &self.mint_config;
+        let seeds = &[b"mint".as_ref(), mint_config.symbol.as_bytes(), &[mint_config.mint_bump]];
+        let signer = [&seeds[..]];
+        if frozen {
+            token_interface::freeze_account(CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                FreezeAccount {
+                    account: self.token_account.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    authority: self.mint.to_account_info(),
+                },
+                &signer,
+            ))?;
+        } else {
+            token_interface::thaw_account(CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                ThawAccount {
+                    account: self.token_account.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    authority: self.mint.to_account_info(),
+                },
+                &signer,
+            ))?;
+        }
+
+        msg!("Token account {} of mint {} frozen: {}", self.token_account.key(), self.mint.key(), frozen);
+        emit!(TokenAccountFreezeEvent {
+            admin: self.admin.key(),
+            mint: self.mint.key(),
+            token_account: self.token_account.key(),
+            owner: self.token_account.owner,
+            frozen,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+}
+
 // 元数据参数
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct InitTokenParams {
@@ -84,23 +413,76 @@ pub struct InitTokenParams {
     pub symbol: String,
     pub uri: String,
     pub decimals: u8,
+    pub max_supply: u64,     // 总供应量上限（最小单位），必须大于0
+    pub epoch_emission: u64, // 每个 epoch 最多新增的供应量，0表示不限
+}
+
+impl InitTokenParams {
+    // symbol 是mint的seed：空 symbol 拼接后与初始版本的 [b"mint"] 地址相同，超长的 symbol 放不进 MintConfig
+    pub fn symbol_is_valid(&self) -> bool {
+        !self.symbol.is_empty() && self.symbol.len() <= MAX_SYMBOL_LENGTH
+    }
+}
+
+// 修改元数据参数，creators 为空表示不设置创作者
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct UpdateTokenMetadataParams {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub creators: Vec<TokenCreator>,
+}
+
+// 元数据创作者，share 为分成百分比
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct TokenCreator {
+    pub address: Pubkey,
+    pub share: u8,
 }
 
 #[derive(Accounts)]
+#[instruction(params: InitTokenParams)]
 pub struct CreateSpl<'info> {
     #[account(mut, signer)]
-    pub payer: Signer<'info>,  // 付费
+    pub payer: Signer<'info>,  // 配置管理员，付费并获得铸币权限
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin == payer.key() @ CustomError::Unauthorized, // 只有管理员可以创建代币
+    )]
+    pub config: Account<'info, Config>,
 
     #[account(
         init,
         payer = payer,
-        seeds = [b"mint"],
+        seeds = [b"mint", params.symbol.as_bytes()], // 每个symbol一个mint
         bump,
-        mint::decimals = 9,  
+        constraint = params.symbol_is_valid() @ CustomError::InvalidSymbol, // 在创建其余账户之前检查
+        mint::decimals = params.decimals,  
         mint::authority = mint, // mint权限归当前合约
         mint::freeze_authority = mint, // 冻结权限归当前合约
+        mint::token_program = token_program,
     )]
-    pub mint: Account<'info, Mint>,  // mint账户
+    pub mint: InterfaceAccount<'info, Mint>,  // mint账户
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"mint_config", mint.key().as_ref()],
+        bump,
+        space = 8 + MintConfig::INIT_SPACE,
+    )]
+    pub mint_config: Account<'info, MintConfig>, // mint配置账户
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [b"registry"],
+        bump,
+        space = 8 + TokenRegistry::INIT_SPACE,
+    )]
+    pub registry: Account<'info, TokenRegistry>, // 代币注册表
 
     /// CHECK: This is a PDA derived from the Mint account, verified in the instruction
     #[account(mut)]
@@ -108,7 +490,7 @@ pub struct CreateSpl<'info> {
 
     pub rent: Sysvar<'info, Rent>, // 租金管理账户
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>, // SPL
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
     pub token_metadata_program: Program<'info, Metaplex>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
@@ -116,26 +498,363 @@ pub struct CreateSpl<'info> {
 #[derive(Accounts)]
 pub struct MintSpl<'info> {
     #[account(mut, signer)]
-    pub payer: Signer<'info>, 
+    pub payer: Signer<'info>, // 铸币权限账户
 
     #[account(
         mut,
-        seeds = [b"mint"],
-        bump,
-        mint::authority = mint,
+        seeds = [b"mint_config", mint.key().as_ref()],
+        bump = mint_config.bump,
+        constraint = mint_config.authority == payer.key() @ CustomError::Unauthorized, // 只有铸币权限账户可以铸币
     )]
-    pub mint: Account<'info, Mint>,  // 代币的 Mint 账户
+    pub mint_config: Account<'info, MintConfig>, // mint配置账户，记录供应量上限和 epoch 铸币量
+
+    #[account(
+        mut,
+        seeds = [b"mint", mint_config.symbol.as_bytes()],
+        bump = mint_config.mint_bump,
+        constraint = mint.mint_authority == COption::Some(mint.key()) @ CustomError::MintingRevoked,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,  // 代币的 Mint 账户
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>, // 全局配置，检查暂停状态
+
+    /// CHECK: Any wallet may receive minted tokens; only used as the ATA owner.
+    pub recipient: UncheckedAccount<'info>, // 接收代币的钱包
 
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = mint,
-        associated_token::authority = payer,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program,
     )]
-    pub payer_ata: Account<'info, TokenAccount>,  // 用户的 Associated Token Account (ATA)
+    pub recipient_ata: InterfaceAccount<'info, TokenAccount>,  // 接收者的 Associated Token Account (ATA)
     
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>, // SPL
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
     pub token_metadata_program: Program<'info, Metaplex>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
+
+/** 修改和锁定元数据结构体 */
+#[derive(Accounts)]
+pub struct UpdateTokenMetadata<'info> {
+    pub admin: Signer<'info>, // 配置管理员
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"mint_config", mint.key().as_ref()],
+        bump = mint_config.bump,
+    )]
+    pub mint_config: Account<'info, MintConfig>, // mint配置账户，提供mint PDA的签名seeds
+
+    #[account(
+        seeds = [b"mint", mint_config.symbol.as_bytes()],
+        bump = mint_config.mint_bump,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // 元数据的 update authority
+
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    /// CHECK: This is the Metaplex metadata PDA of the mint, verified in constraints.
+    pub metadata: UncheckedAccount<'info>,
+
+    pub token_metadata_program: Program<'info, Metaplex>,
+}
+
+/** 冻结和解冻代币账户结构体 */
+#[derive(Accounts)]
+pub struct FreezeTokenAccount<'info> {
+    pub admin: Signer<'info>, // 配置管理员
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"mint_config", mint.key().as_ref()],
+        bump = mint_config.bump,
+    )]
+    pub mint_config: Account<'info, MintConfig>, // mint配置账户，提供mint PDA的签名seeds
+
+    #[account(
+        seeds = [b"mint", mint_config.symbol.as_bytes()],
+        bump = mint_config.mint_bump,
+        mint::freeze_authority = mint,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // 代币的 freeze authority
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>, // 任意持有人的代币账户
+
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+}
+
+/** 持有人销毁代币结构体 */
+#[derive(Accounts)]
+pub struct BurnSpl<'info> {
+    pub holder: Signer<'info>, // 代币持有人
+
+    #[account(
+        mut,
+        seeds = [b"mint_config", mint.key().as_ref()],
+        bump = mint_config.bump,
+    )]
+    pub mint_config: Account<'info, MintConfig>, // 记录累计销毁数量
+
+    #[account(
+        mut,
+        seeds = [b"mint", mint_config.symbol.as_bytes()],
+        bump = mint_config.mint_bump,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // 只能销毁本程序创建的代币
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = holder,
+        token::token_program = token_program,
+    )]
+    pub holder_ata: InterfaceAccount<'info, TokenAccount>, // 持有人自己的代币账户
+
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+}
+
+/** 管理员从质押PDA的ATA销毁代币结构体 */
+#[derive(Accounts)]
+pub struct BurnFromVault<'info> {
+    pub admin: Signer<'info>, // 配置管理员
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Only used to derive the vault PDA and the position seeds.
+    pub user: UncheckedAccount<'info>, // 质押用户钱包
+
+    #[account(
+        seeds = [b"stack", user.key().as_ref()],
+        bump = stack_account.stack_account_pda_pump,
+    )]
+    /// CHECK: This is a PDA verified in constraints.
+    pub pda_stack_account: AccountInfo<'info>, // 用户的质押PDA，作为 vault_ata 的 authority 签名
+
+    #[account(
+        seeds = [user.key().as_ref(), mint.key().as_ref()],
+        bump = stack_account.stack_account_pump,
+    )]
+    pub stack_account: Box<Account<'info, StackAccount>>, // 用户仓位，只读，用于计算可销毁数量
+
+    #[account(
+        mut,
+        seeds = [b"mint_config", mint.key().as_ref()],
+        bump = mint_config.bump,
+    )]
+    pub mint_config: Box<Account<'info, MintConfig>>, // 记录累计销毁数量
+
+    #[account(
+        mut,
+        seeds = [b"mint", mint_config.symbol.as_bytes()],
+        bump = mint_config.mint_bump,
+        mint::token_program = token_program,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = pda_stack_account,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_ata: Box<InterfaceAccount<'info, TokenAccount>>, // 质押PDA的ATA
+
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+}
+
+/** 撤销铸币权限结构体 */
+#[derive(Accounts)]
+pub struct RevokeMintAuthority<'info> {
+    pub authority: Signer<'info>, // 当前铸币权限账户
+
+    #[account(
+        mut,
+        seeds = [b"mint_config", mint.key().as_ref()],
+        bump = mint_config.bump,
+        has_one = authority @ CustomError::Unauthorized,
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"mint", mint_config.symbol.as_bytes()],
+        bump = mint_config.mint_bump,
+        constraint = mint.mint_authority == COption::Some(mint.key()) @ CustomError::MintingRevoked,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+}
+
+#[derive(Accounts)]
+pub struct ProposeMintAuthority<'info> {
+    pub authority: Signer<'info>, // 当前铸币权限账户
+
+    #[account(
+        mut,
+        seeds = [b"mint_config", mint_config.mint.as_ref()],
+        bump = mint_config.bump,
+        has_one = authority @ CustomError::Unauthorized,
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptMintAuthority<'info> {
+    pub pending_authority: Signer<'info>, // 被提名的铸币权限账户
+
+    #[account(
+        mut,
+        seeds = [b"mint_config", mint_config.mint.as_ref()],
+        bump = mint_config.bump,
+        constraint = mint_config.pending_authority == pending_authority.key() @ CustomError::Unauthorized,
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+}
+
+/** mint配置账户结构体 */
+#[account]
+#[derive(InitSpace)]
+pub struct MintConfig {
+    pub mint: Pubkey,
+    pub authority: Pubkey, // 铸币权限账户
+    pub pending_authority: Pubkey, // 被提名的铸币权限账户，默认地址表示没有
+    #[max_len(MAX_SYMBOL_LENGTH)]
+    pub symbol: String, // mint seeds
+    pub mint_bump: u8,
+    pub bump: u8,
+    pub max_supply: u64,         // 总供应量上限
+    pub epoch_emission: u64,     // 每个 epoch 最多新增的供应量，0表示不限
+    pub emission_epoch: u64,     // epoch_minted 对应的 epoch
+    pub epoch_minted: u64,       // emission_epoch 内累计铸造的数量，销毁不会减少
+    pub burned: u64,             // 经 burn_spl / burn_from_vault 累计销毁的数量
+}
+
+impl MintConfig {
+    // 按 Mint.supply 检查供应量上限；当前 epoch 的铸币上限按累计铸造量（不扣除销毁）检查，进入新 epoch 时清零
+    // 否则先销毁再铸造就能在同一个 epoch 内超出上限
+    pub fn record_emission(&mut self, supply: u64, amount: u64, epoch: u64) -> Result<()> {
+        let new_supply = supply.checked_add(amount).ok_or(CustomError::MathOverflow)?;
+        require!(new_supply <= self.max_supply, CustomError::MaxSupplyExceeded);
+        if self.epoch_emission == 0 {
+            return Ok(());
+        }
+        if epoch != self.emission_epoch {
+            self.emission_epoch = epoch;
+            self.epoch_minted = 0;
+        }
+        let minted = self.epoch_minted.checked_add(amount).ok_or(CustomError::MathOverflow)?;
+        require!(minted <= self.epoch_emission, CustomError::EpochEmissionExceeded);
+        self.epoch_minted = minted;
+        Ok(())
+    }
+}
+
+/** 代币注册表结构体 */
+#[account]
+#[derive(InitSpace)]
+pub struct TokenRegistry {
+    #[max_len(MAX_REGISTRY_MINTS)]
+    pub mints: Vec<Pubkey>, // create_token 创建的全部mint
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mint_config(max_supply: u64, epoch_emission: u64) -> MintConfig {
+        MintConfig {
+            mint: Pubkey::default(),
+            authority: Pubkey::default(),
+            pending_authority: Pubkey::default(),
+            symbol: String::new(),
+            mint_bump: 0,
+            bump: 0,
+            max_supply,
+            epoch_emission,
+            emission_epoch: 0,
+            epoch_minted: 0,
+            burned: 0,
+        }
+    }
+
+    #[test]
+    fn max_supply_is_a_hard_cap() {
+        let mut config = mint_config(1_000, 0);
+        config.record_emission(0, 1_000, 0).unwrap();
+        assert_eq!(config.record_emission(1_000, 1, 0).unwrap_err(), CustomError::MaxSupplyExceeded.into());
+        assert_eq!(config.record_emission(u64::MAX, 1, 0).unwrap_err(), CustomError::MathOverflow.into());
+    }
+
+    #[test]
+    fn symbol_must_be_non_empty_and_fit_the_mint_config() {
+        let params = |symbol: &str| InitTokenParams {
+            name: String::new(),
+            symbol: symbol.to_string(),
+            uri: String::new(),
+            decimals: 0,
+            max_supply: 1,
+            epoch_emission: 0,
+        };
+        assert!(params("T").symbol_is_valid());
+        assert!(params(&"T".repeat(MAX_SYMBOL_LENGTH)).symbol_is_valid());
+        assert!(!params("").symbol_is_valid());
+        assert!(!params(&"T".repeat(MAX_SYMBOL_LENGTH + 1)).symbol_is_valid());
+    }
+
+    #[test]
+    fn epoch_emission_resets_each_epoch() {
+        let mut config = mint_config(10_000, 100);
+        config.record_emission(0, 60, 0).unwrap();
+        assert_eq!(config.record_emission(60, 41, 0).unwrap_err(), CustomError::EpochEmissionExceeded.into());
+        config.record_emission(60, 40, 0).unwrap();
+
+        // 新 epoch 重新计算
+        config.record_emission(100, 100, 1).unwrap();
+        assert_eq!((config.emission_epoch, config.epoch_minted), (1, 100));
+    }
+
+    #[test]
+    fn burns_do_not_free_epoch_emission() {
+        let mut config = mint_config(10_000, 100);
+        config.record_emission(0, 100, 0).unwrap();
+        // 销毁 50 后供应量回到 50，本 epoch 仍不能继续铸造
+        assert_eq!(config.record_emission(50, 1, 0).unwrap_err(), CustomError::EpochEmissionExceeded.into());
+        assert_eq!(config.epoch_minted, 100);
+        config.record_emission(50, 100, 1).unwrap();
+    }
+}
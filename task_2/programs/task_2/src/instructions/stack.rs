@@ -2,19 +2,47 @@ use anchor_lang::prelude::*;
 use anchor_lang::system_program;
 use anchor_spl::{
     associated_token::AssociatedToken, // 处理关联代币账户的功能
-    token::{
-        mint_to,      // 铸币功能
-        Mint,         // 代币铸造的结构体
-        MintTo,       // 铸币指令的结构体
-        Token,        // 代币的基本功能
-        TokenAccount, // 代币账户的结构体
+    token_interface::{
+        close_account,    // 关闭代币账户
+        sync_native,      // 同步 WSOL 账户的 lamports 与代币余额
+        transfer_checked, // 带精度校验的转账（兼容 Token 与 Token-2022）
+        CloseAccount,     // 关闭代币账户指令的结构体
+        Mint,             // 代币铸造的结构体
+        SyncNative,       // 同步 WSOL 指令的结构体
+        TokenAccount,     // 代币账户的结构体
+        TokenInterface,   // Token / Token-2022 程序
+        TransferChecked,  // 转账指令的结构体
     },
 };
 
+use crate::events::{
+    ClosePositionEvent, ClosePositionSplEvent, DepositEvent, DepositSplEvent, PositionMigratedEvent, WithdrawEvent,
+    WithdrawSplEvent,
+};
+use crate::instructions::config::Config;
+use crate::instructions::reward::RewardPool;
+use crate::instructions::whitelist::WhitelistedMint;
+
+
+
+// 用户质押 SOL（充值），SOL 质押账户与 spl 质押PDA使用不同seeds；锁定中的仓位只能用 deposit_locked 追加
+pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.stack_account.require_flexible_deposit(now)?;
+
+    deposit_lamports(ctx, amount)
+}
+
+// 把 amount lamports（扣除手续费后）充值到SOL质押账户，并同步增加记录的余额
+fn deposit_lamports(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+    ctx.accounts.config.require_deposits_allowed()?;
+    require!(amount > 0, CustomError::ZeroAmount);
 
+    // 手续费从充值金额中扣除，扣除后必须仍有余额入账
+    let fee = ctx.accounts.config.deposit_fee(amount)?;
+    let net = amount.checked_sub(fee).ok_or(CustomError::MathOverflow)?;
+    require!(net > 0, CustomError::ZeroAmount);
 
-// 用户质押 SOL（充值）
-pub fn deposit(ctx: anchor_lang::context::Context<Deposit>, amount: u64) -> Result<()> {
     let user: AccountInfo<'_> = ctx.accounts.user.to_account_info();
     let stack_account = ctx.accounts.stack_account_pda.to_account_info();
     let system = ctx.accounts.system_program.to_account_info();
@@ -24,54 +52,172 @@ pub fn deposit(ctx: anchor_lang::context::Context<Deposit>, amount: u64) -> Resu
     // 用户充值到质押账户
     anchor_lang::system_program::transfer(
         CpiContext::new(
-            system,
+            system.clone(),
             anchor_lang::system_program::Transfer {
-                from: user,
+                from: user.clone(),
                 to: stack_account,
             },
         ),
-        amount,
+        net,
     )?;
+    // 手续费转入金库
+    if fee > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system,
+                anchor_lang::system_program::Transfer {
+                    from: user,
+                    to: ctx.accounts.treasury.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+    }
 
     // 更新余额
-    ctx.accounts.stack_account.balance += amount;
+    ctx.accounts.stack_account.balance = ctx
+        .accounts
+        .stack_account
+        .balance
+        .checked_add(net)
+        .ok_or(CustomError::MathOverflow)?;
     // 记录bump
-    if (stack_account_pda_pump == 0) {
+    if stack_account_pda_pump == 0 {
         ctx.accounts.stack_account.stack_account_pda_pump = ctx.bumps.stack_account_pda;
     }
 
-    if (stack_account_pump == 0) {
+    if stack_account_pump == 0 {
         ctx.accounts.stack_account.stack_account_pump = ctx.bumps.stack_account;
     }
+    ctx.accounts.stack_account.version = STACK_ACCOUNT_VERSION;
 
-    msg!("Deposit {} lamports to user stack account", amount);
+    msg!("Deposit {} lamports to user stack account, fee {}", amount, fee);
+    emit!(DepositEvent {
+        user: ctx.accounts.user.key(),
+        amount,
+        fee,
+        balance: ctx.accounts.stack_account.balance,
+        lock_end: ctx.accounts.stack_account.lock_end,
+        slot: Clock::get()?.slot,
+    });
     Ok(())
 }
 
 
+// 用户锁仓质押 SOL，lock_duration 为锁定秒数
+pub fn deposit_locked(ctx: Context<Deposit>, amount: u64, lock_duration: i64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.stack_account.lock(now, lock_duration)?;
+
+    deposit_lamports(ctx, amount)
+}
+
 // 用户提款（提取全部 SOL）
 pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+    // 判断质押账户余额
+    let balance = ctx.accounts.stack_account.balance;
+    require!(balance > 0, CustomError::InsufficientBalance);
+
+    withdraw_lamports(ctx, balance)
+}
+
+// 用户部分提款（提取指定数量的 SOL）
+pub fn withdraw_amount(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    // 判断提款金额是否超过质押余额
+    require!(amount > 0, CustomError::ZeroAmount);
+    let balance = ctx.accounts.stack_account.balance;
+    require!(balance > 0, CustomError::InsufficientBalance);
+    require!(amount <= balance, CustomError::WithdrawAmountExceedsBalance);
+
+    withdraw_lamports(ctx, amount)
+}
+
+// 从SOL质押账户转出 amount lamports 给用户，并同步扣减记录的余额
+fn withdraw_lamports(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    let config = Config::load_or_default(&ctx.accounts.config)?;
+    config.require_withdrawals_allowed()?;
+
     let user = ctx.accounts.user.to_account_info();
     let stack_account: AccountInfo<'_> = ctx.accounts.stack_account_pda.to_account_info();
     let system = ctx.accounts.system_program.to_account_info();
 
+    // 锁定期内不能提款
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.stack_account.require_unlocked(now)?;
+
     // 查询
     let balance = ctx.accounts.stack_account.balance;
     let pda_balance = ctx.accounts.stack_account_pda.get_lamports();
 
-    // 判断质押账户余额
-    require!(balance > 0, CustomError::InsufficientBalance);
-
     // 将余额返回给用户
-    // let bump = Pubkey::find_program_address(&[b"stack", user.key.as_ref()], &id()).1;
     let bump = ctx.accounts.stack_account.stack_account_pda_pump;
     let program_id = ctx.program_id;
 
     msg!("find_program_address program_id {} bump {} balance:{} pda_balance:{}", program_id, bump, balance, pda_balance);
-    // drop(pda_balance); // 释放borrow 仅为log
 
-    let seeds: &[&[u8]] = &[b"stack", user.key.as_ref(), &[bump]];
-    let signer = &[&seeds[..]];
+    // 手续费从提取数量中扣除
+    let fee = config.withdraw_fee(amount)?;
+    let net = amount.checked_sub(fee).ok_or(CustomError::MathOverflow)?;
+
+    let seeds: &[&[u8]] = &[b"sol_stack", user.key.as_ref(), &[bump]];
+    let signer = &[seeds];
+    anchor_lang::system_program::transfer(
+        CpiContext::new_with_signer(
+            system.clone(),
+            anchor_lang::system_program::Transfer {
+                from: stack_account.clone(),
+                to: user,
+            },
+            signer,
+        ),
+        net,
+    )?;
+    if fee > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new_with_signer(
+                system,
+                anchor_lang::system_program::Transfer {
+                    from: stack_account,
+                    to: ctx.accounts.treasury.to_account_info(),
+                },
+                signer,
+            ),
+            fee,
+        )?;
+    }
+    ctx.accounts.stack_account.balance = ctx
+        .accounts
+        .stack_account
+        .balance
+        .checked_sub(amount)
+        .ok_or(CustomError::MathOverflow)?;
+
+    msg!(
+        "Withdrew {} lamports to user wallet, fee {}, remaining balance {}",
+        net,
+        fee,
+        ctx.accounts.stack_account.balance
+    );
+    emit!(WithdrawEvent {
+        user: ctx.accounts.user.key(),
+        amount,
+        fee,
+        balance: ctx.accounts.stack_account.balance,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 关闭已清空的 SOL 仓位，数据账户由 close 约束关闭，质押账户的租金转回用户
+pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+    let user = ctx.accounts.user.to_account_info();
+    let stack_account = ctx.accounts.stack_account_pda.to_account_info();
+    let system = ctx.accounts.system_program.to_account_info();
+    let refunded = stack_account.lamports();
+
+    let bump = ctx.accounts.stack_account.stack_account_pda_pump;
+    let seeds: &[&[u8]] = &[b"sol_stack", user.key.as_ref(), &[bump]];
+    let signer = &[seeds];
     anchor_lang::system_program::transfer(
         CpiContext::new_with_signer(
             system,
@@ -81,96 +227,513 @@ pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
             },
             signer,
         ),
-        balance,
+        refunded,
     )?;
-    ctx.accounts.stack_account.balance -= balance;
 
-    msg!("Withdrew {} lamports to user wallet", balance);
+    msg!("Closed stack account, refunded {} lamports of vault rent", refunded);
+    emit!(ClosePositionEvent {
+        user: ctx.accounts.user.key(),
+        refunded,
+        slot: Clock::get()?.slot,
+    });
     Ok(())
 }
 
-// 用户质押 spl（充值）
+// 用户质押 spl（充值），锁定中的仓位只能用 deposit_spl_locked 追加
 pub fn deposit_spl(ctx: Context<DepositSpl>, amount: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.stack_account.require_flexible_deposit(now)?;
+
+    deposit_tokens(ctx, amount)
+}
+
+// 把 amount 个代币（扣除手续费后）充值到质押ATA，并同步增加记录的余额
+fn deposit_tokens(ctx: Context<DepositSpl>, amount: u64) -> Result<()> {
+    ctx.accounts.config.require_deposits_allowed()?;
+    require!(amount > 0, CustomError::ZeroAmount);
+
+    // 余额变动前先结算奖励
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.reward_pool.update(now)?;
+    ctx.accounts.stack_account.settle_rewards(&ctx.accounts.reward_pool)?;
+
+    // 手续费按用户转出数量计算，直接转入该代币的金库ATA
+    let fee = ctx.accounts.config.deposit_fee(amount)?;
+    let net = amount.checked_sub(fee).ok_or(CustomError::MathOverflow)?;
+    require!(net > 0, CustomError::ZeroAmount);
+
+    // 转账前质押ATA的余额，用于计算实际到账数量
+    let vault_before = ctx.accounts.stack_account_ata.amount;
 
     // 用户充值到质押账户
-    let _tx = anchor_spl::token::transfer(
+    transfer_checked(
         CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
-            anchor_spl::token::Transfer {
+            TransferChecked {
                 from: ctx.accounts.payer_ata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
                 to: ctx.accounts.stack_account_ata.to_account_info(),
                 authority: ctx.accounts.payer.to_account_info(),
             }
         ),
-        amount
+        net,
+        ctx.accounts.mint.decimals,
     )?;
+    if fee > 0 {
+        transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.payer_ata.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.treasury_ata.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            fee,
+            ctx.accounts.mint.decimals,
+        )?;
+    }
+
+    // Token-2022 转账手续费会从到账数量中扣除，按实际到账数量记账
+    ctx.accounts.stack_account_ata.reload()?;
+    let received = ctx
+        .accounts
+        .stack_account_ata
+        .amount
+        .checked_sub(vault_before)
+        .ok_or(CustomError::MathOverflow)?;
 
     // 更新余额
-    ctx.accounts.stack_account.balance += amount;
+    let stack_account = &mut ctx.accounts.stack_account;
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    stack_account.balance = stack_account.balance.checked_add(received).ok_or(CustomError::MathOverflow)?;
+    reward_pool.total_staked = reward_pool.total_staked.checked_add(received).ok_or(CustomError::MathOverflow)?;
+    stack_account.sync_reward_debt(reward_pool)?;
     // 记录bump
     ctx.accounts.stack_account.stack_account_pda_pump = ctx.bumps.pda_stack_account;
     ctx.accounts.stack_account.stack_account_pump = ctx.bumps.stack_account;
-    msg!("Deposit {} lamports spl token to user stack account", amount);
+    ctx.accounts.stack_account.version = STACK_ACCOUNT_VERSION;
+    msg!(
+        "Deposit {} spl token {} to user stack account, fee {}, received {}",
+        amount,
+        ctx.accounts.mint.key(),
+        fee,
+        received
+    );
+    emit!(DepositSplEvent {
+        user: ctx.accounts.payer.key(),
+        mint: ctx.accounts.mint.key(),
+        amount,
+        fee,
+        received,
+        balance: ctx.accounts.stack_account.balance,
+        lock_end: ctx.accounts.stack_account.lock_end,
+        slot: Clock::get()?.slot,
+    });
     Ok(())
 }
 
+// 用户锁仓质押 spl，lock_duration 为锁定秒数
+pub fn deposit_spl_locked(ctx: Context<DepositSpl>, amount: u64, lock_duration: i64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.stack_account.lock(now, lock_duration)?;
+
+    deposit_tokens(ctx, amount)
+}
+
 pub fn withdraw_spl(ctx: Context<WithdrawSpl>) -> Result<()> {
     msg!("Executing withdraw_spl function");
     msg!("user {}", ctx.accounts.payer.to_account_info().key);
     msg!("pda_stack_account {}", ctx.accounts.pda_stack_account.to_account_info().key);
+    let config = Config::load_or_default(&ctx.accounts.config)?;
+    config.require_withdrawals_allowed()?;
 
     // 判断质押账户余额
     let balance = ctx.accounts.stack_account.balance;
     require!(balance > 0, CustomError::InsufficientBalance);
 
-    let from_ata = ctx.accounts.stack_account_ata.to_account_info();
-    let to_ata = ctx.accounts.user_ata.to_account_info();
-    let from_pda = ctx.accounts.pda_stack_account.to_account_info();
-    let token_program = ctx.accounts.token_program.to_account_info();
+    // 锁定期内不能提款
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.stack_account.require_unlocked(now)?;
 
+    // 余额变动前先结算奖励
+    ctx.accounts.reward_pool.update(now)?;
+    ctx.accounts.stack_account.settle_rewards(&ctx.accounts.reward_pool)?;
+
+    // 手续费从提取数量中扣除，转入该代币的金库ATA
+    let fee = config.withdraw_fee(balance)?;
+    let net = balance.checked_sub(fee).ok_or(CustomError::MathOverflow)?;
 
     // 交易签名seeds
     let user = ctx.accounts.payer.to_account_info();
     let bump = ctx.accounts.stack_account.stack_account_pda_pump;
     let seeds: &[&[u8]] = &[b"stack", user.key.as_ref(), &[bump]];
-    // let signer = &[&seeds[..]];
-
-    // 方式一 CPI调用 anchor_spl::token::transfer()
-    // let cpi_context = CpiContext::new_with_signer(
-    //     ctx.accounts.token_program.to_account_info(),
-    //         anchor_spl::token::Transfer {
-    //             from: from_ata,
-    //             to: to_ata,
-    //             authority: from_pda,     //指定authority作为需要签名的账户
-    //         },
-    //         signer
-    // );
-    // let _tx = token::transfer(cpi_context, balance)?;
-
-    // 方式二 交易指令
-    let transfer_ix = spl_token::instruction::transfer(
-        token_program.key,      
-        from_ata.key,            
-        to_ata.key,         
-        from_pda.key,               
-        &[&from_pda.key],   // PDA公钥作为需要签名的账户
-        balance,                 
+    let signer = &[seeds];
+
+    // CPI调用 transfer_checked，由质押PDA签名
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.stack_account_ata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.user_ata.to_account_info(),
+                authority: ctx.accounts.pda_stack_account.to_account_info(), // 指定authority作为需要签名的账户
+            },
+            signer,
+        ),
+        net,
+        ctx.accounts.mint.decimals,
     )?;
+    if fee > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.stack_account_ata.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.treasury_ata.to_account_info(),
+                    authority: ctx.accounts.pda_stack_account.to_account_info(),
+                },
+                signer,
+            ),
+            fee,
+            ctx.accounts.mint.decimals,
+        )?;
+    }
+
+    // 更新余额
+    let stack_account = &mut ctx.accounts.stack_account;
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    stack_account.balance = stack_account.balance.checked_sub(balance).ok_or(CustomError::MathOverflow)?;
+    reward_pool.total_staked = reward_pool.total_staked.checked_sub(balance).ok_or(CustomError::MathOverflow)?;
+    stack_account.sync_reward_debt(reward_pool)?;
+    msg!("Withdrew {} spl token to user wallet, fee {}", net, fee);
+    emit!(WithdrawSplEvent {
+        user: ctx.accounts.payer.key(),
+        mint: ctx.accounts.mint.key(),
+        amount: balance,
+        fee,
+        balance: ctx.accounts.stack_account.balance,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 提取初始版本的 spl 仓位并关闭旧数据账户：当时每个用户只有一个 [payer] 仓位，只能质押全局 [b"mint"] 代币
+// 旧仓位没有锁定和奖励，该代币也没有金库ATA，因此不收手续费；数据账户的租金退回用户
+pub fn withdraw_spl_legacy(ctx: Context<WithdrawSplLegacy>) -> Result<()> {
+    let config = Config::load_or_default(&ctx.accounts.config)?;
+    config.require_withdrawals_allowed()?;
+
+    let legacy_stack_account = ctx.accounts.legacy_stack_account.to_account_info();
+    // 初始版本按 space = 8 + 16 分配，按账户大小读取为版本0
+    let legacy = StackAccount::try_deserialize_versioned(&legacy_stack_account.try_borrow_data()?)?;
+    require!(legacy.version == 0, CustomError::NotLegacyPosition);
+
+    // 交易签名seeds
+    let user = ctx.accounts.payer.to_account_info();
+    let seeds: &[&[u8]] = &[b"stack", user.key.as_ref(), &[ctx.bumps.pda_stack_account]];
+    let signer = &[seeds];
+
+    if legacy.balance > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.stack_account_ata.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.user_ata.to_account_info(),
+                    authority: ctx.accounts.pda_stack_account.to_account_info(),
+                },
+                signer,
+            ),
+            legacy.balance,
+            ctx.accounts.mint.decimals,
+        )?;
+    }
+
+    // 关闭旧数据账户，[payer] 地址不再被任何仓位使用
+    let refunded = legacy_stack_account.lamports();
+    let user_lamports = user.lamports().checked_add(refunded).ok_or(CustomError::MathOverflow)?;
+    **user.try_borrow_mut_lamports()? = user_lamports;
+    **legacy_stack_account.try_borrow_mut_lamports()? = 0;
+    legacy_stack_account.assign(&system_program::ID);
+    legacy_stack_account.realloc(0, false)?;
+
+    msg!("Withdrew {} legacy spl token to user wallet, refunded {} lamports", legacy.balance, refunded);
+    emit!(WithdrawSplEvent {
+        user: ctx.accounts.payer.key(),
+        mint: ctx.accounts.mint.key(),
+        amount: legacy.balance,
+        fee: 0,
+        balance: 0,
+        slot: Clock::get()?.slot,
+    });
+    emit!(ClosePositionSplEvent {
+        user: ctx.accounts.payer.key(),
+        mint: ctx.accounts.mint.key(),
+        refunded,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// WSOL（SPL Token 的 native mint），deposit_spl_native / withdraw_spl_native 只接受这个mint
+pub const NATIVE_MINT: Pubkey = anchor_spl::token::spl_token::native_mint::ID;
+
+// 用户直接质押 SOL 到 WSOL 仓位：lamports 转入质押PDA的 WSOL ATA 后 sync_native，无需事先包装
+// 仓位与 deposit_spl 质押 WSOL 共用同一个数据账户和质押ATA
+pub fn deposit_spl_native(ctx: Context<DepositSplNative>, amount: u64) -> Result<()> {
+    ctx.accounts.config.require_deposits_allowed()?;
+    require!(amount > 0, CustomError::ZeroAmount);
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.stack_account.require_flexible_deposit(now)?;
+
+    // 余额变动前先结算奖励
+    ctx.accounts.reward_pool.update(now)?;
+    ctx.accounts.stack_account.settle_rewards(&ctx.accounts.reward_pool)?;
+
+    // 手续费按充值数量计算，包装后转入 WSOL 的金库ATA
+    let fee = ctx.accounts.config.deposit_fee(amount)?;
+    let net = amount.checked_sub(fee).ok_or(CustomError::MathOverflow)?;
+    require!(net > 0, CustomError::ZeroAmount);
+
+    wrap_lamports(&ctx, ctx.accounts.stack_account_ata.to_account_info(), net)?;
+    if fee > 0 {
+        wrap_lamports(&ctx, ctx.accounts.treasury_ata.to_account_info(), fee)?;
+    }
+
+    // 更新余额，WSOL 没有转账手续费，到账数量即 net
+    let stack_account = &mut ctx.accounts.stack_account;
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    stack_account.balance = stack_account.balance.checked_add(net).ok_or(CustomError::MathOverflow)?;
+    reward_pool.total_staked = reward_pool.total_staked.checked_add(net).ok_or(CustomError::MathOverflow)?;
+    stack_account.sync_reward_debt(reward_pool)?;
+    // 记录bump
+    stack_account.stack_account_pda_pump = ctx.bumps.pda_stack_account;
+    stack_account.stack_account_pump = ctx.bumps.stack_account;
+    stack_account.version = STACK_ACCOUNT_VERSION;
+    msg!("Deposit {} lamports as wrapped SOL, fee {}", amount, fee);
+    emit!(DepositSplEvent {
+        user: ctx.accounts.payer.key(),
+        mint: NATIVE_MINT,
+        amount,
+        fee,
+        received: net,
+        balance: ctx.accounts.stack_account.balance,
+        lock_end: ctx.accounts.stack_account.lock_end,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
 
-    let _tx = anchor_lang::solana_program::program::invoke_signed(
-        &transfer_ix,
-        &[
-            from_ata,
-            to_ata,
-            from_pda,
-            token_program,
-        ],
-        &[&seeds[..]],  // 交易签名
+// 把用户的 lamports 转入 WSOL 代币账户并同步代币余额
+fn wrap_lamports<'info>(ctx: &Context<DepositSplNative<'info>>, account: AccountInfo<'info>, lamports: u64) -> Result<()> {
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer { from: ctx.accounts.payer.to_account_info(), to: account.clone() },
+        ),
+        lamports,
     )?;
+    sync_native(CpiContext::new(ctx.accounts.token_program.to_account_info(), SyncNative { account }))
+}
+
+// 提取全部 WSOL 仓位并解包为 SOL：转入临时 WSOL 账户后关闭，lamports（含临时账户租金）退回用户钱包
+pub fn withdraw_spl_native(ctx: Context<WithdrawSplNative>) -> Result<()> {
+    let config = Config::load_or_default(&ctx.accounts.config)?;
+    config.require_withdrawals_allowed()?;
+
+    // 判断质押账户余额
+    let balance = ctx.accounts.stack_account.balance;
+    require!(balance > 0, CustomError::InsufficientBalance);
+
+    // 锁定期内不能提款
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.stack_account.require_unlocked(now)?;
+
+    // 余额变动前先结算奖励
+    ctx.accounts.reward_pool.update(now)?;
+    ctx.accounts.stack_account.settle_rewards(&ctx.accounts.reward_pool)?;
+
+    // 手续费以 WSOL 转入金库ATA，不解包
+    let fee = config.withdraw_fee(balance)?;
+    let net = balance.checked_sub(fee).ok_or(CustomError::MathOverflow)?;
+
+    // 交易签名seeds
+    let user = ctx.accounts.payer.key();
+    let bump = ctx.accounts.stack_account.stack_account_pda_pump;
+    let seeds: &[&[u8]] = &[b"stack", user.as_ref(), &[bump]];
+    let signer = &[seeds];
+
+    if net > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.stack_account_ata.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.unwrap_account.to_account_info(),
+                    authority: ctx.accounts.pda_stack_account.to_account_info(),
+                },
+                signer,
+            ),
+            net,
+            ctx.accounts.mint.decimals,
+        )?;
+    }
+    if fee > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.stack_account_ata.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.treasury_ata.to_account_info(),
+                    authority: ctx.accounts.pda_stack_account.to_account_info(),
+                },
+                signer,
+            ),
+            fee,
+            ctx.accounts.mint.decimals,
+        )?;
+    }
+
+    // 关闭临时 WSOL 账户即完成解包
+    close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.unwrap_account.to_account_info(),
+            destination: ctx.accounts.payer.to_account_info(),
+            authority: ctx.accounts.pda_stack_account.to_account_info(),
+        },
+        signer,
+    ))?;
 
     // 更新余额
-    ctx.accounts.stack_account.balance -= balance;
-    msg!("Withdrew {} lamports spl token to user wallet", balance);
+    let stack_account = &mut ctx.accounts.stack_account;
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    stack_account.balance = stack_account.balance.checked_sub(balance).ok_or(CustomError::MathOverflow)?;
+    reward_pool.total_staked = reward_pool.total_staked.checked_sub(balance).ok_or(CustomError::MathOverflow)?;
+    stack_account.sync_reward_debt(reward_pool)?;
+    msg!("Withdrew {} lamports of wrapped SOL to user wallet, fee {}", net, fee);
+    emit!(WithdrawSplEvent {
+        user,
+        mint: NATIVE_MINT,
+        amount: balance,
+        fee,
+        balance: ctx.accounts.stack_account.balance,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 关闭已清空的 spl 仓位：关闭数据账户、质押ATA，并将质押PDA的租金转回用户
+pub fn close_position_spl(ctx: Context<ClosePositionSpl>) -> Result<()> {
+    let user = ctx.accounts.payer.to_account_info();
+    let pda_stack_account = ctx.accounts.pda_stack_account.to_account_info();
+    let bump = ctx.accounts.stack_account.stack_account_pda_pump;
+    let seeds: &[&[u8]] = &[b"stack", user.key.as_ref(), &[bump]];
+    let signer = &[seeds];
+
+    // 关闭质押ATA，租金退回用户
+    let ata_rent = ctx.accounts.stack_account_ata.to_account_info().lamports();
+    close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.stack_account_ata.to_account_info(),
+            destination: user.clone(),
+            authority: pda_stack_account.clone(),
+        },
+        signer,
+    ))?;
+
+    // 质押PDA只用于签名，转出全部租金
+    let pda_rent = pda_stack_account.lamports();
+    if pda_rent > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: pda_stack_account,
+                    to: user,
+                },
+                signer,
+            ),
+            pda_rent,
+        )?;
+    }
+
+    let refunded = ata_rent + pda_rent;
+    msg!("Closed spl position for mint {}, refunded {} lamports", ctx.accounts.mint.key(), refunded);
+    emit!(ClosePositionSplEvent {
+        user: ctx.accounts.payer.key(),
+        mint: ctx.accounts.mint.key(),
+        refunded,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 把任一旧版本布局的质押账户扩容到当前布局，新增字段按0补齐，新增的租金由用户支付
+// SOL 仓位 mint 传 None（seeds [b"sol_position", user]），spl 仓位传代币mint（seeds [user, mint]）和该代币的奖励池
+pub fn migrate_position(ctx: Context<MigratePosition>, mint: Option<Pubkey>) -> Result<()> {
+    let user = ctx.accounts.user.to_account_info();
+    let position = ctx.accounts.stack_account.to_account_info();
+
+    // 只能迁移自己的仓位
+    let expected = match &mint {
+        Some(mint) => Pubkey::find_program_address(&[user.key.as_ref(), mint.as_ref()], ctx.program_id).0,
+        None => Pubkey::find_program_address(&[b"sol_position", user.key.as_ref()], ctx.program_id).0,
+    };
+    require_keys_eq!(position.key(), expected, CustomError::Unauthorized);
+
+    let mut stack_account = StackAccount::try_deserialize_versioned(&position.try_borrow_data()?)?;
+    require!(stack_account.version < STACK_ACCOUNT_VERSION, CustomError::PositionAlreadyMigrated);
+
+    // 奖励池之前的 spl 仓位余额不在 total_staked 中，计入后从当前累计值开始结算奖励
+    if let Some(mint) = mint {
+        let reward_pool = ctx.accounts.reward_pool.as_mut().ok_or(CustomError::RewardPoolMismatch)?;
+        require_keys_eq!(reward_pool.stake_mint, mint, CustomError::RewardPoolMismatch);
+        if stack_account.version < REWARD_STACK_ACCOUNT_VERSION {
+            reward_pool.update(Clock::get()?.unix_timestamp)?;
+            reward_pool.total_staked =
+                reward_pool.total_staked.checked_add(stack_account.balance).ok_or(CustomError::MathOverflow)?;
+            stack_account.sync_reward_debt(reward_pool)?;
+        }
+    }
+
+    // 补足新布局所需的租金后扩容
+    let space = 8 + StackAccount::INIT_SPACE;
+    let rent_paid = Rent::get()?.minimum_balance(space).saturating_sub(position.lamports());
+    if rent_paid > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: user.clone(),
+                    to: position.clone(),
+                },
+            ),
+            rent_paid,
+        )?;
+    }
+    position.realloc(space, false)?;
+
+    stack_account.version = STACK_ACCOUNT_VERSION;
+    stack_account.try_serialize(&mut &mut position.try_borrow_mut_data()?[..])?;
+
+    msg!("Migrated position {} to version {}, rent paid {}", position.key(), STACK_ACCOUNT_VERSION, rent_paid);
+    emit!(PositionMigratedEvent {
+        user: user.key(),
+        position: position.key(),
+        mint,
+        version: STACK_ACCOUNT_VERSION,
+        rent_paid,
+        slot: Clock::get()?.slot,
+    });
     Ok(())
 }
 
@@ -182,7 +745,7 @@ pub struct Deposit<'info> {
     #[account(
         init_if_needed,
         payer = user,
-        seeds = [b"stack", user.key.as_ref()],
+        seeds = [b"sol_stack", user.key.as_ref()], // 与 spl 质押PDA [b"stack", user] 区分
         owner = system_program::ID, // 系统账户
         bump,
         space = 0 
@@ -192,11 +755,23 @@ pub struct Deposit<'info> {
     #[account(
         init_if_needed, // 如果 stack_account 未初始化，自动初始化
         payer = user, 
-        seeds = [user.key.as_ref()],
+        seeds = [b"sol_position", user.key.as_ref()], // [user] 是初始版本的 spl 仓位，见 withdraw_spl_legacy
         bump, 
-        space = 8 + 16 // #[drive(init space)] or std::mem::size_of::<StackAccount>()
+        space = 8 + StackAccount::INIT_SPACE
     )]
     pub stack_account: Account<'info, StackAccount>,  // 数据账户，可以随意更改不用签名
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>, // 全局配置，检查暂停状态
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"treasury"],
+        owner = system_program::ID, // 系统账户
+        bump,
+        space = 0
+    )]
+    /// CHECK: This is a PDA verified in constraints.
+    pub treasury: AccountInfo<'info>, // SOL 手续费金库
     pub system_program: Program<'info, System>,
 }
 
@@ -205,11 +780,32 @@ pub struct Deposit<'info> {
 pub struct Withdraw<'info> {
     #[account(mut, signer)]
     pub user: Signer<'info>, // 提现用户
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"sol_stack", user.key.as_ref()],
+        bump = stack_account.stack_account_pda_pump, // 使用记录的bump重新推导，确保是当前用户的质押账户
+    )]
     /// CHECK: This is a PDA verified in constraints.
     pub stack_account_pda: AccountInfo<'info>, // 存储SOL的普通质押账户
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"sol_position", user.key.as_ref()],
+        bump = stack_account.stack_account_pump, // 数据账户必须属于当前签名用户
+    )]
     pub stack_account: Account<'info, StackAccount>, // 质押数据账户
+    #[account(seeds = [b"config"], bump)]
+    /// CHECK: Loaded with Config::load_or_default, may not be initialized yet.
+    pub config: UncheckedAccount<'info>, // 全局配置，未初始化时视为未暂停、不收手续费
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"treasury"],
+        owner = system_program::ID, // 系统账户
+        bump,
+        space = 0
+    )]
+    /// CHECK: This is a PDA verified in constraints.
+    pub treasury: AccountInfo<'info>, // SOL 手续费金库
     pub system_program: Program<'info, System>,
 }
 
@@ -233,34 +829,64 @@ pub struct DepositSpl<'info> {
     #[account(
         init_if_needed, // 如果 stack_account 未初始化，自动初始化
         payer = payer, 
-        seeds = [payer.key.as_ref()],
+        seeds = [payer.key.as_ref(), mint.key().as_ref()], // 每个用户每种代币一个仓位
         bump, 
-        space = 8 + 16 // #[drive(init space)] or std::mem::size_of::<StackAccount>()
+        space = 8 + StackAccount::INIT_SPACE
     )]
     pub stack_account: Account<'info, StackAccount>,  // 数据账户，可以随意更改不用签名
 
 
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,  // 代币的 Mint 账户
+
+    #[account(
+        seeds = [b"whitelist", mint.key().as_ref()],
+        bump = whitelisted_mint.bump,
+    )]
+    pub whitelisted_mint: Account<'info, WhitelistedMint>, // 只有白名单内的代币可以质押
+
     #[account(
         mut,
-        seeds = [b"mint"],
-        bump,
-        mint::authority = mint,
+        seeds = [b"reward_pool", mint.key().as_ref()],
+        bump = reward_pool.bump,
     )]
-    pub mint: Account<'info, Mint>,  // 代币的 Mint 账户
+    pub reward_pool: Account<'info, RewardPool>, // 该代币的奖励池
 
-    #[account(mut)]
-    pub payer_ata: Account<'info, TokenAccount>,  // 用户的 Associated Token Account (ATA)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>, // 全局配置，检查暂停状态
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = payer,
+        token::token_program = token_program,
+        constraint = !payer_ata.is_frozen() @ CustomError::AccountFrozen, // 冻结的代币账户不能充值
+    )]
+    pub payer_ata: InterfaceAccount<'info, TokenAccount>,  // 用户的 Associated Token Account (ATA)
 
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = mint,
         associated_token::authority = pda_stack_account,
+        associated_token::token_program = token_program,
+    )]
+    pub stack_account_ata: InterfaceAccount<'info, TokenAccount>, 
+
+    #[account(seeds = [b"treasury"], bump)]
+    /// CHECK: This is a PDA verified in constraints, only used as the fee ATA authority.
+    pub treasury: UncheckedAccount<'info>, // 手续费金库PDA
+
+    #[account(
+        mut, // 由 add_whitelisted_mint 创建
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
     )]
-    pub stack_account_ata: Account<'info, TokenAccount>, 
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>, // 该代币的手续费ATA（Box 减少栈占用）
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>, // SPL
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
@@ -272,29 +898,413 @@ pub struct WithdrawSpl<'info> {
     #[account(
         mut,
         seeds = [b"stack", payer.key.as_ref()],
-        bump
+        bump = stack_account.stack_account_pda_pump, // 使用记录的bump重新推导
     )]
     /// CHECK: This is a PDA verified in constraints.
     pub pda_stack_account: AccountInfo<'info>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [payer.key.as_ref(), mint.key().as_ref()],
+        bump = stack_account.stack_account_pump, // 数据账户必须属于当前签名用户和代币
+    )]
     pub stack_account: Account<'info, StackAccount>, // 质押数据账户
 
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,  // 代币的 Mint 账户（移出白名单后仍可提现）
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool", mint.key().as_ref()],
+        bump = reward_pool.bump,
+    )]
+    pub reward_pool: Account<'info, RewardPool>, // 该代币的奖励池
+
+    #[account(seeds = [b"config"], bump)]
+    /// CHECK: Loaded with Config::load_or_default, may not be initialized yet.
+    pub config: UncheckedAccount<'info>, // 全局配置，未初始化时视为未暂停、不收手续费
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub user_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = pda_stack_account, // 必须是当前用户质押PDA的ATA
+        associated_token::token_program = token_program,
+    )]
+    pub stack_account_ata: InterfaceAccount<'info, TokenAccount>, 
+
+    #[account(seeds = [b"treasury"], bump)]
+    /// CHECK: This is a PDA verified in constraints, only used as the fee ATA authority.
+    pub treasury: UncheckedAccount<'info>, // 手续费金库PDA
+
+    #[account(
+        mut, // 由 add_whitelisted_mint 创建
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>, // 该代币的手续费ATA（Box 减少栈占用）
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/** 提现初始版本 spl 仓位结构体 */
+#[derive(Accounts)]
+pub struct WithdrawSplLegacy<'info> {
     #[account(mut)]
-    pub user_ata: Account<'info, TokenAccount>,
+    pub payer: Signer<'info>, // 仓位用户，接收代币和旧数据账户的租金
+    #[account(
+        seeds = [b"stack", payer.key.as_ref()],
+        bump,
+    )]
+    /// CHECK: This is a PDA verified in constraints.
+    pub pda_stack_account: AccountInfo<'info>, // 质押PDA，旧代币质押ATA的所有者
+
+    /// CHECK: The baseline layout is read with StackAccount::try_deserialize_versioned; seeds and owner are checked here.
+    #[account(
+        mut,
+        seeds = [payer.key.as_ref()],
+        bump,
+        owner = crate::ID,
+    )]
+    pub legacy_stack_account: UncheckedAccount<'info>, // 初始版本的质押数据账户，指令内关闭
+
+    #[account(
+        seeds = [b"mint"],
+        bump,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // 初始版本的全局代币
+
+    #[account(seeds = [b"config"], bump)]
+    /// CHECK: Loaded with Config::load_or_default, may not be initialized yet.
+    pub config: UncheckedAccount<'info>, // 全局配置，未初始化时视为未暂停
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub user_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = pda_stack_account,
+        associated_token::token_program = token_program,
+    )]
+    pub stack_account_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/** 直接用 SOL 充值 WSOL 仓位结构体 */
+#[derive(Accounts)]
+pub struct DepositSplNative<'info> {
     #[account(mut)]
-    pub stack_account_ata: Account<'info, TokenAccount>, 
+    pub payer: Signer<'info>, // 充值用户，支付 lamports
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [b"stack", payer.key.as_ref()],
+        owner = system_program::ID,
+        bump,
+        space = 0
+    )]
+    /// CHECK: This is a PDA verified in constraints.
+    pub pda_stack_account: AccountInfo<'info>, // 质押PDA，WSOL 质押ATA的所有者
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [payer.key.as_ref(), mint.key().as_ref()], // 与 deposit_spl 质押 WSOL 共用仓位
+        bump,
+        space = 8 + StackAccount::INIT_SPACE
+    )]
+    pub stack_account: Account<'info, StackAccount>,
+
+    #[account(
+        address = NATIVE_MINT @ CustomError::MintMismatch,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // WSOL mint
+
+    #[account(
+        seeds = [b"whitelist", mint.key().as_ref()],
+        bump = whitelisted_mint.bump,
+    )]
+    pub whitelisted_mint: Account<'info, WhitelistedMint>, // WSOL 也必须在白名单内
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool", mint.key().as_ref()],
+        bump = reward_pool.bump,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>, // 全局配置，检查暂停状态
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = pda_stack_account,
+        associated_token::token_program = token_program,
+    )]
+    pub stack_account_ata: Box<InterfaceAccount<'info, TokenAccount>>, // 质押PDA的 WSOL ATA
+
+    #[account(seeds = [b"treasury"], bump)]
+    /// CHECK: This is a PDA verified in constraints, only used as the fee ATA authority.
+    pub treasury: UncheckedAccount<'info>, // 手续费金库PDA
+
+    #[account(
+        mut, // 由 add_whitelisted_mint 创建
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>, // WSOL 手续费ATA
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>, // SPL
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
-/** 质押账户结构体 */
+/** 提现 WSOL 仓位并解包结构体 */
+#[derive(Accounts)]
+pub struct WithdrawSplNative<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>, // 仓位用户，接收解包后的 SOL
+    #[account(
+        mut,
+        seeds = [b"stack", payer.key.as_ref()],
+        bump = stack_account.stack_account_pda_pump,
+    )]
+    /// CHECK: This is a PDA verified in constraints.
+    pub pda_stack_account: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [payer.key.as_ref(), mint.key().as_ref()],
+        bump = stack_account.stack_account_pump,
+    )]
+    pub stack_account: Account<'info, StackAccount>,
+
+    #[account(
+        address = NATIVE_MINT @ CustomError::MintMismatch,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // WSOL mint（移出白名单后仍可提现）
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool", mint.key().as_ref()],
+        bump = reward_pool.bump,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(seeds = [b"config"], bump)]
+    /// CHECK: Loaded with Config::load_or_default, may not be initialized yet.
+    pub config: UncheckedAccount<'info>, // 全局配置，未初始化时视为未暂停、不收手续费
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = pda_stack_account,
+        associated_token::token_program = token_program,
+    )]
+    pub stack_account_ata: Box<InterfaceAccount<'info, TokenAccount>>, // 质押PDA的 WSOL ATA
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"unwrap", payer.key.as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = pda_stack_account,
+        token::token_program = token_program,
+    )]
+    pub unwrap_account: Box<InterfaceAccount<'info, TokenAccount>>, // 临时 WSOL 账户，指令内关闭
+
+    #[account(seeds = [b"treasury"], bump)]
+    /// CHECK: This is a PDA verified in constraints, only used as the fee ATA authority.
+    pub treasury: UncheckedAccount<'info>, // 手续费金库PDA
+
+    #[account(
+        mut, // 由 add_whitelisted_mint 创建
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>, // WSOL 手续费ATA
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/** 关闭 SOL 仓位结构体 */
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(mut, signer)]
+    pub user: Signer<'info>, // 仓位用户，接收退回的租金
+    #[account(
+        mut,
+        seeds = [b"sol_stack", user.key.as_ref()],
+        bump = stack_account.stack_account_pda_pump,
+    )]
+    /// CHECK: This is a PDA verified in constraints.
+    pub stack_account_pda: AccountInfo<'info>, // 存储SOL的普通质押账户
+    #[account(
+        mut,
+        seeds = [b"sol_position", user.key.as_ref()],
+        bump = stack_account.stack_account_pump,
+        constraint = stack_account.balance == 0 @ CustomError::PositionNotEmpty, // 余额清空后才能关闭
+        close = user,
+    )]
+    pub stack_account: Account<'info, StackAccount>, // 质押数据账户
+    pub system_program: Program<'info, System>,
+}
+
+/** 关闭 spl 仓位结构体 */
+#[derive(Accounts)]
+pub struct ClosePositionSpl<'info> {
+    #[account(mut, signer)]
+    pub payer: Signer<'info>, // 仓位用户，接收退回的租金
+    #[account(
+        mut,
+        seeds = [b"stack", payer.key.as_ref()],
+        bump = stack_account.stack_account_pda_pump,
+    )]
+    /// CHECK: This is a PDA verified in constraints.
+    pub pda_stack_account: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [payer.key.as_ref(), mint.key().as_ref()],
+        bump = stack_account.stack_account_pump,
+        constraint = stack_account.balance == 0 @ CustomError::PositionNotEmpty, // 余额清空后才能关闭
+        constraint = stack_account.pending_rewards == 0 @ CustomError::UnclaimedRewards, // 先领取奖励
+        close = payer,
+    )]
+    pub stack_account: Account<'info, StackAccount>, // 质押数据账户
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = pda_stack_account,
+        associated_token::token_program = token_program,
+    )]
+    pub stack_account_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+}
+
+/** 迁移旧布局质押账户结构体 */
+#[derive(Accounts)]
+pub struct MigratePosition<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>, // 仓位所有者，支付扩容的租金
+
+    /// CHECK: Old layouts cannot be loaded as StackAccount; the owner is checked here, the PDA in the instruction.
+    #[account(mut, owner = crate::ID)]
+    pub stack_account: UncheckedAccount<'info>, // 待迁移的质押数据账户
+
+    #[account(mut)]
+    pub reward_pool: Option<Account<'info, RewardPool>>, // spl 仓位代币的奖励池，SOL 仓位不传
+
+    pub system_program: Program<'info, System>,
+}
+
+/** 质押账户结构体
+    新字段从 reserved 中分配并提升 version，不需要改变账户大小
+*/
 #[account]
+#[derive(InitSpace)]
 pub struct StackAccount {
     pub balance: u64, // 记录用户的质押金额
     pub stack_account_pump: u8, 
-    pub stack_account_pda_pump: u8
+    pub stack_account_pda_pump: u8,
+    pub lock_start: i64, // 锁定开始时间（unix秒），0表示未锁定
+    pub lock_end: i64,   // 锁定结束时间（unix秒），之前不能提款
+    pub reward_debt: u128,    // 已结算的累计奖励（见 RewardPool.acc_reward_per_share）
+    pub pending_rewards: u64, // 待领取奖励
+    pub version: u8,          // 布局版本，旧布局账户读取为0
+    pub reserved: [u8; 64],   // 预留空间
+}
+
+// 当前质押账户布局版本
+pub const STACK_ACCOUNT_VERSION: u8 = 3;
+// 从该版本起 spl 仓位余额计入 RewardPool.total_staked
+pub const REWARD_STACK_ACCOUNT_VERSION: u8 = 2;
+// 旧布局账户大小（含 discriminator），下标即读取时的版本号
+pub const LEGACY_STACK_ACCOUNT_SPACES: [usize; 3] = [
+    8 + 16,                         // 0：初始版本按 space = 8 + 16 分配，balance 和两个bump 之后有6字节未使用
+    8 + 8 + 1 + 1 + 8 + 8,          // 1：增加锁定时间
+    8 + 8 + 1 + 1 + 8 + 8 + 16 + 8, // 2：增加 reward_debt、pending_rewards
+];
+
+// 最长锁定时长：4年
+pub const MAX_LOCK_DURATION: i64 = 4 * 365 * 24 * 60 * 60;
+
+impl StackAccount {
+    // 读取任意版本的质押账户：按账户大小识别旧布局，缺少的字段按0补齐，version 设为对应的旧版本号
+    pub fn try_deserialize_versioned(data: &[u8]) -> Result<Self> {
+        if data.len() >= 8 + Self::INIT_SPACE {
+            return Self::try_deserialize(&mut &data[..]);
+        }
+        let version = LEGACY_STACK_ACCOUNT_SPACES
+            .iter()
+            .position(|&space| space == data.len())
+            .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        let mut padded = data.to_vec();
+        padded.resize(8 + Self::INIT_SPACE, 0);
+        let mut stack_account = Self::try_deserialize(&mut padded.as_slice())?;
+        stack_account.version = version as u8;
+        Ok(stack_account)
+    }
+
+    // 锁定整个仓位，新的锁定只能延长不能缩短
+    // 锁定会作用于仓位的全部余额，已有未锁定的余额时拒绝，需要先提取
+    pub fn lock(&mut self, now: i64, lock_duration: i64) -> Result<()> {
+        require!(
+            lock_duration > 0 && lock_duration <= MAX_LOCK_DURATION,
+            CustomError::InvalidLockDuration
+        );
+        require!(self.balance == 0 || now < self.lock_end, CustomError::UnlockedBalance);
+        let lock_end = now.checked_add(lock_duration).ok_or(CustomError::MathOverflow)?;
+        if lock_end > self.lock_end {
+            self.lock_start = now;
+            self.lock_end = lock_end;
+        }
+        msg!("Position locked from {} until {}", self.lock_start, self.lock_end);
+        Ok(())
+    }
+
+    // 未锁定的充值不能进入锁定中的仓位，否则会被一起锁定到 lock_end
+    pub fn require_flexible_deposit(&self, now: i64) -> Result<()> {
+        require!(now >= self.lock_end, CustomError::DepositIntoLockedPosition);
+        Ok(())
+    }
+
+    // 检查仓位是否已解锁
+    pub fn require_unlocked(&self, now: i64) -> Result<()> {
+        if now < self.lock_end {
+            msg!("Position is locked until {} ({} seconds remaining)", self.lock_end, self.lock_end - now);
+            return err!(CustomError::PositionLocked);
+        }
+        Ok(())
+    }
 }
 
 /** 自定义异常 */
@@ -302,4 +1312,169 @@ pub struct StackAccount {
 pub enum CustomError {
     #[msg("Insufficient balance for withdrawal.")]
     InsufficientBalance,
+    #[msg("Withdraw amount exceeds the staked balance.")]
+    WithdrawAmountExceedsBalance,
+    #[msg("Signer is not authorized for this operation.")]
+    Unauthorized,
+    #[msg("Token registry is full.")]
+    RegistryFull,
+    #[msg("Position is still locked.")]
+    PositionLocked,
+    #[msg("Lock duration must be positive and at most four years.")]
+    InvalidLockDuration,
+    #[msg("Mint does not match the expected mint.")]
+    MintMismatch,
+    #[msg("Vault does not match the expected vault.")]
+    VaultMismatch,
+    #[msg("No rewards available to claim.")]
+    NoRewardsToClaim,
+    #[msg("Position still holds a balance and cannot be closed.")]
+    PositionNotEmpty,
+    #[msg("Claim pending rewards before closing the position.")]
+    UnclaimedRewards,
+    #[msg("Arithmetic overflow or underflow.")]
+    MathOverflow,
+    #[msg("Amount must be greater than zero.")]
+    ZeroAmount,
+    #[msg("Program is paused.")]
+    ProgramPaused,
+    #[msg("Deposits are disabled in emergency mode.")]
+    DepositsDisabled,
+    #[msg("Fee must be at most 10000 basis points.")]
+    InvalidFee,
+    #[msg("Signer is not a member of the multisig.")]
+    NotMultisigSigner,
+    #[msg("Multisig needs 1 to 10 distinct signers and a threshold between 1 and the signer count.")]
+    InvalidMultisig,
+    #[msg("Proposal instruction has too many accounts or too much data.")]
+    ProposalTooLarge,
+    #[msg("Proposal has already been executed.")]
+    ProposalExecuted,
+    #[msg("Multisig signers changed after the proposal was created.")]
+    StaleProposal,
+    #[msg("Proposal does not have enough approvals.")]
+    NotEnoughApprovals,
+    #[msg("Treasury holds no fees to collect.")]
+    NoFeesToCollect,
+    #[msg("Deposit is too small to mint any shares.")]
+    DepositTooSmall,
+    #[msg("Token account is frozen.")]
+    AccountFrozen,
+    #[msg("Max supply must be greater than zero.")]
+    InvalidMaxSupply,
+    #[msg("Minting would exceed the max supply.")]
+    MaxSupplyExceeded,
+    #[msg("Minting would exceed the per-epoch emission limit.")]
+    EpochEmissionExceeded,
+    #[msg("Minting has been permanently revoked.")]
+    MintingRevoked,
+    #[msg("Burn amount exceeds the vault balance not owed to the user.")]
+    BurnExceedsSurplus,
+    #[msg("Position already uses the current layout.")]
+    PositionAlreadyMigrated,
+    #[msg("Position holds an unlocked balance; withdraw it before locking.")]
+    UnlockedBalance,
+    #[msg("Account is not a legacy position.")]
+    NotLegacyPosition,
+    #[msg("Reward pool does not match the position mint.")]
+    RewardPoolMismatch,
+    #[msg("Position is locked; add to it with a locked deposit.")]
+    DepositIntoLockedPosition,
+    #[msg("Symbol must be 1 to 10 bytes long.")]
+    InvalidSymbol,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    fn position(balance: u64) -> StackAccount {
+        StackAccount {
+            balance,
+            stack_account_pump: 254,
+            stack_account_pda_pump: 253,
+            lock_start: 10,
+            lock_end: 20,
+            reward_debt: 30,
+            pending_rewards: 40,
+            version: STACK_ACCOUNT_VERSION,
+            reserved: [0; 64],
+        }
+    }
+
+    // 按旧版本的字段顺序手工编码，不经过当前的 StackAccount
+    fn legacy_bytes(version: usize) -> Vec<u8> {
+        let mut data = StackAccount::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.extend_from_slice(&[254, 253]);
+        if version == 0 {
+            data.resize(8 + 16, 0); // 初始版本的 space
+        }
+        if version >= 1 {
+            data.extend_from_slice(&10i64.to_le_bytes());
+            data.extend_from_slice(&20i64.to_le_bytes());
+        }
+        if version >= 2 {
+            data.extend_from_slice(&30u128.to_le_bytes());
+            data.extend_from_slice(&40u64.to_le_bytes());
+        }
+        assert_eq!(data.len(), LEGACY_STACK_ACCOUNT_SPACES[version]);
+        data
+    }
+
+    #[test]
+    fn each_legacy_layout_reads_with_its_own_version() {
+        for version in 0..LEGACY_STACK_ACCOUNT_SPACES.len() {
+            let legacy = StackAccount::try_deserialize_versioned(&legacy_bytes(version)).unwrap();
+            assert_eq!(legacy.version as usize, version);
+            assert_eq!(legacy.balance, 1_000);
+            assert_eq!((legacy.stack_account_pump, legacy.stack_account_pda_pump), (254, 253));
+        }
+
+        let baseline = StackAccount::try_deserialize_versioned(&legacy_bytes(0)).unwrap();
+        assert_eq!((baseline.lock_end, baseline.reward_debt, baseline.pending_rewards), (0, 0, 0));
+        let locked = StackAccount::try_deserialize_versioned(&legacy_bytes(1)).unwrap();
+        assert_eq!((locked.lock_start, locked.lock_end, locked.pending_rewards), (10, 20, 0));
+        let rewarded = StackAccount::try_deserialize_versioned(&legacy_bytes(2)).unwrap();
+        assert_eq!((rewarded.lock_end, rewarded.reward_debt, rewarded.pending_rewards), (20, 30, 40));
+    }
+
+    #[test]
+    fn lock_rejects_an_unlocked_balance() {
+        // position 锁定到 20，之后的余额视为未锁定
+        let mut locked = position(1_000);
+        locked.lock(10, 60).unwrap();
+        assert_eq!(locked.lock_end, 70);
+        assert_eq!(position(1_000).lock(20, 60).unwrap_err(), CustomError::UnlockedBalance.into());
+
+        // 空仓位可以开始新的锁定
+        let mut empty = position(0);
+        empty.lock(20, 60).unwrap();
+        assert_eq!((empty.lock_start, empty.lock_end), (20, 80));
+    }
+
+    #[test]
+    fn flexible_deposit_rejects_a_locked_position() {
+        // position 锁定到 20，锁定期内的未锁定充值会被一起锁定
+        let locked = position(1_000);
+        assert_eq!(
+            locked.require_flexible_deposit(19).unwrap_err(),
+            CustomError::DepositIntoLockedPosition.into()
+        );
+        assert!(locked.require_flexible_deposit(20).is_ok());
+    }
+
+    #[test]
+    fn current_layout_round_trips_and_other_sizes_fail() {
+        let mut data = Vec::new();
+        position(7).try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + StackAccount::INIT_SPACE);
+        let current = StackAccount::try_deserialize_versioned(&data).unwrap();
+        assert_eq!((current.version, current.balance), (STACK_ACCOUNT_VERSION, 7));
+
+        for len in [0, 8, LEGACY_STACK_ACCOUNT_SPACES[0] - 1, LEGACY_STACK_ACCOUNT_SPACES[1] + 1, 8 + StackAccount::INIT_SPACE - 1] {
+            assert!(StackAccount::try_deserialize_versioned(&data[..len]).is_err());
+        }
+    }
 }
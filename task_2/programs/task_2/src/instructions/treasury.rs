@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::events::FeesCollectedEvent;
+use crate::instructions::config::Config;
+use crate::instructions::stack::CustomError;
+
+// 手续费金库
+// SOL 手续费存入金库PDA [b"treasury"]（系统账户），spl 手续费存入金库PDA的ATA（每种代币一个）
+// 金库PDA由第一笔 SOL 充值创建；代币手续费ATA由 add_whitelisted_mint（或 init_treasury_ata）创建，用户不支付它的租金
+// 管理员通过 collect_fees / collect_fees_spl 提取
+
+// 提取 SOL 手续费（仅管理员可操作），金库保留免租金的最低余额
+pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+    let treasury = ctx.accounts.treasury.to_account_info();
+    let rent_exempt = Rent::get()?.minimum_balance(0);
+    let amount = treasury.lamports().saturating_sub(rent_exempt);
+    require!(amount > 0, CustomError::NoFeesToCollect);
+
+    let seeds: &[&[u8]] = &[b"treasury", &[ctx.bumps.treasury]];
+    let signer = &[seeds];
+    anchor_lang::system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: treasury,
+                to: ctx.accounts.recipient.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )?;
+
+    msg!("Collected {} lamports of fees to {}", amount, ctx.accounts.recipient.key());
+    emit!(FeesCollectedEvent {
+        mint: Pubkey::default(),
+        recipient: ctx.accounts.recipient.key(),
+        amount,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 提取某种代币的全部手续费（仅管理员可操作）
+pub fn collect_fees_spl(ctx: Context<CollectFeesSpl>) -> Result<()> {
+    let amount = ctx.accounts.treasury_ata.amount;
+    require!(amount > 0, CustomError::NoFeesToCollect);
+
+    let seeds: &[&[u8]] = &[b"treasury", &[ctx.bumps.treasury]];
+    let signer = &[seeds];
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.treasury_ata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.recipient_ata.to_account_info(),
+                authority: ctx.accounts.treasury.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    msg!("Collected {} fees of mint {} to {}", amount, ctx.accounts.mint.key(), ctx.accounts.recipient_ata.key());
+    emit!(FeesCollectedEvent {
+        mint: ctx.accounts.mint.key(),
+        recipient: ctx.accounts.recipient_ata.key(),
+        amount,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 为已在白名单中的代币补建手续费ATA（仅管理员可操作），用于本功能上线前加入白名单的代币
+pub fn init_treasury_ata(ctx: Context<InitTreasuryAta>) -> Result<()> {
+    msg!("Treasury fee account {} ready for mint {}", ctx.accounts.treasury_ata.key(), ctx.accounts.mint.key());
+    Ok(())
+}
+
+/** 提取 SOL 手续费结构体 */
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    pub admin: Signer<'info>, // 配置管理员
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump,
+        owner = system_program::ID,
+    )]
+    /// CHECK: This is a PDA verified in constraints.
+    pub treasury: AccountInfo<'info>, // SOL 手续费金库
+
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>, // 接收手续费的钱包
+
+    pub system_program: Program<'info, System>,
+}
+
+/** 提取 spl 手续费结构体 */
+#[derive(Accounts)]
+pub struct CollectFeesSpl<'info> {
+    pub admin: Signer<'info>, // 配置管理员
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(seeds = [b"treasury"], bump)]
+    /// CHECK: This is a PDA verified in constraints, only used to sign.
+    pub treasury: UncheckedAccount<'info>, // 金库PDA，手续费ATA的所有者
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_ata: InterfaceAccount<'info, TokenAccount>, // 该代币的手续费ATA
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub recipient_ata: InterfaceAccount<'info, TokenAccount>, // 接收手续费的代币账户
+
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+}
+
+/** 创建手续费ATA结构体 */
+#[derive(Accounts)]
+pub struct InitTreasuryAta<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // 配置管理员，支付ATA租金
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(seeds = [b"treasury"], bump)]
+    /// CHECK: This is a PDA verified in constraints, only used as the fee ATA authority.
+    pub treasury: UncheckedAccount<'info>, // 金库PDA，手续费ATA的所有者
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_ata: InterfaceAccount<'info, TokenAccount>, // 该代币的手续费ATA
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
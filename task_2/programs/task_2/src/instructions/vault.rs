@@ -0,0 +1,543 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        burn,             // 销毁份额代币
+        mint_to,          // 铸造份额代币
+        transfer_checked, // 带精度校验的转账（兼容 Token 与 Token-2022）
+        Burn,
+        Mint,
+        MintTo,
+        TokenAccount,
+        TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::events::{DepositSharesEvent, WithdrawSharesEvent};
+use crate::instructions::config::Config;
+use crate::instructions::stack::CustomError;
+use crate::instructions::whitelist::WhitelistedMint;
+
+// 份额金库
+// 每种白名单代币一个共享金库PDA [b"share_vault", mint]，资产存放在金库PDA的ATA中
+// 充值时按 已发行份额 / 金库资产 的比例铸造凭证代币（份额代币，mint PDA [b"receipt", mint]），提现时销毁凭证代币取回资产
+// 直接转入金库ATA的代币（收益、捐赠）按份额比例归所有持有人，凭证代币可以自由转让
+// 与 deposit_spl 的个人仓位相互独立：份额金库没有锁仓和奖励池
+
+// 首次充值时永久锁定的份额，防止首个充值者通过捐赠抬高份额价格（通胀攻击）
+pub const MINIMUM_SHARES: u64 = 1_000;
+// 计算份额时的虚拟份额和虚拟资产，避免除零并进一步提高攻击成本
+pub const VIRTUAL_SHARES: u128 = 1;
+pub const VIRTUAL_ASSETS: u128 = 1;
+// exchange_rate 返回的每份额资产的精度
+pub const RATE_SCALE: u64 = 1_000_000_000;
+
+// 为白名单代币创建份额金库和凭证代币（仅管理员可操作）
+pub fn initialize_share_vault(ctx: Context<InitializeShareVault>) -> Result<()> {
+    let share_vault = &mut ctx.accounts.share_vault;
+    share_vault.mint = ctx.accounts.mint.key();
+    share_vault.receipt_mint = ctx.accounts.receipt_mint.key();
+    share_vault.total_shares = 0;
+    share_vault.bump = ctx.bumps.share_vault;
+
+    msg!("Share vault {} issues receipt mint {}", share_vault.key(), share_vault.receipt_mint);
+    Ok(())
+}
+
+// 用户存入代币，按比例获得凭证代币
+pub fn deposit_shares(ctx: Context<DepositShares>, amount: u64) -> Result<()> {
+    ctx.accounts.config.require_deposits_allowed()?;
+    require!(amount > 0, CustomError::ZeroAmount);
+
+    // 手续费按用户转出数量计算，直接转入该代币的金库ATA
+    let fee = ctx.accounts.config.deposit_fee(amount)?;
+    let net = amount.checked_sub(fee).ok_or(CustomError::MathOverflow)?;
+    require!(net > 0, CustomError::ZeroAmount);
+
+    // 充值前的金库资产，用于计算份额
+    let total_assets = ctx.accounts.vault_ata.amount;
+
+    transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.payer_ata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vault_ata.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ),
+        net,
+        ctx.accounts.mint.decimals,
+    )?;
+    if fee > 0 {
+        transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.payer_ata.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.treasury_ata.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            fee,
+            ctx.accounts.mint.decimals,
+        )?;
+    }
+
+    // Token-2022 转账手续费会从到账数量中扣除，按实际到账数量计算份额
+    ctx.accounts.vault_ata.reload()?;
+    let received = ctx.accounts.vault_ata.amount.checked_sub(total_assets).ok_or(CustomError::MathOverflow)?;
+
+    let share_vault = &ctx.accounts.share_vault;
+    let shares = share_vault.shares_for_deposit(received, total_assets)?;
+    let locked = if share_vault.total_shares == 0 { MINIMUM_SHARES } else { 0 };
+
+    // 由份额金库PDA签名铸造凭证代币
+    let mint_key = ctx.accounts.mint.key();
+    let seeds: &[&[u8]] = &[b"share_vault", mint_key.as_ref(), &[share_vault.bump]];
+    let signer = &[seeds];
+    mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.receipt_mint.to_account_info(),
+                to: ctx.accounts.payer_receipt_ata.to_account_info(),
+                authority: ctx.accounts.share_vault.to_account_info(),
+            },
+            signer,
+        ),
+        shares,
+    )?;
+
+    let share_vault = &mut ctx.accounts.share_vault;
+    share_vault.total_shares = share_vault
+        .total_shares
+        .checked_add(shares)
+        .and_then(|total| total.checked_add(locked))
+        .ok_or(CustomError::MathOverflow)?;
+
+    msg!("Deposit {} of mint {} for {} shares, fee {}, received {}", amount, mint_key, shares, fee, received);
+    emit!(DepositSharesEvent {
+        user: ctx.accounts.payer.key(),
+        mint: mint_key,
+        amount,
+        fee,
+        received,
+        shares,
+        total_assets: ctx.accounts.vault_ata.amount,
+        total_shares: ctx.accounts.share_vault.total_shares,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 用户销毁凭证代币，按比例取回资产
+pub fn withdraw_shares(ctx: Context<WithdrawShares>, shares: u64) -> Result<()> {
+    let config = Config::load_or_default(&ctx.accounts.config)?;
+    config.require_withdrawals_allowed()?;
+    require!(shares > 0, CustomError::ZeroAmount);
+    require!(shares <= ctx.accounts.payer_receipt_ata.amount, CustomError::InsufficientBalance);
+
+    let share_vault = &ctx.accounts.share_vault;
+    let amount = share_vault.assets_for_shares(shares, ctx.accounts.vault_ata.amount)?;
+    require!(amount > 0, CustomError::ZeroAmount);
+
+    // 手续费从取回数量中扣除，转入该代币的金库ATA
+    let fee = config.withdraw_fee(amount)?;
+    let net = amount.checked_sub(fee).ok_or(CustomError::MathOverflow)?;
+
+    burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.receipt_mint.to_account_info(),
+                from: ctx.accounts.payer_receipt_ata.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ),
+        shares,
+    )?;
+
+    // 由份额金库PDA签名转出资产
+    let mint_key = ctx.accounts.mint.key();
+    let seeds: &[&[u8]] = &[b"share_vault", mint_key.as_ref(), &[share_vault.bump]];
+    let signer = &[seeds];
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_ata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.user_ata.to_account_info(),
+                authority: ctx.accounts.share_vault.to_account_info(),
+            },
+            signer,
+        ),
+        net,
+        ctx.accounts.mint.decimals,
+    )?;
+    if fee > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault_ata.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.treasury_ata.to_account_info(),
+                    authority: ctx.accounts.share_vault.to_account_info(),
+                },
+                signer,
+            ),
+            fee,
+            ctx.accounts.mint.decimals,
+        )?;
+    }
+
+    let share_vault = &mut ctx.accounts.share_vault;
+    share_vault.total_shares = share_vault.total_shares.checked_sub(shares).ok_or(CustomError::MathOverflow)?;
+    ctx.accounts.vault_ata.reload()?;
+
+    msg!("Redeemed {} shares of mint {} for {}, fee {}", shares, mint_key, net, fee);
+    emit!(WithdrawSharesEvent {
+        user: ctx.accounts.payer.key(),
+        mint: mint_key,
+        shares,
+        amount,
+        fee,
+        total_assets: ctx.accounts.vault_ata.amount,
+        total_shares: ctx.accounts.share_vault.total_shares,
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+// 查询份额金库的兑换比例，通过返回值提供给客户端（模拟交易即可读取）
+pub fn exchange_rate(ctx: Context<QueryShareVault>) -> Result<ExchangeRate> {
+    let share_vault = &ctx.accounts.share_vault;
+    let total_assets = ctx.accounts.vault_ata.amount;
+    Ok(ExchangeRate {
+        total_assets,
+        total_shares: share_vault.total_shares,
+        assets_per_share: share_vault.assets_per_share(total_assets)?,
+    })
+}
+
+/** 创建份额金库结构体 */
+#[derive(Accounts)]
+pub struct InitializeShareVault<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>, // 配置管理员，支付账户租金
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>, // 金库资产代币
+
+    #[account(
+        seeds = [b"whitelist", mint.key().as_ref()],
+        bump = whitelisted_mint.bump,
+    )]
+    pub whitelisted_mint: Account<'info, WhitelistedMint>, // 只有白名单内的代币可以创建金库
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"share_vault", mint.key().as_ref()],
+        bump,
+        space = 8 + ShareVault::INIT_SPACE,
+    )]
+    pub share_vault: Account<'info, ShareVault>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"receipt", mint.key().as_ref()],
+        bump,
+        mint::decimals = mint.decimals, // 与资产代币精度一致，初始按 1:1 兑换
+        mint::authority = share_vault,
+        mint::token_program = token_program,
+    )]
+    pub receipt_mint: Box<InterfaceAccount<'info, Mint>>, // 凭证代币
+
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = mint,
+        associated_token::authority = share_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_ata: Box<InterfaceAccount<'info, TokenAccount>>, // 金库资产ATA
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/** 份额金库充值结构体 */
+#[derive(Accounts)]
+pub struct DepositShares<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>, // 充值用户
+
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [b"whitelist", mint.key().as_ref()],
+        bump = whitelisted_mint.bump,
+    )]
+    pub whitelisted_mint: Account<'info, WhitelistedMint>, // 移出白名单后不能继续充值
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>, // 全局配置，检查暂停状态和手续费
+
+    #[account(
+        mut,
+        seeds = [b"share_vault", mint.key().as_ref()],
+        bump = share_vault.bump,
+        has_one = receipt_mint,
+    )]
+    pub share_vault: Account<'info, ShareVault>,
+
+    #[account(mut)]
+    pub receipt_mint: Box<InterfaceAccount<'info, Mint>>, // 凭证代币
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = payer,
+        token::token_program = token_program,
+        constraint = !payer_ata.is_frozen() @ CustomError::AccountFrozen, // 冻结的代币账户不能充值
+    )]
+    pub payer_ata: Box<InterfaceAccount<'info, TokenAccount>>, // 用户的资产代币账户
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = receipt_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub payer_receipt_ata: Box<InterfaceAccount<'info, TokenAccount>>, // 用户的凭证代币ATA
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = share_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_ata: Box<InterfaceAccount<'info, TokenAccount>>, // 金库资产ATA
+
+    #[account(seeds = [b"treasury"], bump)]
+    /// CHECK: This is a PDA verified in constraints, only used as the fee ATA authority.
+    pub treasury: UncheckedAccount<'info>, // 手续费金库PDA
+
+    #[account(
+        mut, // 由 add_whitelisted_mint 创建
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>, // 该代币的手续费ATA
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/** 份额金库提现结构体 */
+#[derive(Accounts)]
+pub struct WithdrawShares<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>, // 凭证代币持有人
+
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>, // 移出白名单后仍可提现
+
+    #[account(seeds = [b"config"], bump)]
+    /// CHECK: Loaded with Config::load_or_default, may not be initialized yet.
+    pub config: UncheckedAccount<'info>, // 全局配置，未初始化时视为未暂停、不收手续费
+
+    #[account(
+        mut,
+        seeds = [b"share_vault", mint.key().as_ref()],
+        bump = share_vault.bump,
+        has_one = receipt_mint,
+    )]
+    pub share_vault: Account<'info, ShareVault>,
+
+    #[account(mut)]
+    pub receipt_mint: Box<InterfaceAccount<'info, Mint>>, // 凭证代币
+
+    #[account(
+        mut,
+        token::mint = receipt_mint,
+        token::authority = payer,
+        token::token_program = token_program,
+    )]
+    pub payer_receipt_ata: Box<InterfaceAccount<'info, TokenAccount>>, // 用户的凭证代币账户
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub user_ata: Box<InterfaceAccount<'info, TokenAccount>>, // 接收资产的代币账户
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = share_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_ata: Box<InterfaceAccount<'info, TokenAccount>>, // 金库资产ATA
+
+    #[account(seeds = [b"treasury"], bump)]
+    /// CHECK: This is a PDA verified in constraints, only used as the fee ATA authority.
+    pub treasury: UncheckedAccount<'info>, // 手续费金库PDA
+
+    #[account(
+        mut, // 由 add_whitelisted_mint 创建
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>, // 该代币的手续费ATA
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/** 查询兑换比例结构体 */
+#[derive(Accounts)]
+pub struct QueryShareVault<'info> {
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [b"share_vault", mint.key().as_ref()], bump = share_vault.bump)]
+    pub share_vault: Account<'info, ShareVault>,
+
+    #[account(
+        associated_token::mint = mint,
+        associated_token::authority = share_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_ata: InterfaceAccount<'info, TokenAccount>, // 金库资产ATA
+
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+}
+
+/** 份额金库账户结构体 */
+#[account]
+#[derive(InitSpace)]
+pub struct ShareVault {
+    pub mint: Pubkey,         // 金库资产代币
+    pub receipt_mint: Pubkey, // 凭证代币
+    pub total_shares: u64,    // 已发行份额，含首次充值永久锁定的 MINIMUM_SHARES
+    pub bump: u8,
+}
+
+/** exchange_rate 的返回值 */
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ExchangeRate {
+    pub total_assets: u64,     // 金库资产（含收益和捐赠）
+    pub total_shares: u64,     // 已发行份额
+    pub assets_per_share: u64, // 每 RATE_SCALE 份额可兑换的资产
+}
+
+// a * b / c，向下取整
+fn mul_div(a: u128, b: u128, c: u128) -> Result<u64> {
+    let value = a.checked_mul(b).and_then(|v| v.checked_div(c)).ok_or(CustomError::MathOverflow)?;
+    Ok(u64::try_from(value).map_err(|_| CustomError::MathOverflow)?)
+}
+
+impl ShareVault {
+    // 存入 assets 可获得的份额（向下取整，有利于金库）
+    // 首次充值按 1:1 计算，并扣除永久锁定的 MINIMUM_SHARES
+    pub fn shares_for_deposit(&self, assets: u64, total_assets: u64) -> Result<u64> {
+        if self.total_shares == 0 {
+            require!(assets > MINIMUM_SHARES, CustomError::DepositTooSmall);
+            return Ok(assets - MINIMUM_SHARES);
+        }
+        let shares = mul_div(
+            assets as u128,
+            self.total_shares as u128 + VIRTUAL_SHARES,
+            total_assets as u128 + VIRTUAL_ASSETS,
+        )?;
+        require!(shares > 0, CustomError::DepositTooSmall);
+        Ok(shares)
+    }
+
+    // 赎回 shares 可取回的资产（向下取整，有利于金库）
+    pub fn assets_for_shares(&self, shares: u64, total_assets: u64) -> Result<u64> {
+        mul_div(
+            shares as u128,
+            total_assets as u128 + VIRTUAL_ASSETS,
+            self.total_shares as u128 + VIRTUAL_SHARES,
+        )
+    }
+
+    // 每 RATE_SCALE 份额可兑换的资产，尚未发行份额时按 1:1
+    pub fn assets_per_share(&self, total_assets: u64) -> Result<u64> {
+        if self.total_shares == 0 {
+            return Ok(RATE_SCALE);
+        }
+        self.assets_for_shares(RATE_SCALE, total_assets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault(total_shares: u64) -> ShareVault {
+        ShareVault {
+            mint: Pubkey::default(),
+            receipt_mint: Pubkey::default(),
+            total_shares,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn first_deposit_locks_minimum_shares() {
+        let vault = vault(0);
+        assert_eq!(vault.shares_for_deposit(MINIMUM_SHARES, 0).unwrap_err(), CustomError::DepositTooSmall.into());
+        assert_eq!(vault.shares_for_deposit(10_000, 0).unwrap(), 10_000 - MINIMUM_SHARES);
+        assert_eq!(vault.assets_per_share(0).unwrap(), RATE_SCALE);
+    }
+
+    #[test]
+    fn donations_are_shared_pro_rata() {
+        // 10_000 份额对应 10_000 资产，之后捐赠 5_000
+        let vault = vault(10_000);
+        assert_eq!(vault.assets_for_shares(2_000, 15_000).unwrap(), 2_999);
+        // 新充值按当前比例获得份额，向下取整
+        assert_eq!(vault.shares_for_deposit(3_000, 15_000).unwrap(), 2_000);
+        assert_eq!(vault.assets_per_share(15_000).unwrap(), 1_499_950_004);
+    }
+
+    #[test]
+    fn inflation_attack_is_not_profitable() {
+        // 攻击者首次充值 1_001 只得到 1 份额，再向金库捐赠 1_000_000
+        let attacker = vault(0).shares_for_deposit(1_001, 0).unwrap();
+        assert_eq!(attacker, 1);
+        let vault = vault(1_001);
+        let total_assets = 1_001 + 1_000_000;
+        // 受害者充值 1_000 仍能获得份额，攻击者的 1 份额只值约 1_000
+        let victim = vault.shares_for_deposit(1_000, total_assets).unwrap();
+        assert!(victim > 0);
+        assert!(vault.assets_for_shares(attacker, total_assets).unwrap() < 1_001);
+        // 小额充值无法获得份额时直接失败，而不是把资产送给已有持有人
+        assert_eq!(vault.shares_for_deposit(999, total_assets).unwrap_err(), CustomError::DepositTooSmall.into());
+    }
+}
@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::instructions::reward::RewardPool;
+use crate::instructions::stack::CustomError;
+use crate::program::Task2;
+
+// 将mint加入质押白名单（仅程序升级权限账户可操作），同时创建该代币的手续费ATA，用户充值和提现时不再支付它的租金
+pub fn add_whitelisted_mint(ctx: Context<AddWhitelistedMint>) -> Result<()> {
+    let whitelisted_mint = &mut ctx.accounts.whitelisted_mint;
+    whitelisted_mint.mint = ctx.accounts.mint.key();
+    whitelisted_mint.bump = ctx.bumps.whitelisted_mint;
+
+    // 首次加入白名单时创建奖励池，默认不发放奖励
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    if reward_pool.stake_mint == Pubkey::default() {
+        reward_pool.admin = ctx.accounts.authority.key();
+        reward_pool.stake_mint = ctx.accounts.mint.key();
+        reward_pool.last_update_ts = Clock::get()?.unix_timestamp;
+        reward_pool.bump = ctx.bumps.reward_pool;
+    }
+
+    msg!("Mint {} added to stake whitelist", ctx.accounts.mint.key());
+    Ok(())
+}
+
+// 将mint移出质押白名单，已有仓位仍可提现
+pub fn remove_whitelisted_mint(ctx: Context<RemoveWhitelistedMint>) -> Result<()> {
+    msg!("Mint {} removed from stake whitelist", ctx.accounts.whitelisted_mint.mint);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AddWhitelistedMint<'info> {
+    #[account(mut, signer)]
+    pub authority: Signer<'info>, // 程序升级权限账户
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>, // 允许质押的代币（Token 或 Token-2022）
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"whitelist", mint.key().as_ref()],
+        bump,
+        space = 8 + WhitelistedMint::INIT_SPACE,
+    )]
+    pub whitelisted_mint: Account<'info, WhitelistedMint>,
+
+    #[account(
+        init_if_needed, // 重新加入白名单时沿用原奖励池
+        payer = authority,
+        seeds = [b"reward_pool", mint.key().as_ref()],
+        bump,
+        space = 8 + RewardPool::INIT_SPACE,
+    )]
+    pub reward_pool: Account<'info, RewardPool>, // 该代币的奖励池
+
+    #[account(seeds = [b"treasury"], bump)]
+    /// CHECK: This is a PDA verified in constraints, only used as the fee ATA authority.
+    pub treasury: UncheckedAccount<'info>, // 手续费金库PDA
+
+    #[account(
+        init_if_needed, // 重新加入白名单时沿用原手续费ATA
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_ata: InterfaceAccount<'info, TokenAccount>, // 该代币的手续费ATA
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, Task2>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ CustomError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>, // SPL Token 或 Token-2022
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveWhitelistedMint<'info> {
+    #[account(mut, signer)]
+    pub authority: Signer<'info>, // 程序升级权限账户
+
+    #[account(
+        mut,
+        close = authority, // 关闭白名单账户，租金退回
+        seeds = [b"whitelist", whitelisted_mint.mint.as_ref()],
+        bump = whitelisted_mint.bump,
+    )]
+    pub whitelisted_mint: Account<'info, WhitelistedMint>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, Task2>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ CustomError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+}
+
+/** 质押白名单账户结构体 */
+#[account]
+#[derive(InitSpace)]
+pub struct WhitelistedMint {
+    pub mint: Pubkey, // 允许质押的代币
+    pub bump: u8,
+}
@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
+pub mod events; // 事件
 pub mod instructions; // 目录
-use instructions::{spl::*, stack::*}; // 模块
+use instructions::{config::*, multisig::*, reward::*, spl::*, stack::*, treasury::*, vault::*, whitelist::*}; // 模块
 
 declare_id!("9fFVjtHSekgUgzAvrPrN2NptvQLbwGsTBizDZsxsAAFr");
 
@@ -8,11 +9,66 @@ declare_id!("9fFVjtHSekgUgzAvrPrN2NptvQLbwGsTBizDZsxsAAFr");
     掌握spl-token的基本用法
     任务描述
     在Task1的基础上，将存solana更改为储存spl token
+    同一个程序同时提供 SOL 质押（deposit/withdraw）与 spl 质押（deposit_spl/withdraw_spl）
+    每个用户每种白名单代币单独记录仓位，不同代币余额互不混合
+    初始版本按 [payer] 记录的 [b"mint"] 代币仓位由 withdraw_spl_legacy 提取并关闭，SOL 仓位使用 [b"sol_position", user]
+    质押账户带布局版本和预留空间，旧布局账户由用户 migrate_position 扩容（补交租金）后继续使用
+    deposit_spl_native / withdraw_spl_native 直接收发 SOL，在质押PDA的 WSOL ATA 中自动包装和解包
+    管理员 create_token 按 symbol 创建任意精度的mint，并登记到代币注册表
+    create_token 时确定总供应量上限和可选的每 epoch 铸币上限，mint_spl 按 Mint.supply 检查；revoke_mint_authority 永久撤销铸币
+    持有人 burn_spl 销毁自己的代币；管理员 burn_from_vault 销毁质押PDA的ATA中超出用户仓位的代币，两者都计入 MintConfig.burned
+    管理员可以 update_token_metadata 修改元数据，freeze_metadata 永久锁定元数据
+    管理员可以用mint的冻结权限 freeze_account / thaw_account 冻结和解冻任意持有人的代币账户，冻结的账户不能充值
+    质押按奖励池的发放速率累计奖励代币，claim_rewards 领取
+    全局配置可以暂停充值、铸币和提现；应急模式下只允许提现
+    充值和提现按配置的万分比收取手续费，存入金库PDA（SOL）或金库ATA（spl，加入白名单时由管理员创建），管理员 collect_fees 提取
+    份额金库 deposit_shares 按份额比例铸造可转让的凭证代币，withdraw_shares 销毁凭证取回资产，金库收益按份额分配
+    管理员和铸币权限都通过 提名/接受 两步移交，可以移交给多签签名PDA，由 M-of-N 多签提案执行管理操作
 */
 #[program]
 pub mod task_2 {
     use super::*;
 
+    pub fn initialize_config(ctx: Context<InitializeConfig>, params: ConfigParams) -> Result<()> {
+        crate::instructions::config::initialize_config(ctx, params)
+    }
+
+    pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+        crate::instructions::config::set_paused(ctx, paused)
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        crate::instructions::config::update_config(ctx, params)
+    }
+
+    pub fn propose_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+        crate::instructions::config::propose_admin(ctx, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        crate::instructions::config::accept_admin(ctx)
+    }
+
+    pub fn create_multisig(ctx: Context<CreateMultisig>, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        crate::instructions::multisig::create_multisig(ctx, signers, threshold)
+    }
+
+    pub fn set_multisig_signers(ctx: Context<SetMultisigSigners>, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        crate::instructions::multisig::set_multisig_signers(ctx, signers, threshold)
+    }
+
+    pub fn create_proposal(ctx: Context<CreateProposal>, instruction: ProposalInstruction) -> Result<()> {
+        crate::instructions::multisig::create_proposal(ctx, instruction)
+    }
+
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        crate::instructions::multisig::approve_proposal(ctx)
+    }
+
+    pub fn execute_proposal<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>) -> Result<()> {
+        crate::instructions::multisig::execute_proposal(ctx)
+    }
+
     pub fn create_token(ctx: Context<CreateSpl>, metadata: InitTokenParams) -> Result<()> {
         msg!("test log");
         crate::instructions::spl::create_token(ctx, metadata)
@@ -22,11 +78,139 @@ pub mod task_2 {
         crate::instructions::spl::mint_spl(ctx, amount)
     }
 
+    pub fn burn_spl(ctx: Context<BurnSpl>, amount: u64) -> Result<()> {
+        crate::instructions::spl::burn_spl(ctx, amount)
+    }
+
+    pub fn burn_from_vault(ctx: Context<BurnFromVault>, amount: u64) -> Result<()> {
+        crate::instructions::spl::burn_from_vault(ctx, amount)
+    }
+
+    pub fn revoke_mint_authority(ctx: Context<RevokeMintAuthority>) -> Result<()> {
+        crate::instructions::spl::revoke_mint_authority(ctx)
+    }
+
+    pub fn update_token_metadata(ctx: Context<UpdateTokenMetadata>, params: UpdateTokenMetadataParams) -> Result<()> {
+        crate::instructions::spl::update_token_metadata(ctx, params)
+    }
+
+    pub fn freeze_metadata(ctx: Context<UpdateTokenMetadata>) -> Result<()> {
+        crate::instructions::spl::freeze_metadata(ctx)
+    }
+
+    pub fn freeze_account(ctx: Context<FreezeTokenAccount>) -> Result<()> {
+        crate::instructions::spl::freeze_account(ctx)
+    }
+
+    pub fn thaw_account(ctx: Context<FreezeTokenAccount>) -> Result<()> {
+        crate::instructions::spl::thaw_account(ctx)
+    }
+
+    pub fn propose_mint_authority(ctx: Context<ProposeMintAuthority>, new_authority: Pubkey) -> Result<()> {
+        crate::instructions::spl::propose_mint_authority(ctx, new_authority)
+    }
+
+    pub fn accept_mint_authority(ctx: Context<AcceptMintAuthority>) -> Result<()> {
+        crate::instructions::spl::accept_mint_authority(ctx)
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        crate::instructions::stack::deposit(ctx, amount)
+    }
+
+    pub fn deposit_locked(ctx: Context<Deposit>, amount: u64, lock_duration: i64) -> Result<()> {
+        crate::instructions::stack::deposit_locked(ctx, amount, lock_duration)
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+        crate::instructions::stack::withdraw(ctx)
+    }
+
+    pub fn withdraw_amount(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        crate::instructions::stack::withdraw_amount(ctx, amount)
+    }
+
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        crate::instructions::stack::close_position(ctx)
+    }
+
+    pub fn add_whitelisted_mint(ctx: Context<AddWhitelistedMint>) -> Result<()> {
+        crate::instructions::whitelist::add_whitelisted_mint(ctx)
+    }
+
+    pub fn remove_whitelisted_mint(ctx: Context<RemoveWhitelistedMint>) -> Result<()> {
+        crate::instructions::whitelist::remove_whitelisted_mint(ctx)
+    }
+
     pub fn deposit_spl(ctx: Context<DepositSpl>, amount: u64) -> Result<()> {
         crate::instructions::stack::deposit_spl(ctx, amount)
     }
 
+    pub fn deposit_spl_locked(ctx: Context<DepositSpl>, amount: u64, lock_duration: i64) -> Result<()> {
+        crate::instructions::stack::deposit_spl_locked(ctx, amount, lock_duration)
+    }
+
     pub fn withdraw_spl(ctx: Context<WithdrawSpl>) -> Result<()>{
         crate::instructions::stack::withdraw_spl(ctx)
     }
+
+    pub fn withdraw_spl_legacy(ctx: Context<WithdrawSplLegacy>) -> Result<()> {
+        crate::instructions::stack::withdraw_spl_legacy(ctx)
+    }
+
+    pub fn deposit_spl_native(ctx: Context<DepositSplNative>, amount: u64) -> Result<()> {
+        crate::instructions::stack::deposit_spl_native(ctx, amount)
+    }
+
+    pub fn withdraw_spl_native(ctx: Context<WithdrawSplNative>) -> Result<()> {
+        crate::instructions::stack::withdraw_spl_native(ctx)
+    }
+
+    pub fn migrate_position(ctx: Context<MigratePosition>, mint: Option<Pubkey>) -> Result<()> {
+        crate::instructions::stack::migrate_position(ctx, mint)
+    }
+
+    pub fn close_position_spl(ctx: Context<ClosePositionSpl>) -> Result<()> {
+        crate::instructions::stack::close_position_spl(ctx)
+    }
+
+    pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+        crate::instructions::treasury::collect_fees(ctx)
+    }
+
+    pub fn init_treasury_ata(ctx: Context<InitTreasuryAta>) -> Result<()> {
+        crate::instructions::treasury::init_treasury_ata(ctx)
+    }
+
+    pub fn collect_fees_spl(ctx: Context<CollectFeesSpl>) -> Result<()> {
+        crate::instructions::treasury::collect_fees_spl(ctx)
+    }
+
+    pub fn initialize_share_vault(ctx: Context<InitializeShareVault>) -> Result<()> {
+        crate::instructions::vault::initialize_share_vault(ctx)
+    }
+
+    pub fn deposit_shares(ctx: Context<DepositShares>, amount: u64) -> Result<()> {
+        crate::instructions::vault::deposit_shares(ctx, amount)
+    }
+
+    pub fn withdraw_shares(ctx: Context<WithdrawShares>, shares: u64) -> Result<()> {
+        crate::instructions::vault::withdraw_shares(ctx, shares)
+    }
+
+    pub fn exchange_rate(ctx: Context<QueryShareVault>) -> Result<ExchangeRate> {
+        crate::instructions::vault::exchange_rate(ctx)
+    }
+
+    pub fn configure_reward_pool(ctx: Context<ConfigureRewardPool>, reward_rate: u64) -> Result<()> {
+        crate::instructions::reward::configure_reward_pool(ctx, reward_rate)
+    }
+
+    pub fn fund_reward_pool(ctx: Context<FundRewardPool>, amount: u64) -> Result<()> {
+        crate::instructions::reward::fund_reward_pool(ctx, amount)
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        crate::instructions::reward::claim_rewards(ctx)
+    }
 }
@@ -10,9 +10,19 @@ import {
 } from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID,
+  NATIVE_MINT,
+  ExtensionType,
   getAssociatedTokenAddress,
-  getAccount
+  getAccount,
+  getMint,
+  getMintLen,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializeMintInstruction,
+  createAssociatedTokenAccountIdempotent,
+  mintTo,
+  transfer
 } from "@solana/spl-token";
 import { Metadata } from "@metaplex-foundation/mpl-token-metadata";
 import { assert } from "chai";
@@ -27,6 +37,8 @@ describe("task_2", () => {
   const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
     "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s",
   );
+  // 不限总量的供应量上限（u64 最大值）
+  const UNCAPPED_SUPPLY = new anchor.BN("18446744073709551615");
 
   // 测试用户钱包
   const keypairPath = require("os").homedir() + "/.config/solana/id.json";
@@ -41,25 +53,63 @@ describe("task_2", () => {
   let pdaStackAccountPda: PublicKey;
 
   // mint账户
+  const tokenSymbol = "TT1";
   let mintPda: PublicKey;
   let mintBump: number;
+  let mintConfigPda: PublicKey;
+  let registryPda: PublicKey;
 
   // mint - user ATA账户
   let userAta: PublicKey;
   // mint - stack ATA账户
   let stackAta: PublicKey;
 
+  // mint白名单账户
+  let whitelistedMintPda: PublicKey;
+  // 奖励池账户
+  let rewardPoolPda: PublicKey;
+  // 奖励代币（TT6）
+  let rewardMintPda: PublicKey;
+  let rewardMintConfigPda: PublicKey;
+
+
+  // 全局配置账户
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+
+  // 手续费金库，spl 手续费存入金库的ATA（未显式传入时由 anchor 按 mint 推导）
+  const [treasuryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("treasury")],
+    program.programId
+  );
+
+  // 程序升级权限数据账户
+  const [programData] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111"),
+  );
 
   // 初始化
   before(async () => {
     // mint账户
     const [pda, bump] = await PublicKey.findProgramAddressSync(
-      [Buffer.from("mint")],
+      [Buffer.from("mint"), Buffer.from(tokenSymbol)],
       program.programId
     );
     mintPda = pda;
     mintBump = bump;
 
+    [mintConfigPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_config"), mintPda.toBuffer()],
+      program.programId
+    );
+    [registryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry")],
+      program.programId
+    );
+
     // user ATA 账户
     [userAta] = await PublicKey.findProgramAddressSync(
       [user.publicKey.toBuffer(), TOKEN_PROGRAM_ID.toBuffer(), mintPda.toBuffer() ],     
@@ -68,7 +118,7 @@ describe("task_2", () => {
 
     // stack PDA
     [stackAccountPda] = await PublicKey.findProgramAddressSync(
-      [user.publicKey.toBuffer()],
+      [user.publicKey.toBuffer(), mintPda.toBuffer()],
       program.programId
     );
 
@@ -82,9 +132,75 @@ describe("task_2", () => {
       ASSOCIATED_TOKEN_PROGRAM_ID
     );
 
+    [whitelistedMintPda] = await PublicKey.findProgramAddressSync(
+      [Buffer.from("whitelist"), mintPda.toBuffer()],
+      program.programId
+    );
+
+    [rewardPoolPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_pool"), mintPda.toBuffer()],
+      program.programId
+    );
+
+  });
+
+  // 按 symbol 创建代币，返回 mint 和 mint配置账户
+  const createToken = async (
+    symbol: string,
+    decimals: number,
+    maxSupply: anchor.BN = UNCAPPED_SUPPLY,
+    epochEmission: anchor.BN = new anchor.BN(0),
+  ) => {
+    const [mint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mint"), Buffer.from(symbol)],
+      program.programId
+    );
+    const [mintConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_config"), mint.toBuffer()],
+      program.programId
+    );
+    const [metadata] = PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID,
+    );
+    await program.methods
+      .createToken({ name: `Task Token ${symbol}`, symbol, uri: "", decimals, maxSupply, epochEmission })
+      .accounts({
+        payer: user.publicKey,
+        config: configPda,
+        mint,
+        mintConfig,
+        registry: registryPda,
+        metadata,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    return { mint, mintConfig };
+  };
+
+  it("Is initialize config!", async () => {
+    // 部署钱包为升级权限账户，初始化后成为管理员
+    await program.methods
+    .initializeConfig({ emergency: false, depositFeeBps: 0, withdrawFeeBps: 0 })
+    .accounts({
+      authority: user.publicKey,
+      config: configPda,
+      program: program.programId,
+      programData: programData,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+
+    const config = await program.account.config.fetch(configPda);
+    assert.isTrue(config.admin.equals(user.publicKey));
+    assert.isFalse(config.paused);
+    assert.isFalse(config.emergency);
   });
 
-  
   it("Is createToken and mint!", async () => {
     // metadata账户
     const [metadataPda] = await PublicKey.findProgramAddressSync(
@@ -98,9 +214,11 @@ describe("task_2", () => {
 
     const metadata = {
       name: "Task Token 1",
-      symbol: "TT1",
+      symbol: tokenSymbol,
       uri: "https://raw.githubusercontent.com/solana-developers/opos-asset/main/assets/DeveloperPortal/metadata.json",
       decimals: 9,
+      maxSupply: UNCAPPED_SUPPLY,
+      epochEmission: new anchor.BN(0),
     };
 
     // 创建token
@@ -108,7 +226,10 @@ describe("task_2", () => {
       .createToken(metadata)
       .accounts({
         payer: user.publicKey,
+        config: configPda,
         mint: mintPda,
+        mintConfig: mintConfigPda,
+        registry: registryPda,
         metadata: metadataPda,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         systemProgram: SystemProgram.programId,
@@ -125,8 +246,11 @@ describe("task_2", () => {
     .mintSpl(new anchor.BN(amount))
     .accounts({
       payer: user.publicKey,
+      mintConfig: mintConfigPda,
       mint: mintPda,
-      payerAta: userAta,
+      recipient: user.publicKey,
+      recipientAta: userAta,
+      config: configPda,
       systemProgram: SystemProgram.programId,
       tokenProgram: TOKEN_PROGRAM_ID,
       token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
@@ -141,6 +265,150 @@ describe("task_2", () => {
     console.log("userAtaAddress ", userAta, " token balance:", ataAccount.amount.toString())
   });
 
+  it("mintSpl rejects unauthorized callers", async () => {
+    const stranger = Keypair.generate();
+    const airdropSig = await program.provider.connection.requestAirdrop(stranger.publicKey, LAMPORTS_PER_SOL);
+    await program.provider.connection.confirmTransaction(airdropSig);
+    const strangerAta = await getAssociatedTokenAddress(mintPda, stranger.publicKey);
+
+    try {
+      await program.methods
+      .mintSpl(new anchor.BN(1000))
+      .accounts({
+        payer: stranger.publicKey,
+        mintConfig: mintConfigPda,
+        mint: mintPda,
+        recipient: stranger.publicKey,
+        recipientAta: strangerAta,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .signers([stranger])
+      .rpc();
+      assert.fail("mint by a non-authority should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "Unauthorized");
+    }
+  });
+
+  it("createToken rejects non-admin callers", async () => {
+    const stranger = Keypair.generate();
+    const airdropSig = await program.provider.connection.requestAirdrop(stranger.publicKey, LAMPORTS_PER_SOL);
+    await program.provider.connection.confirmTransaction(airdropSig);
+    const symbol = "SPAM";
+    const [mint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mint"), Buffer.from(symbol)],
+      program.programId
+    );
+    const [mintConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_config"), mint.toBuffer()],
+      program.programId
+    );
+    const [metadata] = PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID,
+    );
+
+    try {
+      await program.methods
+      .createToken({ name: "Spam", symbol, uri: "", decimals: 0, maxSupply: UNCAPPED_SUPPLY, epochEmission: new anchor.BN(0) })
+      .accounts({
+        payer: stranger.publicKey,
+        config: configPda,
+        mint,
+        mintConfig,
+        registry: registryPda,
+        metadata,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .signers([stranger])
+      .rpc();
+      assert.fail("createToken by a non-admin should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "Unauthorized");
+    }
+  });
+
+  it("Is createToken with custom decimals and registry!", async () => {
+    const symbol = "TT6";
+    const [mint6] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mint"), Buffer.from(symbol)],
+      program.programId
+    );
+    const [mintConfig6] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_config"), mint6.toBuffer()],
+      program.programId
+    );
+    rewardMintPda = mint6;
+    rewardMintConfigPda = mintConfig6;
+    const [metadata6] = PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint6.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID,
+    );
+
+    await program.methods
+      .createToken({
+        name: "Task Token 6",
+        symbol,
+        uri: "https://raw.githubusercontent.com/solana-developers/opos-asset/main/assets/DeveloperPortal/metadata.json",
+        decimals: 6,
+        maxSupply: UNCAPPED_SUPPLY,
+        epochEmission: new anchor.BN(0),
+      })
+      .accounts({
+        payer: user.publicKey,
+        mint: mint6,
+        mintConfig: mintConfig6,
+        registry: registryPda,
+        metadata: metadata6,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    // 精度来自参数
+    const mintAccount = await getMint(program.provider.connection, mint6);
+    assert.strictEqual(mintAccount.decimals, 6);
+
+    // 注册表可枚举全部mint
+    const registry = await program.account.tokenRegistry.fetch(registryPda);
+    const mints = registry.mints.map((m) => m.toBase58());
+    assert.include(mints, mintPda.toBase58());
+    assert.include(mints, mint6.toBase58());
+  });
+
+  it("Is whitelist mint!", async () => {
+    const tx = await program.methods
+    .addWhitelistedMint()
+    .accounts({
+      authority: user.publicKey,
+      mint: mintPda,
+      whitelistedMint: whitelistedMintPda,
+      rewardPool: rewardPoolPda,
+      treasury: treasuryPda,
+      program: program.programId,
+      programData: programData,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+    console.log("addWhitelistedMint transaction signature", tx);
+
+    const whitelisted = await program.account.whitelistedMint.fetch(whitelistedMintPda);
+    assert.ok(whitelisted.mint.equals(mintPda));
+  });
+
   it("Is deposit!", async () => {
     const amount = 100 * LAMPORTS_PER_SOL;
 
@@ -151,8 +419,12 @@ describe("task_2", () => {
       pdaStackAccount: pdaStackAccountPda,
       stackAccount: stackAccountPda,
       mint: mintPda,
+      whitelistedMint: whitelistedMintPda,
+      rewardPool: rewardPoolPda,
       payerAta: userAta,
       stackAccountAta: stackAta,
+      config: configPda,
+      treasury: treasuryPda,
       systemProgram: SystemProgram.programId,
       tokenProgram: TOKEN_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -168,6 +440,285 @@ describe("task_2", () => {
 
   });
 
+  it("Deposit and mint reject zero amounts", async () => {
+    try {
+      await program.methods
+      .depositSpl(new anchor.BN(0))
+      .accounts({
+        payer: user.publicKey,
+        pdaStackAccount: pdaStackAccountPda,
+        stackAccount: stackAccountPda,
+        mint: mintPda,
+        whitelistedMint: whitelistedMintPda,
+        rewardPool: rewardPoolPda,
+        payerAta: userAta,
+        stackAccountAta: stackAta,
+        config: configPda,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+      assert.fail("zero deposit should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "ZeroAmount");
+    }
+
+    try {
+      await program.methods
+      .mintSpl(new anchor.BN(0))
+      .accounts({
+        payer: user.publicKey,
+        mintConfig: mintConfigPda,
+        mint: mintPda,
+        recipient: user.publicKey,
+        recipientAta: userAta,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+      assert.fail("zero mint should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "ZeroAmount");
+    }
+  });
+
+  it("Is reward accrual and claim!", async () => {
+    const rewardVault = await getAssociatedTokenAddress(rewardMintPda, rewardPoolPda, true);
+    const userRewardAta = await getAssociatedTokenAddress(rewardMintPda, user.publicKey);
+
+    // 设置奖励代币和发放速率
+    await program.methods
+    .configureRewardPool(new anchor.BN(1_000_000))
+    .accounts({
+      admin: user.publicKey,
+      rewardPool: rewardPoolPda,
+      rewardMint: rewardMintPda,
+      rewardVault: rewardVault,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+
+    // 铸造奖励代币并注资
+    const fundAmount = 1_000_000_000;
+    await program.methods
+    .mintSpl(new anchor.BN(fundAmount))
+    .accounts({
+      payer: user.publicKey,
+      mintConfig: rewardMintConfigPda,
+      mint: rewardMintPda,
+      recipient: user.publicKey,
+      recipientAta: userRewardAta,
+      config: configPda,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+    await program.methods
+    .fundRewardPool(new anchor.BN(fundAmount))
+    .accounts({
+      admin: user.publicKey,
+      rewardPool: rewardPoolPda,
+      rewardMint: rewardMintPda,
+      adminAta: userRewardAta,
+      rewardVault: rewardVault,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+
+    // 等待奖励累计
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+    const before = await getAccount(program.provider.connection, userRewardAta);
+
+    await program.methods
+    .claimRewards()
+    .accounts({
+      payer: user.publicKey,
+      rewardPool: rewardPoolPda,
+      stackAccount: stackAccountPda,
+      rewardMint: rewardMintPda,
+      rewardVault: rewardVault,
+      userRewardAta: userRewardAta,
+      config: configPda,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+
+    const after = await getAccount(program.provider.connection, userRewardAta);
+    console.log("claimed rewards:", (after.amount - before.amount).toString());
+    assert.isTrue(after.amount > before.amount);
+
+    // 奖励代币设置后不能更换
+    try {
+      await program.methods
+      .configureRewardPool(new anchor.BN(1_000_000))
+      .accounts({
+        admin: user.publicKey,
+        rewardPool: rewardPoolPda,
+        rewardMint: mintPda,
+        rewardVault: await getAssociatedTokenAddress(mintPda, rewardPoolPda, true),
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+      assert.fail("changing the reward mint should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "MintMismatch");
+    }
+
+    // 只能向奖励池记录的金库注资
+    try {
+      await program.methods
+      .fundRewardPool(new anchor.BN(1))
+      .accounts({
+        admin: user.publicKey,
+        rewardPool: rewardPoolPda,
+        rewardMint: rewardMintPda,
+        adminAta: userRewardAta,
+        rewardVault: userRewardAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+      assert.fail("funding a foreign vault should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "VaultMismatch");
+    }
+
+    try {
+      await program.methods
+      .fundRewardPool(new anchor.BN(0))
+      .accounts({
+        admin: user.publicKey,
+        rewardPool: rewardPoolPda,
+        rewardMint: rewardMintPda,
+        adminAta: userRewardAta,
+        rewardVault: rewardVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+      assert.fail("zero funding should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "ZeroAmount");
+    }
+  });
+
+  it("Withdraw rejects a foreign user's ledger and vault", async () => {
+    // 攻击者钱包
+    const attacker = Keypair.generate();
+    const airdropSig = await program.provider.connection.requestAirdrop(attacker.publicKey, LAMPORTS_PER_SOL);
+    await program.provider.connection.confirmTransaction(airdropSig);
+
+    const [attackerStackAccountPda] = PublicKey.findProgramAddressSync(
+      [attacker.publicKey.toBuffer(), mintPda.toBuffer()],
+      program.programId
+    );
+    const [attackerPdaStackAccountPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stack"), attacker.publicKey.toBuffer()],
+      program.programId
+    );
+    const attackerAta = await getAssociatedTokenAddress(mintPda, attacker.publicKey);
+    const attackerStackAta = await getAssociatedTokenAddress(mintPda, attackerPdaStackAccountPda, true);
+
+    // 给攻击者铸币，攻击者质押后拥有自己的数据账户
+    const amount = 10 * LAMPORTS_PER_SOL;
+    await program.methods
+    .mintSpl(new anchor.BN(amount))
+    .accounts({
+      payer: user.publicKey,
+      mintConfig: mintConfigPda,
+      mint: mintPda,
+      recipient: attacker.publicKey,
+      recipientAta: attackerAta,
+      config: configPda,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+
+    await program.methods
+    .depositSpl(new anchor.BN(amount))
+    .accounts({
+      payer: attacker.publicKey,
+      pdaStackAccount: attackerPdaStackAccountPda,
+      stackAccount: attackerStackAccountPda,
+      mint: mintPda,
+      whitelistedMint: whitelistedMintPda,
+      rewardPool: rewardPoolPda,
+      payerAta: attackerAta,
+      stackAccountAta: attackerStackAta,
+      config: configPda,
+      treasury: treasuryPda,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .signers([attacker])
+    .rpc();
+
+    // 使用他人的数据账户
+    try {
+      await program.methods
+      .withdrawSpl()
+      .accounts({
+        payer: attacker.publicKey,
+        pdaStackAccount: attackerPdaStackAccountPda,
+        stackAccount: stackAccountPda,
+        mint: mintPda,
+        rewardPool: rewardPoolPda,
+        userAta: attackerAta,
+        stackAccountAta: attackerStackAta,
+        config: configPda,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .signers([attacker])
+      .rpc();
+      assert.fail("withdraw with a foreign ledger should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "ConstraintSeeds");
+    }
+
+    // 使用他人的质押ATA
+    try {
+      await program.methods
+      .withdrawSpl()
+      .accounts({
+        payer: attacker.publicKey,
+        pdaStackAccount: attackerPdaStackAccountPda,
+        stackAccount: attackerStackAccountPda,
+        mint: mintPda,
+        rewardPool: rewardPoolPda,
+        userAta: attackerAta,
+        stackAccountAta: stackAta,
+        config: configPda,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .signers([attacker])
+      .rpc();
+      assert.fail("withdraw from a foreign vault should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "ConstraintTokenOwner");
+    }
+  });
+
   it("Is withdraw!", async () => {
     console.log("user:", user.publicKey);
     console.log("pdaStackAccount:", pdaStackAccountPda);
@@ -179,8 +730,12 @@ describe("task_2", () => {
       payer: user.publicKey,
       pdaStackAccount: pdaStackAccountPda,
       stackAccount: stackAccountPda,
+      mint: mintPda,
+      rewardPool: rewardPoolPda,
       userAta: userAta,
       stackAccountAta: stackAta,
+      config: configPda,
+      treasury: treasuryPda,
       systemProgram: SystemProgram.programId,
       tokenProgram: TOKEN_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -196,5 +751,1377 @@ describe("task_2", () => {
 
   });
 
+  it("Empty position rejects withdraw, close with pending rewards and empty claims", async () => {
+    const rewardVault = await getAssociatedTokenAddress(rewardMintPda, rewardPoolPda, true);
+    const userRewardAta = await getAssociatedTokenAddress(rewardMintPda, user.publicKey);
+
+    try {
+      await program.methods
+      .withdrawSpl()
+      .accounts({
+        payer: user.publicKey,
+        pdaStackAccount: pdaStackAccountPda,
+        stackAccount: stackAccountPda,
+        mint: mintPda,
+        rewardPool: rewardPoolPda,
+        userAta: userAta,
+        stackAccountAta: stackAta,
+        config: configPda,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+      assert.fail("withdraw from an empty position should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InsufficientBalance");
+    }
+
+    // 提现时结算的奖励尚未领取
+    const position = await program.account.stackAccount.fetch(stackAccountPda);
+    assert.isTrue(position.pendingRewards.toNumber() > 0);
+    try {
+      await program.methods
+      .closePositionSpl()
+      .accounts({
+        payer: user.publicKey,
+        pdaStackAccount: pdaStackAccountPda,
+        stackAccount: stackAccountPda,
+        mint: mintPda,
+        stackAccountAta: stackAta,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+      assert.fail("closing a position with pending rewards should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "UnclaimedRewards");
+    }
+
+    // 领取后仓位余额为0，不再累计奖励
+    const claimAccounts = {
+      payer: user.publicKey,
+      rewardPool: rewardPoolPda,
+      stackAccount: stackAccountPda,
+      rewardMint: rewardMintPda,
+      rewardVault: rewardVault,
+      userRewardAta: userRewardAta,
+      config: configPda,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    };
+    await program.methods.claimRewards().accounts(claimAccounts).rpc();
+    try {
+      await program.methods.claimRewards().accounts(claimAccounts).rpc();
+      assert.fail("claim without pending rewards should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "NoRewardsToClaim");
+    }
+  });
+
+  it("Is deposit and withdraw SOL!", async () => {
+    // SOL 质押账户与 spl 质押PDA使用不同seeds
+    const [solStackPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("sol_stack"), user.publicKey.toBuffer()],
+      program.programId
+    );
+    const [solStackAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("sol_position"), user.publicKey.toBuffer()],
+      program.programId
+    );
+    assert.notOk(solStackPda.equals(pdaStackAccountPda));
+
+    const amount = 0.1 * LAMPORTS_PER_SOL;
+    await program.methods
+    .deposit(new anchor.BN(amount))
+    .accounts({
+      user: user.publicKey,
+      stackAccountPda: solStackPda,
+      stackAccount: solStackAccount,
+      config: configPda,
+      treasury: treasuryPda,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+
+    let position = await program.account.stackAccount.fetch(solStackAccount);
+    assert.strictEqual(position.balance.toNumber(), amount);
+
+    await program.methods
+    .withdraw()
+    .accounts({
+      user: user.publicKey,
+      stackAccountPda: solStackPda,
+      stackAccount: solStackAccount,
+      config: configPda,
+      treasury: treasuryPda,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
 
+    position = await program.account.stackAccount.fetch(solStackAccount);
+    assert.strictEqual(position.balance.toNumber(), 0);
+
+    const solAccounts = {
+      user: user.publicKey,
+      stackAccountPda: solStackPda,
+      stackAccount: solStackAccount,
+      config: configPda,
+      treasury: treasuryPda,
+      systemProgram: SystemProgram.programId,
+    };
+    try {
+      await program.methods.withdraw().accounts(solAccounts).rpc();
+      assert.fail("withdraw from an empty position should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InsufficientBalance");
+    }
+
+    try {
+      await program.methods.deposit(new anchor.BN(0)).accounts(solAccounts).rpc();
+      assert.fail("zero deposit should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "ZeroAmount");
+    }
+
+    await program.methods.deposit(new anchor.BN(amount)).accounts(solAccounts).rpc();
+    try {
+      await program.methods.withdrawAmount(new anchor.BN(amount + 1)).accounts(solAccounts).rpc();
+      assert.fail("withdraw amount above balance should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "WithdrawAmountExceedsBalance");
+    }
+    await program.methods.withdraw().accounts(solAccounts).rpc();
+  });
+
+  it("Is deposit Token-2022 mint with transfer fee!", async () => {
+    const connection = program.provider.connection;
+    const feeBasisPoints = 100; // 1% 转账手续费
+    const decimals = 6;
+
+    // 创建带转账手续费扩展的 Token-2022 mint
+    const mint2022 = Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const lamports = await connection.getMinimumBalanceForRentExemption(mintLen);
+    const createMintTx = new anchor.web3.Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: user.publicKey,
+        newAccountPubkey: mint2022.publicKey,
+        space: mintLen,
+        lamports,
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      createInitializeTransferFeeConfigInstruction(
+        mint2022.publicKey, user.publicKey, user.publicKey, feeBasisPoints, BigInt(1_000_000_000), TOKEN_2022_PROGRAM_ID,
+      ),
+      createInitializeMintInstruction(mint2022.publicKey, decimals, user.publicKey, null, TOKEN_2022_PROGRAM_ID),
+    );
+    await anchor.web3.sendAndConfirmTransaction(connection, createMintTx, [user, mint2022]);
+
+    const userAta2022 = await createAssociatedTokenAccountIdempotent(
+      connection, user, mint2022.publicKey, user.publicKey, {}, TOKEN_2022_PROGRAM_ID,
+    );
+    await mintTo(connection, user, mint2022.publicKey, userAta2022, user, 1_000_000, [], {}, TOKEN_2022_PROGRAM_ID);
+
+    const [whitelisted2022] = PublicKey.findProgramAddressSync(
+      [Buffer.from("whitelist"), mint2022.publicKey.toBuffer()],
+      program.programId
+    );
+    const [rewardPool2022] = PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_pool"), mint2022.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+    .addWhitelistedMint()
+    .accounts({
+      authority: user.publicKey,
+      mint: mint2022.publicKey,
+      whitelistedMint: whitelisted2022,
+      rewardPool: rewardPool2022,
+      treasury: treasuryPda,
+      program: program.programId,
+      programData: programData,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+    })
+    .rpc();
+
+    const [stackAccount2022] = PublicKey.findProgramAddressSync(
+      [user.publicKey.toBuffer(), mint2022.publicKey.toBuffer()],
+      program.programId
+    );
+    const stackAta2022 = await getAssociatedTokenAddress(
+      mint2022.publicKey, pdaStackAccountPda, true, TOKEN_2022_PROGRAM_ID,
+    );
+
+    // 质押，仓位按扣除手续费后的实际到账数量记账
+    const amount = 100_000;
+    await program.methods
+    .depositSpl(new anchor.BN(amount))
+    .accounts({
+      payer: user.publicKey,
+      pdaStackAccount: pdaStackAccountPda,
+      stackAccount: stackAccount2022,
+      mint: mint2022.publicKey,
+      whitelistedMint: whitelisted2022,
+      rewardPool: rewardPool2022,
+      payerAta: userAta2022,
+      stackAccountAta: stackAta2022,
+      config: configPda,
+      treasury: treasuryPda,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+
+    const position = await program.account.stackAccount.fetch(stackAccount2022);
+    const expectedFee = amount * feeBasisPoints / 10_000;
+    assert.strictEqual(position.balance.toNumber(), amount - expectedFee);
+
+    // SPL Token 仓位不受影响
+    const legacyPosition = await program.account.stackAccount.fetch(stackAccountPda);
+    assert.strictEqual(legacyPosition.balance.toNumber(), 0);
+
+    // 仓位有余额时不能关闭
+    try {
+      await program.methods
+      .closePositionSpl()
+      .accounts({
+        payer: user.publicKey,
+        pdaStackAccount: pdaStackAccountPda,
+        stackAccount: stackAccount2022,
+        mint: mint2022.publicKey,
+        stackAccountAta: stackAta2022,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .rpc();
+      assert.fail("closing a non-empty position should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "PositionNotEmpty");
+    }
+  });
+
+  it("Is deposit locked and withdraw refused!", async () => {
+    const amount = 10 * LAMPORTS_PER_SOL;
+    const lockDuration = 3600; // 锁定1小时
+
+    // 锁定时长不能超过4年
+    try {
+      await program.methods
+      .depositSplLocked(new anchor.BN(amount), new anchor.BN(5 * 365 * 24 * 60 * 60))
+      .accounts({
+        payer: user.publicKey,
+        pdaStackAccount: pdaStackAccountPda,
+        stackAccount: stackAccountPda,
+        mint: mintPda,
+        whitelistedMint: whitelistedMintPda,
+        rewardPool: rewardPoolPda,
+        payerAta: userAta,
+        stackAccountAta: stackAta,
+        config: configPda,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+      assert.fail("lock longer than four years should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InvalidLockDuration");
+    }
+
+    await program.methods
+    .depositSplLocked(new anchor.BN(amount), new anchor.BN(lockDuration))
+    .accounts({
+      payer: user.publicKey,
+      pdaStackAccount: pdaStackAccountPda,
+      stackAccount: stackAccountPda,
+      mint: mintPda,
+      whitelistedMint: whitelistedMintPda,
+      rewardPool: rewardPoolPda,
+      payerAta: userAta,
+      stackAccountAta: stackAta,
+      config: configPda,
+      treasury: treasuryPda,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+
+    const position = await program.account.stackAccount.fetch(stackAccountPda);
+    assert.strictEqual(position.lockEnd.sub(position.lockStart).toNumber(), lockDuration);
+
+    // 锁定期内的未锁定充值会被一起锁定，必须改用 depositSplLocked
+    try {
+      await program.methods
+      .depositSpl(new anchor.BN(amount))
+      .accounts({
+        payer: user.publicKey,
+        pdaStackAccount: pdaStackAccountPda,
+        stackAccount: stackAccountPda,
+        mint: mintPda,
+        whitelistedMint: whitelistedMintPda,
+        rewardPool: rewardPoolPda,
+        payerAta: userAta,
+        stackAccountAta: stackAta,
+        config: configPda,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+      assert.fail("flexible deposit into a locked position should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "DepositIntoLockedPosition");
+    }
+
+    // 锁定期内提款失败
+    try {
+      await program.methods
+      .withdrawSpl()
+      .accounts({
+        payer: user.publicKey,
+        pdaStackAccount: pdaStackAccountPda,
+        stackAccount: stackAccountPda,
+        mint: mintPda,
+        rewardPool: rewardPoolPda,
+        userAta: userAta,
+        stackAccountAta: stackAta,
+        config: configPda,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+      assert.fail("withdraw of locked funds should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "PositionLocked");
+    }
+  });
+
+  it("Pause and emergency mode gate deposits, mints and withdrawals", async () => {
+    const [solStackPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("sol_stack"), user.publicKey.toBuffer()],
+      program.programId
+    );
+    const [solStackAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("sol_position"), user.publicKey.toBuffer()],
+      program.programId
+    );
+    const solAccounts = {
+      user: user.publicKey,
+      stackAccountPda: solStackPda,
+      stackAccount: solStackAccount,
+      config: configPda,
+      treasury: treasuryPda,
+      systemProgram: SystemProgram.programId,
+    };
+    const mintAccounts = {
+      payer: user.publicKey,
+      mintConfig: mintConfigPda,
+      mint: mintPda,
+      recipient: user.publicKey,
+      recipientAta: userAta,
+      config: configPda,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    };
+    const withdrawSplAccounts = {
+      payer: user.publicKey,
+      pdaStackAccount: pdaStackAccountPda,
+      stackAccount: stackAccountPda,
+      mint: mintPda,
+      rewardPool: rewardPoolPda,
+      config: configPda,
+      treasury: treasuryPda,
+      userAta: userAta,
+      stackAccountAta: stackAta,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    };
+    const amount = 0.1 * LAMPORTS_PER_SOL;
+    await program.methods.deposit(new anchor.BN(amount)).accounts(solAccounts).rpc();
+
+    // 非管理员不能暂停
+    const stranger = Keypair.generate();
+    try {
+      await program.methods.setPaused(true).accounts({ admin: stranger.publicKey, config: configPda }).signers([stranger]).rpc();
+      assert.fail("non-admin should not pause");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "Unauthorized");
+    }
+
+    // 暂停后充值、铸币和提现都失败
+    await program.methods.setPaused(true).accounts({ admin: user.publicKey, config: configPda }).rpc();
+    const paused = [
+      program.methods.deposit(new anchor.BN(amount)).accounts(solAccounts),
+      program.methods.withdraw().accounts(solAccounts),
+      program.methods.mintSpl(new anchor.BN(1)).accounts(mintAccounts),
+      program.methods.withdrawSpl().accounts(withdrawSplAccounts),
+    ];
+    for (const call of paused) {
+      try {
+        await call.rpc();
+        assert.fail("paused program should reject the instruction");
+      } catch (err) {
+        assert.strictEqual(err.error.errorCode.code, "ProgramPaused");
+      }
+    }
+
+    // 应急模式：允许提现，禁止充值和铸币
+    await program.methods.setPaused(false).accounts({ admin: user.publicKey, config: configPda }).rpc();
+    await program.methods
+    .updateConfig({ emergency: true, depositFeeBps: 0, withdrawFeeBps: 0 })
+    .accounts({ admin: user.publicKey, config: configPda })
+    .rpc();
+    for (const call of [
+      program.methods.deposit(new anchor.BN(amount)).accounts(solAccounts),
+      program.methods.mintSpl(new anchor.BN(1)).accounts(mintAccounts),
+    ]) {
+      try {
+        await call.rpc();
+        assert.fail("emergency mode should reject deposits and mints");
+      } catch (err) {
+        assert.strictEqual(err.error.errorCode.code, "DepositsDisabled");
+      }
+    }
+    await program.methods.withdraw().accounts(solAccounts).rpc();
+    const position = await program.account.stackAccount.fetch(solStackAccount);
+    assert.strictEqual(position.balance.toNumber(), 0);
+
+    // 手续费不能超过 100%
+    try {
+      await program.methods
+      .updateConfig({ emergency: false, depositFeeBps: 0, withdrawFeeBps: 10_001 })
+      .accounts({ admin: user.publicKey, config: configPda })
+      .rpc();
+      assert.fail("fee above 10000 bps should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InvalidFee");
+    }
+
+    await program.methods
+    .updateConfig({ emergency: false, depositFeeBps: 0, withdrawFeeBps: 0 })
+    .accounts({ admin: user.publicKey, config: configPda })
+    .rpc();
+  });
+
+  it("Reward accrual overflow is rejected", async () => {
+    // 新代币的奖励池，单份质押按 u64::MAX 的速率发放
+    const { mint, mintConfig } = await createToken("TT2", 0);
+    const [whitelisted] = PublicKey.findProgramAddressSync(
+      [Buffer.from("whitelist"), mint.toBuffer()],
+      program.programId
+    );
+    const [rewardPool] = PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_pool"), mint.toBuffer()],
+      program.programId
+    );
+    const [stackAccount] = PublicKey.findProgramAddressSync(
+      [user.publicKey.toBuffer(), mint.toBuffer()],
+      program.programId
+    );
+    const ata = await getAssociatedTokenAddress(mint, user.publicKey);
+    const vault = await getAssociatedTokenAddress(mint, pdaStackAccountPda, true);
+    const rewardVault = await getAssociatedTokenAddress(rewardMintPda, rewardPool, true);
+
+    await program.methods
+    .addWhitelistedMint()
+    .accounts({
+      authority: user.publicKey,
+      mint,
+      whitelistedMint: whitelisted,
+      rewardPool,
+      treasury: treasuryPda,
+      program: program.programId,
+      programData: programData,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+    await program.methods
+    .mintSpl(new anchor.BN(1))
+    .accounts({
+      payer: user.publicKey,
+      mintConfig,
+      mint,
+      recipient: user.publicKey,
+      recipientAta: ata,
+      config: configPda,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+    await program.methods
+    .depositSpl(new anchor.BN(1))
+    .accounts({
+      payer: user.publicKey,
+      pdaStackAccount: pdaStackAccountPda,
+      stackAccount,
+      mint,
+      whitelistedMint: whitelisted,
+      rewardPool,
+      payerAta: ata,
+      stackAccountAta: vault,
+      config: configPda,
+      treasury: treasuryPda,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+    await program.methods
+    .configureRewardPool(new anchor.BN("18446744073709551615"))
+    .accounts({
+      admin: user.publicKey,
+      rewardPool,
+      rewardMint: rewardMintPda,
+      rewardVault,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+
+    // 两秒后待领取奖励超出 u64
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    try {
+      await program.methods
+      .claimRewards()
+      .accounts({
+        payer: user.publicKey,
+        rewardPool,
+        stackAccount,
+        rewardMint: rewardMintPda,
+        rewardVault,
+        userRewardAta: await getAssociatedTokenAddress(rewardMintPda, user.publicKey),
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+      assert.fail("overflowing rewards should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "MathOverflow");
+    }
+  });
+
+  it("Fees are routed to the treasury and collected by the admin", async () => {
+    const connection = program.provider.connection;
+    // 新代币的仓位，避免受前面锁仓测试影响
+    const { mint, mintConfig } = await createToken("TT4", 0);
+    const [whitelisted] = PublicKey.findProgramAddressSync(
+      [Buffer.from("whitelist"), mint.toBuffer()],
+      program.programId
+    );
+    const [rewardPool] = PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_pool"), mint.toBuffer()],
+      program.programId
+    );
+    const [stackAccount] = PublicKey.findProgramAddressSync(
+      [user.publicKey.toBuffer(), mint.toBuffer()],
+      program.programId
+    );
+    const ata = await getAssociatedTokenAddress(mint, user.publicKey);
+    const vault = await getAssociatedTokenAddress(mint, pdaStackAccountPda, true);
+    const treasuryAta = await getAssociatedTokenAddress(mint, treasuryPda, true);
+    const tokenBalance = async (account: PublicKey) => Number((await getAccount(connection, account)).amount);
+
+    await program.methods
+    .addWhitelistedMint()
+    .accounts({
+      authority: user.publicKey,
+      mint,
+      whitelistedMint: whitelisted,
+      rewardPool,
+      treasury: treasuryPda,
+      program: program.programId,
+      programData: programData,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+    await program.methods
+    .mintSpl(new anchor.BN(10_000))
+    .accounts({
+      payer: user.publicKey,
+      mintConfig,
+      mint,
+      recipient: user.publicKey,
+      recipientAta: ata,
+      config: configPda,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+
+    const depositSpl = (amount: number) =>
+      program.methods
+      .depositSpl(new anchor.BN(amount))
+      .accounts({
+        payer: user.publicKey,
+        pdaStackAccount: pdaStackAccountPda,
+        stackAccount,
+        mint,
+        whitelistedMint: whitelisted,
+        rewardPool,
+        payerAta: ata,
+        stackAccountAta: vault,
+        config: configPda,
+        treasury: treasuryPda,
+        treasuryAta,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      });
+
+    // 充值 0.3%、提现 1%，向上取整
+    await program.methods
+    .updateConfig({ emergency: false, depositFeeBps: 30, withdrawFeeBps: 100 })
+    .accounts({ admin: user.publicKey, config: configPda })
+    .rpc();
+
+    await depositSpl(1_001).rpc();
+    const position = await program.account.stackAccount.fetch(stackAccount);
+    assert.strictEqual(position.balance.toNumber(), 1_001 - 4);
+    assert.strictEqual(await tokenBalance(treasuryAta), 4);
+
+    await program.methods
+    .withdrawSpl()
+    .accounts({
+      payer: user.publicKey,
+      pdaStackAccount: pdaStackAccountPda,
+      stackAccount,
+      mint,
+      rewardPool,
+      userAta: ata,
+      stackAccountAta: vault,
+      config: configPda,
+      treasury: treasuryPda,
+      treasuryAta,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+    assert.strictEqual(await tokenBalance(treasuryAta), 4 + 10);
+    assert.strictEqual(await tokenBalance(ata), 10_000 - 4 - 10);
+
+    // 扣除手续费后没有余额入账
+    try {
+      await depositSpl(1).rpc();
+      assert.fail("deposit fully consumed by the fee should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "ZeroAmount");
+    }
+
+    // 只有管理员可以提取手续费
+    const collectAccounts = (admin: PublicKey) => ({
+      admin,
+      config: configPda,
+      treasury: treasuryPda,
+      mint,
+      treasuryAta,
+      recipientAta: ata,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    });
+    const stranger = Keypair.generate();
+    try {
+      await program.methods.collectFeesSpl().accounts(collectAccounts(stranger.publicKey)).signers([stranger]).rpc();
+      assert.fail("non-admin should not collect fees");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "Unauthorized");
+    }
+    await program.methods.collectFeesSpl().accounts(collectAccounts(user.publicKey)).rpc();
+    assert.strictEqual(await tokenBalance(treasuryAta), 0);
+    assert.strictEqual(await tokenBalance(ata), 10_000);
+
+    await program.methods
+    .updateConfig({ emergency: false, depositFeeBps: 0, withdrawFeeBps: 0 })
+    .accounts({ admin: user.publicKey, config: configPda })
+    .rpc();
+  });
+
+  it("Share vault mints receipt tokens and shares yield pro-rata", async () => {
+    const connection = program.provider.connection;
+    const { mint, mintConfig } = await createToken("TT5", 0);
+    const [whitelisted] = PublicKey.findProgramAddressSync(
+      [Buffer.from("whitelist"), mint.toBuffer()],
+      program.programId
+    );
+    const [rewardPool] = PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_pool"), mint.toBuffer()],
+      program.programId
+    );
+    const [shareVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("share_vault"), mint.toBuffer()],
+      program.programId
+    );
+    const [receiptMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("receipt"), mint.toBuffer()],
+      program.programId
+    );
+    const ata = await getAssociatedTokenAddress(mint, user.publicKey);
+    const receiptAta = await getAssociatedTokenAddress(receiptMint, user.publicKey);
+    const vaultAta = await getAssociatedTokenAddress(mint, shareVault, true);
+    const treasuryAta = await getAssociatedTokenAddress(mint, treasuryPda, true);
+    const tokenBalance = async (account: PublicKey) => Number((await getAccount(connection, account)).amount);
+
+    await program.methods
+    .addWhitelistedMint()
+    .accounts({
+      authority: user.publicKey,
+      mint,
+      whitelistedMint: whitelisted,
+      rewardPool,
+      treasury: treasuryPda,
+      program: program.programId,
+      programData: programData,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+    const mintTo = (recipient: PublicKey, recipientAta: PublicKey, amount: number) =>
+      program.methods
+      .mintSpl(new anchor.BN(amount))
+      .accounts({
+        payer: user.publicKey,
+        mintConfig,
+        mint,
+        recipient,
+        recipientAta,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    await mintTo(user.publicKey, ata, 20_000);
+
+    await program.methods
+    .initializeShareVault()
+    .accounts({
+      admin: user.publicKey,
+      config: configPda,
+      mint,
+      whitelistedMint: whitelisted,
+      shareVault,
+      receiptMint,
+      vaultAta,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+
+    const depositShares = (amount: number) =>
+      program.methods
+      .depositShares(new anchor.BN(amount))
+      .accounts({
+        payer: user.publicKey,
+        mint,
+        whitelistedMint: whitelisted,
+        config: configPda,
+        shareVault,
+        receiptMint,
+        payerAta: ata,
+        payerReceiptAta: receiptAta,
+        vaultAta,
+        treasury: treasuryPda,
+        treasuryAta,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      });
+    const exchangeRate = () =>
+      program.methods
+      .exchangeRate()
+      .accounts({ mint, shareVault, vaultAta, tokenProgram: TOKEN_PROGRAM_ID })
+      .view();
+
+    // 首次充值必须超过永久锁定的 1000 份额
+    try {
+      await depositShares(1_000).rpc();
+      assert.fail("first deposit below the locked shares should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "DepositTooSmall");
+    }
+    await depositShares(10_000).rpc();
+    assert.strictEqual(await tokenBalance(receiptAta), 9_000);
+
+    // 直接转入金库的收益按份额分享
+    await mintTo(shareVault, vaultAta, 5_000);
+    const rate = await exchangeRate();
+    assert.strictEqual(rate.totalAssets.toNumber(), 15_000);
+    assert.strictEqual(rate.totalShares.toNumber(), 10_000);
+    assert.strictEqual(rate.assetsPerShare.toNumber(), 1_499_950_004);
+
+    // 3000 * 10001 / 15001 向下取整
+    await depositShares(3_000).rpc();
+    assert.strictEqual(await tokenBalance(receiptAta), 11_000);
+
+    await program.methods
+    .withdrawShares(new anchor.BN(11_000))
+    .accounts({
+      payer: user.publicKey,
+      mint,
+      config: configPda,
+      shareVault,
+      receiptMint,
+      payerReceiptAta: receiptAta,
+      userAta: ata,
+      vaultAta,
+      treasury: treasuryPda,
+      treasuryAta,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+    // 11000 * 18001 / 12001 向下取整，锁定份额对应的资产留在金库
+    assert.strictEqual(await tokenBalance(receiptAta), 0);
+    assert.strictEqual(await tokenBalance(ata), 20_000 - 13_000 + 16_499);
+    const vault = await program.account.shareVault.fetch(shareVault);
+    assert.strictEqual(vault.totalShares.toNumber(), 1_000);
+    assert.strictEqual(await tokenBalance(vaultAta), 18_000 - 16_499);
+  });
+
+  it("Native SOL is wrapped on deposit and unwrapped on withdraw", async () => {
+    const connection = program.provider.connection;
+    const [whitelisted] = PublicKey.findProgramAddressSync(
+      [Buffer.from("whitelist"), NATIVE_MINT.toBuffer()],
+      program.programId
+    );
+    const [rewardPool] = PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_pool"), NATIVE_MINT.toBuffer()],
+      program.programId
+    );
+    const [stackAccount] = PublicKey.findProgramAddressSync(
+      [user.publicKey.toBuffer(), NATIVE_MINT.toBuffer()],
+      program.programId
+    );
+    const [unwrapAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("unwrap"), user.publicKey.toBuffer()],
+      program.programId
+    );
+    const vault = await getAssociatedTokenAddress(NATIVE_MINT, pdaStackAccountPda, true);
+    const treasuryAta = await getAssociatedTokenAddress(NATIVE_MINT, treasuryPda, true);
+    const amount = LAMPORTS_PER_SOL / 2;
+
+    await program.methods
+    .addWhitelistedMint()
+    .accounts({
+      authority: user.publicKey,
+      mint: NATIVE_MINT,
+      whitelistedMint: whitelisted,
+      rewardPool,
+      treasury: treasuryPda,
+      program: program.programId,
+      programData: programData,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+
+    await program.methods
+    .depositSplNative(new anchor.BN(amount))
+    .accounts({
+      payer: user.publicKey,
+      pdaStackAccount: pdaStackAccountPda,
+      stackAccount,
+      mint: NATIVE_MINT,
+      whitelistedMint: whitelisted,
+      rewardPool,
+      config: configPda,
+      stackAccountAta: vault,
+      treasury: treasuryPda,
+      treasuryAta,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+    const position = await program.account.stackAccount.fetch(stackAccount);
+    assert.strictEqual(position.balance.toNumber(), amount);
+    assert.strictEqual(Number((await getAccount(connection, vault)).amount), amount);
+
+    const before = await connection.getBalance(user.publicKey);
+    await program.methods
+    .withdrawSplNative()
+    .accounts({
+      payer: user.publicKey,
+      pdaStackAccount: pdaStackAccountPda,
+      stackAccount,
+      mint: NATIVE_MINT,
+      rewardPool,
+      config: configPda,
+      stackAccountAta: vault,
+      unwrapAccount,
+      treasury: treasuryPda,
+      treasuryAta,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+    // 解包后的 SOL 回到钱包（扣除交易手续费），临时 WSOL 账户已关闭
+    const after = await connection.getBalance(user.publicKey);
+    assert.isAbove(after - before, amount - 10_000);
+    assert.strictEqual(Number((await getAccount(connection, vault)).amount), 0);
+    assert.isNull(await connection.getAccountInfo(unwrapAccount));
+  });
+
+  it("Token metadata can be updated until frozen", async () => {
+    const connection = program.provider.connection;
+    const { mint, mintConfig } = await createToken("TT7", 0);
+    const [metadata] = PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID,
+    );
+    const accounts = (admin: PublicKey) => ({
+      admin,
+      config: configPda,
+      mintConfig,
+      mint,
+      metadata,
+      tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+    });
+    const params = {
+      name: "Renamed Token",
+      symbol: "RNM",
+      uri: "https://example.com/rnm.json",
+      creators: [{ address: user.publicKey, share: 100 }],
+    };
+
+    // 非管理员不能修改元数据
+    const stranger = Keypair.generate();
+    const airdropSig = await connection.requestAirdrop(stranger.publicKey, LAMPORTS_PER_SOL);
+    await connection.confirmTransaction(airdropSig);
+    try {
+      await program.methods
+      .updateTokenMetadata(params)
+      .accounts(accounts(stranger.publicKey))
+      .signers([stranger])
+      .rpc();
+      assert.fail("metadata update by a non-admin should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "Unauthorized");
+    }
+
+    await program.methods.updateTokenMetadata(params).accounts(accounts(user.publicKey)).rpc();
+    // 元数据字段按固定长度补 \0，比较前去掉
+    let data = await Metadata.fromAccountAddress(connection, metadata);
+    assert.strictEqual(data.data.name.replace(/\0/g, ""), params.name);
+    assert.strictEqual(data.data.symbol.replace(/\0/g, ""), params.symbol);
+    assert.strictEqual(data.data.uri.replace(/\0/g, ""), params.uri);
+    assert.isTrue(data.isMutable);
+
+    // 冻结后元数据不可再修改
+    await program.methods.freezeMetadata().accounts(accounts(user.publicKey)).rpc();
+    data = await Metadata.fromAccountAddress(connection, metadata);
+    assert.isFalse(data.isMutable);
+    try {
+      await program.methods.updateTokenMetadata(params).accounts(accounts(user.publicKey)).rpc();
+      assert.fail("metadata update after freeze should fail");
+    } catch (err) {
+      assert.include(String(err), "custom program error");
+    }
+  });
+
+  it("Frozen token accounts cannot deposit until thawed", async () => {
+    const connection = program.provider.connection;
+    const freezeAccounts = {
+      admin: user.publicKey,
+      config: configPda,
+      mintConfig: mintConfigPda,
+      mint: mintPda,
+      tokenAccount: userAta,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    // TT1 仓位仍在锁定期内，只能追加锁仓
+    const deposit = () =>
+      program.methods
+      .depositSplLocked(new anchor.BN(1000), new anchor.BN(60))
+      .accounts({
+        payer: user.publicKey,
+        pdaStackAccount: pdaStackAccountPda,
+        stackAccount: stackAccountPda,
+        mint: mintPda,
+        whitelistedMint: whitelistedMintPda,
+        rewardPool: rewardPoolPda,
+        payerAta: userAta,
+        stackAccountAta: stackAta,
+        config: configPda,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    await program.methods.freezeAccount().accounts(freezeAccounts).rpc();
+    assert.isTrue((await getAccount(connection, userAta)).isFrozen);
+    try {
+      await deposit();
+      assert.fail("deposit from a frozen account should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "AccountFrozen");
+    }
+
+    await program.methods.thawAccount().accounts(freezeAccounts).rpc();
+    assert.isFalse((await getAccount(connection, userAta)).isFrozen);
+    await deposit();
+  });
+
+  it("mintSpl enforces the supply cap and revokeMintAuthority stops minting", async () => {
+    const connection = program.provider.connection;
+    const { mint, mintConfig } = await createToken("TT8", 0, new anchor.BN(1000), new anchor.BN(600));
+    const ata = await getAssociatedTokenAddress(mint, user.publicKey);
+    const mintTo = (amount: number) =>
+      program.methods
+      .mintSpl(new anchor.BN(amount))
+      .accounts({
+        payer: user.publicKey,
+        mintConfig,
+        mint,
+        recipient: user.publicKey,
+        recipientAta: ata,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    // 同一个 epoch 内最多铸造 600
+    await mintTo(600);
+    try {
+      await mintTo(1);
+      assert.fail("minting past the epoch emission should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "EpochEmissionExceeded");
+    }
+
+    // 销毁不会释放本 epoch 的铸币额度
+    await program.methods
+    .burnSpl(new anchor.BN(100))
+    .accounts({ holder: user.publicKey, mintConfig, mint, holderAta: ata, tokenProgram: TOKEN_PROGRAM_ID })
+    .rpc();
+    try {
+      await mintTo(1);
+      assert.fail("burning should not free epoch emission");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "EpochEmissionExceeded");
+    }
+    assert.strictEqual((await program.account.mintConfig.fetch(mintConfig)).epochMinted.toNumber(), 600);
+
+    await program.methods
+    .revokeMintAuthority()
+    .accounts({ authority: user.publicKey, mintConfig, mint, tokenProgram: TOKEN_PROGRAM_ID })
+    .rpc();
+    const mintAccount = await getMint(connection, mint);
+    assert.isNull(mintAccount.mintAuthority);
+    assert.strictEqual(Number(mintAccount.supply), 500);
+    try {
+      await mintTo(1);
+      assert.fail("minting after revocation should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "MintingRevoked");
+    }
+  });
+
+  it("Holders burn their tokens and the admin burns vault surplus", async () => {
+    const connection = program.provider.connection;
+    const supply = async () => Number((await getMint(connection, mintPda)).supply);
+    const before = await supply();
+
+    await program.methods
+    .burnSpl(new anchor.BN(100))
+    .accounts({
+      holder: user.publicKey,
+      mintConfig: mintConfigPda,
+      mint: mintPda,
+      holderAta: userAta,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+    assert.strictEqual(await supply(), before - 100);
+
+    // 直接转入质押ATA的代币不属于仓位，管理员只能销毁这部分
+    await transfer(connection, user, userAta, stackAta, user, 50);
+    const position = await program.account.stackAccount.fetch(stackAccountPda);
+    const surplus = Number((await getAccount(connection, stackAta)).amount) - position.balance.toNumber();
+    const burnFromVault = (amount: number) =>
+      program.methods
+      .burnFromVault(new anchor.BN(amount))
+      .accounts({
+        admin: user.publicKey,
+        config: configPda,
+        user: user.publicKey,
+        pdaStackAccount: pdaStackAccountPda,
+        stackAccount: stackAccountPda,
+        mintConfig: mintConfigPda,
+        mint: mintPda,
+        vaultAta: stackAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    try {
+      await burnFromVault(surplus + 1);
+      assert.fail("burning tokens owed to the position should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "BurnExceedsSurplus");
+    }
+    await burnFromVault(surplus);
+
+    assert.strictEqual(await supply(), before - 100 - surplus);
+    const mintConfig = await program.account.mintConfig.fetch(mintConfigPda);
+    assert.isAtLeast(mintConfig.burned.toNumber(), 100 + surplus);
+    assert.strictEqual(Number((await getAccount(connection, stackAta)).amount), position.balance.toNumber());
+  });
+
+  it("New positions use the current layout and need no migration", async () => {
+    const position = await program.account.stackAccount.fetch(stackAccountPda);
+    assert.strictEqual(position.version, 3);
+    try {
+      await program.methods
+      .migratePosition(mintPda)
+      .accounts({
+        user: user.publicKey,
+        stackAccount: stackAccountPda,
+        rewardPool: rewardPoolPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+      assert.fail("migrating a current position should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "PositionAlreadyMigrated");
+    }
+  });
+
+  it("Admin and mint authority handover take two steps", async () => {
+    const newAdmin = Keypair.generate();
+    const airdropSig = await program.provider.connection.requestAirdrop(newAdmin.publicKey, LAMPORTS_PER_SOL);
+    await program.provider.connection.confirmTransaction(airdropSig);
+
+    await program.methods
+    .proposeAdmin(newAdmin.publicKey)
+    .accounts({ admin: user.publicKey, config: configPda })
+    .rpc();
+    // 只有被提名的账户可以接受
+    try {
+      await program.methods
+      .acceptAdmin()
+      .accounts({ pendingAdmin: user.publicKey, config: configPda })
+      .rpc();
+      assert.fail("only the pending admin can accept");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "Unauthorized");
+    }
+    await program.methods
+    .acceptAdmin()
+    .accounts({ pendingAdmin: newAdmin.publicKey, config: configPda })
+    .signers([newAdmin])
+    .rpc();
+    let config = await program.account.config.fetch(configPda);
+    assert.strictEqual(config.admin.toBase58(), newAdmin.publicKey.toBase58());
+
+    // 交还管理员权限
+    await program.methods
+    .proposeAdmin(user.publicKey)
+    .accounts({ admin: newAdmin.publicKey, config: configPda })
+    .signers([newAdmin])
+    .rpc();
+    await program.methods
+    .acceptAdmin()
+    .accounts({ pendingAdmin: user.publicKey, config: configPda })
+    .rpc();
+    config = await program.account.config.fetch(configPda);
+    assert.strictEqual(config.admin.toBase58(), user.publicKey.toBase58());
+    assert.strictEqual(config.pendingAdmin.toBase58(), PublicKey.default.toBase58());
+
+    // 铸币权限移交给 newAdmin
+    const { mint, mintConfig } = await createToken("TT3", 0);
+    await program.methods
+    .proposeMintAuthority(newAdmin.publicKey)
+    .accounts({ authority: user.publicKey, mintConfig })
+    .rpc();
+    await program.methods
+    .acceptMintAuthority()
+    .accounts({ pendingAuthority: newAdmin.publicKey, mintConfig })
+    .signers([newAdmin])
+    .rpc();
+    const mintConfigAccount = await program.account.mintConfig.fetch(mintConfig);
+    assert.strictEqual(mintConfigAccount.authority.toBase58(), newAdmin.publicKey.toBase58());
+
+    try {
+      await program.methods
+      .mintSpl(new anchor.BN(1))
+      .accounts({
+        payer: user.publicKey,
+        mintConfig,
+        mint,
+        recipient: user.publicKey,
+        recipientAta: await getAssociatedTokenAddress(mint, user.publicKey),
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+      assert.fail("previous mint authority should not mint");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "Unauthorized");
+    }
+  });
+
+  it("Multisig executes proposals after reaching the threshold", async () => {
+    const member = Keypair.generate();
+    const airdropSig = await program.provider.connection.requestAirdrop(member.publicKey, LAMPORTS_PER_SOL);
+    await program.provider.connection.confirmTransaction(airdropSig);
+
+    const [multisigPda] = PublicKey.findProgramAddressSync([Buffer.from("multisig")], program.programId);
+    const [multisigSigner] = PublicKey.findProgramAddressSync([Buffer.from("multisig_signer")], program.programId);
+    const proposalPda = (index: number) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), new anchor.BN(index).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+
+    // 提交提案：目标指令的账户和数据
+    const propose = async (index: number, ix: anchor.web3.TransactionInstruction, proposer: Keypair) => {
+      await program.methods
+      .createProposal({
+        programId: ix.programId,
+        accounts: ix.keys.map((k) => ({ pubkey: k.pubkey, isSigner: k.isSigner, isWritable: k.isWritable })),
+        data: ix.data,
+      })
+      .accounts({
+        proposer: proposer.publicKey,
+        multisig: multisigPda,
+        proposal: proposalPda(index),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers(proposer === user ? [] : [proposer])
+      .rpc();
+    };
+    const approve = (index: number, signer: Keypair) =>
+      program.methods
+      .approveProposal()
+      .accounts({ signer: signer.publicKey, multisig: multisigPda, proposal: proposalPda(index) })
+      .signers(signer === user ? [] : [signer])
+      .rpc();
+    // remaining_accounts：目标指令的账户（多签签名PDA除外）和目标程序
+    const execute = (index: number, ix: anchor.web3.TransactionInstruction) =>
+      program.methods
+      .executeProposal()
+      .accounts({ multisig: multisigPda, proposal: proposalPda(index), multisigSigner })
+      .remainingAccounts([
+        ...ix.keys
+          .filter((k) => !k.pubkey.equals(multisigSigner))
+          .map((k) => ({ pubkey: k.pubkey, isSigner: false, isWritable: k.isWritable })),
+        { pubkey: ix.programId, isSigner: false, isWritable: false },
+      ])
+      .rpc();
+
+    // 2-of-2 多签接管管理员权限
+    await program.methods
+    .createMultisig([user.publicKey, member.publicKey], 2)
+    .accounts({
+      admin: user.publicKey,
+      config: configPda,
+      multisig: multisigPda,
+      multisigSigner,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+    await program.methods
+    .proposeAdmin(multisigSigner)
+    .accounts({ admin: user.publicKey, config: configPda })
+    .rpc();
+
+    const accept = await program.methods
+    .acceptAdmin()
+    .accounts({ pendingAdmin: multisigSigner, config: configPda })
+    .instruction();
+    await propose(0, accept, user);
+    try {
+      await execute(0, accept);
+      assert.fail("execution below threshold should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "NotEnoughApprovals");
+    }
+    await approve(0, member);
+    await execute(0, accept);
+    let config = await program.account.config.fetch(configPda);
+    assert.strictEqual(config.admin.toBase58(), multisigSigner.toBase58());
+    try {
+      await execute(0, accept);
+      assert.fail("proposal can only be executed once");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "ProposalExecuted");
+    }
+
+    // 原管理员不能再直接修改配置
+    try {
+      await program.methods
+      .setPaused(true)
+      .accounts({ admin: user.publicKey, config: configPda })
+      .rpc();
+      assert.fail("former admin should be rejected");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "Unauthorized");
+    }
+
+    // 通过提案把管理员交还给钱包，后续测试仍可使用
+    const handBack = await program.methods
+    .proposeAdmin(user.publicKey)
+    .accounts({ admin: multisigSigner, config: configPda })
+    .instruction();
+    await propose(1, handBack, member);
+    await approve(1, user);
+    await execute(1, handBack);
+    await program.methods
+    .acceptAdmin()
+    .accounts({ pendingAdmin: user.publicKey, config: configPda })
+    .rpc();
+    config = await program.account.config.fetch(configPda);
+    assert.strictEqual(config.admin.toBase58(), user.publicKey.toBase58());
+
+    const proposal = await program.account.proposal.fetch(proposalPda(1));
+    assert.isTrue(proposal.executed);
+  });
+
+  // 注册表写满后无法再创建代币，放在最后执行
+  it("createToken rejects a full registry", async () => {
+    const registry = await program.account.tokenRegistry.fetch(registryPda);
+    for (let i = registry.mints.length; i < 64; i++) {
+      await createToken(`R${i}`, 0);
+    }
+
+    try {
+      await createToken("RFULL", 0);
+      assert.fail("createToken on a full registry should fail");
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "RegistryFull");
+    }
+  });
 });